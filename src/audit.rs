@@ -0,0 +1,69 @@
+/*! Append-only audit log with one JSON object per line, e.g.
+ *
+ *     {"recorded":"2024-01-15T05:31:12+01:00","date":"2024-01-15","event":"early-clock-in",...}
+ */
+use chrono::{DateTime, Local, NaiveDate, NaiveTime};
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event {
+    /// Clock-in before the earliest allowed start, counted from the clamped time instead
+    EarlyClockIn {
+        actual: NaiveTime,
+        clamped: NaiveTime,
+    },
+}
+
+#[derive(Serialize)]
+pub struct Note {
+    pub recorded: DateTime<Local>,
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+pub fn append(path: &Path, note: &Note) {
+    let mut file = match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(file) => file,
+        Err(e) => panic!("Cannot open audit log {}: {}", path.display(), e),
+    };
+    if let Err(e) = writeln!(file, "{}", serde_json::to_string(note).unwrap()) {
+        panic!("Cannot write audit log {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::fs;
+
+    #[test]
+    fn early_clock_in_appends_a_line() {
+        let path =
+            std::env::temp_dir().join(format!("time_rust-audit-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let note = Note {
+            recorded: Local.with_ymd_and_hms(2024, 1, 15, 5, 31, 12).unwrap(),
+            date: NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            event: Event::EarlyClockIn {
+                actual: NaiveTime::from_hms_opt(5, 31, 0).unwrap(),
+                clamped: NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+            },
+        };
+        append(&path, &note);
+        append(&path, &note);
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(
+            "\"date\":\"2024-01-15\",\"event\":\"early-clock-in\",\
+             \"actual\":\"05:31:00\",\"clamped\":\"06:00:00\"}"
+        ));
+    }
+}
@@ -0,0 +1,325 @@
+/*! Rules for the break deducted when no breaks are given: the built-in 30/45 minutes, a flat
+ * break, or a table of required breaks by worked duration, one range per line, e.g.
+ *
+ *     0-6h: 0
+ *     6-9h: 30m
+ *     9h+: 45m
+ *
+ * Ranges include their lower and exclude their upper bound.  They have to start at 0 and follow
+ * each other without gaps or overlaps; only the last one may be open-ended.
+ */
+use crate::format_duration;
+use crate::i18n::trf;
+use chrono::Duration;
+use std::cmp::max;
+use std::fs;
+use std::path::Path;
+
+pub trait BreakPolicy {
+    /** Break required for the given worked duration
+     */
+    fn required(&self, worked: Duration) -> Duration;
+
+    /** Largest break the policy may require
+     */
+    fn longest(&self) -> Duration;
+
+    /** Why the given worked duration requires the break it does, for --explain-breaks
+     */
+    fn explain(&self, worked: Duration) -> String;
+
+    /** Break to take for working the given duration, the break itself counting toward the
+     * duration its rule is chosen by, as it does for a day
+     */
+    fn required_for_work(&self, work: Duration) -> Duration {
+        let mut required = self.required(work);
+        loop {
+            let next = self.required(work + required);
+            if next <= required {
+                return required;
+            }
+            required = next;
+        }
+    }
+}
+
+/** Built-in rule: a short break, the long one once present for the threshold; by default
+ * 30 minutes, 45 from 9:30
+ */
+pub struct Standard {
+    pub short: Duration,
+    pub long: Duration,
+    pub threshold: Duration,
+}
+
+impl Default for Standard {
+    fn default() -> Standard {
+        Standard {
+            short: Duration::try_minutes(30).unwrap(),
+            long: Duration::try_minutes(45).unwrap(),
+            threshold: Duration::try_hours(9).unwrap() + Duration::try_minutes(30).unwrap(),
+        }
+    }
+}
+
+impl BreakPolicy for Standard {
+    fn required(&self, worked: Duration) -> Duration {
+        if worked >= self.threshold {
+            self.long
+        } else {
+            self.short
+        }
+    }
+
+    fn longest(&self) -> Duration {
+        max(self.short, self.long)
+    }
+
+    fn explain(&self, worked: Duration) -> String {
+        trf(
+            if worked >= self.threshold {
+                "{} reaches the threshold of {}: default break of {}"
+            } else {
+                "{} is below the threshold of {}: default break of {}"
+            },
+            &[
+                &format_duration(&worked),
+                &format_duration(&self.threshold),
+                &format_duration(&self.required(worked)),
+            ],
+        )
+    }
+}
+
+/** The same break however long the day
+ */
+pub struct Flat(pub Duration);
+
+impl BreakPolicy for Flat {
+    fn required(&self, _worked: Duration) -> Duration {
+        self.0
+    }
+
+    fn longest(&self) -> Duration {
+        self.0
+    }
+
+    fn explain(&self, _worked: Duration) -> String {
+        trf("flat break of {}", &[&format_duration(&self.0)])
+    }
+}
+
+/** Policy by name: default, de-default (German working hours act), none, or flat<minutes>;
+ * none for an unknown name
+ */
+pub fn named(name: &str) -> Option<Box<dyn BreakPolicy>> {
+    match name {
+        "default" => Some(Box::new(Standard::default())),
+        "de-default" => Some(Box::new(BreakTable::parse("0-6h: 0\n6-9h: 30m\n9h+: 45m"))),
+        "none" => Some(Box::new(Flat(Duration::zero()))),
+        _ => match name.strip_prefix("flat").map(|m| m.parse::<u32>()) {
+            Some(Ok(minutes)) => Some(Box::new(Flat(Duration::try_minutes(minutes as i64)?))),
+            _ => None,
+        },
+    }
+}
+
+struct Row {
+    from: Duration,
+    /// None for the open-ended last row
+    to: Option<Duration>,
+    required: Duration,
+}
+
+pub struct BreakTable {
+    rows: Vec<Row>,
+}
+
+/** Parse a duration such as 0, 45m, 6h, 1h30m, or 6:30
+ */
+fn parse_amount(input: &str) -> Duration {
+    let input = input.trim();
+    if input.contains(':') {
+        return crate::create_duration(input)
+            .unwrap_or_else(|e| panic!("Invalid duration in break policy: {}.  Stop!", e));
+    }
+    let invalid = || panic!("Invalid duration '{}' in break policy.  Stop!", input);
+    let (hours, minutes) = match input.split_once('h') {
+        Some((hours, minutes)) => (hours, minutes.trim_end_matches('m')),
+        None => ("", input.trim_end_matches('m')),
+    };
+    let hours: i64 = if hours.is_empty() {
+        0
+    } else {
+        hours.parse().unwrap_or_else(|_| invalid())
+    };
+    let minutes: i64 = if minutes.is_empty() {
+        0
+    } else {
+        minutes.parse().unwrap_or_else(|_| invalid())
+    };
+    Duration::try_hours(hours).unwrap() + Duration::try_minutes(minutes).unwrap()
+}
+
+fn parse_row(line: &str) -> Row {
+    let (range, required) = match line.split_once(':') {
+        Some(parts) => parts,
+        None => panic!("Invalid break policy row '{}'.  Stop!", line),
+    };
+    let range = range.trim();
+    let (from, to) = match range.strip_suffix('+') {
+        Some(from) => (parse_amount(from), None),
+        None => match range.split_once('-') {
+            // A unit given only once applies to both bounds, as in 6-9h
+            Some((from, to)) => {
+                let unit = to.trim_start_matches(|c: char| c.is_ascii_digit());
+                let from = if from.ends_with(['h', 'm']) || from.trim() == "0" {
+                    from.to_owned()
+                } else {
+                    format!("{}{}", from, unit)
+                };
+                (parse_amount(&from), Some(parse_amount(to)))
+            }
+            None => panic!("Invalid break policy range '{}'.  Stop!", range),
+        },
+    };
+    Row {
+        from,
+        to,
+        required: parse_amount(required),
+    }
+}
+
+impl BreakTable {
+    pub fn parse(content: &str) -> BreakTable {
+        let rows: Vec<Row> = content
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(parse_row)
+            .collect();
+
+        let mut expected_from = Duration::zero();
+        for (i, row) in rows.iter().enumerate() {
+            if row.from != expected_from {
+                panic!(
+                    "Break policy ranges must be contiguous, expected a range starting at {}.  Stop!",
+                    format_duration(&expected_from)
+                );
+            }
+            match row.to {
+                Some(to) if to <= row.from => {
+                    panic!("Break policy range ends before it starts.  Stop!")
+                }
+                Some(to) => expected_from = to,
+                None if i + 1 < rows.len() => {
+                    panic!("Only the last break policy range may be open-ended.  Stop!")
+                }
+                None => {}
+            }
+        }
+        if rows.is_empty() {
+            panic!("Empty break policy.  Stop!");
+        }
+        BreakTable { rows }
+    }
+
+    pub fn load(path: &Path) -> BreakTable {
+        match fs::read_to_string(path) {
+            Ok(content) => BreakTable::parse(&content),
+            Err(e) => panic!("Cannot read break policy {}: {}", path.display(), e),
+        }
+    }
+
+    fn row(&self, worked: Duration) -> &Row {
+        match self
+            .rows
+            .iter()
+            .find(|row| worked >= row.from && row.to.is_none_or(|to| worked < to))
+        {
+            Some(row) => row,
+            None => panic!(
+                "Worked duration {} not covered by the break policy.  Stop!",
+                format_duration(&worked)
+            ),
+        }
+    }
+}
+
+impl BreakPolicy for BreakTable {
+    fn required(&self, worked: Duration) -> Duration {
+        self.row(worked).required
+    }
+
+    fn longest(&self) -> Duration {
+        self.rows.iter().map(|row| row.required).max().unwrap()
+    }
+
+    fn explain(&self, worked: Duration) -> String {
+        let row = self.row(worked);
+        let required = format_duration(&row.required);
+        let from = format_duration(&row.from);
+        match row.to {
+            Some(to) => trf(
+                "the break policy requires {} from {} up to {}",
+                &[&required, &from, &format_duration(&to)],
+            ),
+            None => trf(
+                "the break policy requires {} from {} on",
+                &[&required, &from],
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hours(h: i64) -> Duration {
+        Duration::try_hours(h).unwrap()
+    }
+
+    #[test]
+    fn standard_default() {
+        let policy = Standard::default();
+        assert_eq!(
+            policy.required(hours(8)),
+            Duration::try_minutes(30).unwrap()
+        );
+        assert_eq!(
+            policy.required(hours(10)),
+            Duration::try_minutes(45).unwrap()
+        );
+    }
+
+    #[test]
+    fn break_for_work_counts_toward_the_threshold() {
+        let policy = Standard::default();
+        assert_eq!(policy.required_for_work(hours(8)), policy.short);
+        // 9:00 of work and the short break make 9:30 present
+        assert_eq!(policy.required_for_work(hours(9)), policy.long);
+    }
+
+    #[test]
+    fn standard_custom_threshold() {
+        let policy = Standard {
+            threshold: crate::create_duration("6:00").unwrap(),
+            ..Standard::default()
+        };
+        assert_eq!(policy.required(hours(10)), policy.long);
+        assert_eq!(policy.required(hours(5)), policy.short);
+    }
+
+    #[test]
+    fn table_row_by_worked_duration() {
+        let table = BreakTable::parse("0-6h: 0\n6-9h: 30m\n9h+: 45m");
+        let at = |worked: &str| table.required(crate::create_duration(worked).unwrap());
+        assert_eq!(at("5:59"), Duration::zero());
+        assert_eq!(at("6:00"), Duration::try_minutes(30).unwrap());
+        assert_eq!(at("8:59"), Duration::try_minutes(30).unwrap());
+        assert_eq!(at("9:00"), Duration::try_minutes(45).unwrap());
+        assert_eq!(at("12:00"), Duration::try_minutes(45).unwrap());
+        assert_eq!(table.longest(), Duration::try_minutes(45).unwrap());
+    }
+}
@@ -0,0 +1,184 @@
+/*! Optional TOML configuration, by default $XDG_CONFIG_HOME/time_rust/config.toml in ~/.config,
+ * e.g. the working contract:
+ *
+ *     weekly-goal = "38.5"
+ *     min-start = "6:00"
+ *     break-short = "0:30"
+ *     break-long = "0:45"
+ *     break-threshold = "9:30"
+ *     max-daily = "10:00"
+ *     projections = "countdown"
+ *     holidays = "de"
+ *     starting-balance = "-2:30"
+ *     notify = true
+ *     notify-on = "goal,max"
+ *     import-ics = "/home/me/calendar.ics"
+ *     export-columns = "date=Datum,start=Beginn,end=Ende,hours=Stunden"
+ *     rounding = "15"
+ *     rounding-mode = "favor"
+ *     rounding-to = "stamps"
+ *     lang = "de"
+ *
+ *     [goal-overrides]
+ *     monday-thursday = "8:30"
+ *     friday = "4:00"
+ *     saturday-sunday = "0:00"
+ *     2024-12-24 = "2:00"
+ *
+ *     [targets.layered]
+ *     minimum = "6:00"
+ *     contractual = "7:48"
+ *     stretch = "9:00"
+ *
+ * The plain settings take the values of the command line arguments of the same name, which beat
+ * them.  A date override beats a weekday override, which beats a range of weekdays; all beat the
+ * goal derived from the weekly goal, also in reports.  Named lists of targets are only reported
+ * on, see --targets; a [rules] table sets the break rules checked, see --rules, and a [push] table
+ * the services of the push subcommand.
+ */
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Config {
+    pub weekly_goal: Option<String>,
+    pub min_start: Option<String>,
+    pub break_short: Option<String>,
+    pub break_long: Option<String>,
+    pub break_threshold: Option<String>,
+    pub max_daily: Option<String>,
+    pub format: Option<String>,
+    pub projections: Option<String>,
+    pub round_mode: Option<String>,
+    pub rounding: Option<String>,
+    pub rounding_mode: Option<String>,
+    pub rounding_to: Option<String>,
+    pub template: Option<String>,
+    /// Language of the output, en or de, as with --lang
+    pub lang: Option<String>,
+    pub holidays: Option<String>,
+    /// Vacation file instead of $XDG_DATA_HOME/time_rust/days-off
+    pub days_off: Option<String>,
+    pub starting_balance: Option<String>,
+    /// Send desktop notifications under --watch, as with --notify
+    pub notify: bool,
+    pub notify_on: Option<String>,
+    pub notify_lead: Option<String>,
+    /// Calendar file whose events of the day give breaks and meetings, as with --import-ics
+    pub import_ics: Option<String>,
+    /// Column layout of the export subcommand, as with its --columns
+    pub export_columns: Option<String>,
+    /// Daily goals <HH:MM[:SS]> keyed by weekday name or date <YYYY-MM-DD>
+    pub goal_overrides: BTreeMap<String, String>,
+    /// Named lists of target durations <HH:MM[:SS]> keyed by target name
+    pub targets: BTreeMap<String, BTreeMap<String, String>>,
+    /// Break rules to check the logged breaks against, see the rules module
+    pub rules: Option<crate::rules::Spec>,
+    /// Credentials of the services of the push subcommand, see the push module
+    pub push: crate::push::Spec,
+}
+
+/** Config file read without --config, if it exists
+ */
+pub fn default_path() -> Option<PathBuf> {
+    match std::env::var("XDG_CONFIG_HOME") {
+        Ok(config) if !config.is_empty() => Some(PathBuf::from(config)),
+        _ => std::env::var("HOME")
+            .ok()
+            .map(|home| PathBuf::from(home).join(".config")),
+    }
+    .map(|config| config.join("time_rust").join("config.toml"))
+}
+
+/** Whether a range of weekdays such as monday-thursday contains the weekday; a range may wrap
+ * around the week, as in saturday-sunday or friday-monday
+ */
+fn weekdays_contain(range: &str, weekday: Weekday) -> bool {
+    let Some((first, last)) = range.split_once('-') else {
+        return false;
+    };
+    match (
+        first.trim().parse::<Weekday>(),
+        last.trim().parse::<Weekday>(),
+    ) {
+        (Ok(first), Ok(last)) => {
+            let days =
+                |day: Weekday| (day.num_days_from_monday() + 7 - first.num_days_from_monday()) % 7;
+            days(weekday) <= days(last)
+        }
+        _ => false,
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Config {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => panic!("Cannot read config {}: {}", path.display(), e),
+        };
+        match toml::from_str(&content) {
+            Ok(config) => config,
+            Err(e) => panic!("Invalid config {}: {}", path.display(), e),
+        }
+    }
+
+    /** Daily goal configured for the given date, if any
+     */
+    pub fn goal_override(&self, date: NaiveDate) -> Option<&String> {
+        let by_date = self
+            .goal_overrides
+            .iter()
+            .find(|(key, _)| NaiveDate::parse_from_str(key, "%Y-%m-%d") == Ok(date));
+        let by_weekday = self
+            .goal_overrides
+            .iter()
+            .find(|(key, _)| key.parse::<Weekday>() == Ok(date.weekday()));
+        let by_weekdays = self
+            .goal_overrides
+            .iter()
+            .find(|(key, _)| weekdays_contain(key, date.weekday()));
+        by_date.or(by_weekday).or(by_weekdays).map(|(_, goal)| goal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_and_overrides() {
+        let config: Config = toml::from_str(
+            "weekly-goal = \"38.5\"\nmax-daily = \"9:00\"\n\n[goal-overrides]\nfriday = \"4:00\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.weekly_goal.as_deref(), Some("38.5"));
+        assert_eq!(config.max_daily.as_deref(), Some("9:00"));
+        assert_eq!(config.min_start, None);
+        let friday = NaiveDate::from_ymd_opt(2024, 1, 19).unwrap();
+        assert_eq!(
+            config.goal_override(friday).map(String::as_str),
+            Some("4:00")
+        );
+    }
+
+    #[test]
+    fn weekday_ranges() {
+        let config: Config = toml::from_str(
+            "[goal-overrides]\nmon-thu = \"8:30\"\nfriday = \"5:00\"\nsaturday-sunday = \"0:00\"\n",
+        )
+        .unwrap();
+        let goal = |day| {
+            config
+                .goal_override(NaiveDate::from_ymd_opt(2024, 1, day).unwrap())
+                .map(String::as_str)
+        };
+        assert_eq!(goal(15), Some("8:30"));
+        assert_eq!(goal(18), Some("8:30"));
+        assert_eq!(goal(19), Some("5:00"));
+        assert_eq!(goal(21), Some("0:00"));
+    }
+}
@@ -0,0 +1,136 @@
+/*! Description of a single working day in YAML, e.g.
+ *
+ *     start: "8.00"
+ *     end: "16h30"
+ *     goal: "7:48"
+ *     breaks:
+ *       - "12:30-13:00"
+ *       - "12.00-12.45"
+ *
+ * Values may use '.' as separator or h/m suffixes; `normalize` rewrites them canonically.
+ */
+use crate::error::ParseError;
+use crate::{create_duration, create_time, format_duration, merge_intervals, parse_interval};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const TIME_FORMAT: &str = "%H:%M:%S";
+
+#[derive(Deserialize)]
+pub struct DayFile {
+    pub start: String,
+    pub end: Option<String>,
+    pub goal: Option<String>,
+    #[serde(default)]
+    pub breaks: Vec<String>,
+}
+
+/** Canonical form: <HH:MM:SS> everywhere, breaks sorted and merged, derived values filled in
+ */
+#[derive(Serialize)]
+pub struct CanonicalDay {
+    pub start: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub goal: Option<String>,
+    pub breaks: Vec<String>,
+    pub break_time: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub work_time: Option<String>,
+}
+
+pub fn read(path: &Path) -> DayFile {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => panic!("Cannot read day file {}: {}", path.display(), e),
+    };
+    match serde_yaml::from_str(&content) {
+        Ok(day) => day,
+        Err(e) => panic!("Invalid day file {}: {}", path.display(), e),
+    }
+}
+
+pub fn write(path: &Path, day: &CanonicalDay) {
+    if let Err(e) = fs::write(path, to_yaml(day)) {
+        panic!("Cannot write day file {}: {}", path.display(), e);
+    }
+}
+
+pub fn to_yaml(day: &CanonicalDay) -> String {
+    serde_yaml::to_string(day).unwrap()
+}
+
+/** Rewrite loosely written values such as 8.30, 8h30, 8h, or 45m into <HH:MM[:SS]>
+ */
+fn tidy(input: &str) -> String {
+    let value = input.trim().to_lowercase().replace('.', ":");
+    if let Some((hours, minutes)) = value.split_once('h') {
+        let minutes = minutes.trim_end_matches('m');
+        format!(
+            "{}:{}",
+            hours,
+            if minutes.is_empty() { "0" } else { minutes }
+        )
+    } else if let Some(minutes) = value.strip_suffix('m') {
+        format!("0:{}", minutes)
+    } else {
+        value
+    }
+}
+
+pub fn normalize(day: &DayFile) -> Result<CanonicalDay, ParseError> {
+    let start = create_time(&tidy(&day.start))?;
+    let end = day
+        .end
+        .as_ref()
+        .map(|e| create_time(&tidy(e)))
+        .transpose()?;
+    let breaks = merge_intervals(
+        day.breaks
+            .iter()
+            .map(|b| {
+                let tidied: Vec<String> = b.split('-').map(tidy).collect();
+                parse_interval(&tidied.join("-"))
+            })
+            .collect::<Result<_, _>>()?,
+    );
+    let break_time = breaks.iter().map(|(s, e)| *e - *s).sum();
+    let goal = day
+        .goal
+        .as_ref()
+        .map(|g| create_duration(&tidy(g)))
+        .transpose()?;
+
+    Ok(CanonicalDay {
+        start: start.format(TIME_FORMAT).to_string(),
+        end: end.map(|e| e.format(TIME_FORMAT).to_string()),
+        goal: goal.map(|g| format_duration(&g)),
+        breaks: breaks
+            .iter()
+            .map(|(s, e)| format!("{}-{}", s.format(TIME_FORMAT), e.format(TIME_FORMAT)))
+            .collect(),
+        break_time: format_duration(&break_time),
+        work_time: end.map(|e| format_duration(&(e - start - break_time))),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messy_day_becomes_canonical() {
+        let day: DayFile = serde_yaml::from_str(
+            "start: \"8.00\"\nend: \"16h30\"\ngoal: \"7:48\"\n\
+             breaks:\n  - \"12:30-13:00\"\n  - \"12.00-12.45\"\n  - \"15h-15h15\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            to_yaml(&normalize(&day).unwrap()),
+            "start: 08:00:00\nend: 16:30:00\ngoal: 07:48:00\nbreaks:\n\
+             - 12:00:00-13:00:00\n- 15:00:00-15:15:00\nbreak_time: 01:15:00\nwork_time: 07:15:00\n"
+        );
+    }
+}
@@ -0,0 +1,235 @@
+/*! Days without a goal: vacation from a file of dates or date ranges, one per line with an
+ * optional note, e.g.
+ *
+ *     2024-05-10 bridge day
+ *     2024-12-23..2024-12-31 christmas vacation
+ *
+ * and public holidays computed for a region.  Empty lines and lines starting with '#' are
+ * ignored.  The vacation subcommand appends ranges to the file.
+ */
+use crate::error::{choice, ParseError};
+use chrono::{Datelike, Duration, NaiveDate};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/** Vacation file used without --days-off, $XDG_DATA_HOME/time_rust/days-off in ~/.local/share
+ */
+pub fn default_path() -> PathBuf {
+    let data = match std::env::var("XDG_DATA_HOME") {
+        Ok(data) if !data.is_empty() => PathBuf::from(data),
+        _ => match std::env::var("HOME") {
+            Ok(home) => PathBuf::from(home).join(".local").join("share"),
+            Err(_) => panic!("Neither XDG_DATA_HOME nor HOME set for the days off.  Stop!"),
+        },
+    };
+    data.join("time_rust").join("days-off")
+}
+
+/** Parse <YYYY-MM-DD> or <YYYY-MM-DD>..<YYYY-MM-DD>, both ends inclusive
+ */
+pub fn parse_range(input: &str) -> Result<(NaiveDate, NaiveDate), ParseError> {
+    let date = |s: &str| {
+        NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").map_err(|_| ParseError::NotADate {
+            input: input.to_owned(),
+        })
+    };
+    let (first, last) = match input.split_once("..") {
+        Some((first, last)) => (date(first)?, date(last)?),
+        None => (date(input)?, date(input)?),
+    };
+    if last < first {
+        return Err(ParseError::OutOfRange {
+            input: input.to_owned(),
+        });
+    }
+    Ok((first, last))
+}
+
+/** A range of vacation days and its note
+ */
+pub struct Vacation {
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+    pub note: String,
+}
+
+/** Read the vacation file; a missing file has none
+ */
+pub fn read(path: &Path) -> Vec<Vacation> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+    content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (range, note) = line.split_once(' ').unwrap_or((line, ""));
+            match parse_range(range) {
+                Ok((first, last)) => Vacation {
+                    first,
+                    last,
+                    note: note.trim().to_owned(),
+                },
+                Err(e) => panic!("Invalid days off {}: {}.  Stop!", path.display(), e),
+            }
+        })
+        .collect()
+}
+
+/** Append a range of vacation days, creating the file and its directory if needed
+ */
+pub fn append(path: &Path, first: NaiveDate, last: NaiveDate, note: &str) {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        if let Err(e) = fs::create_dir_all(dir) {
+            panic!("Cannot create {}: {}", dir.display(), e);
+        }
+    }
+    let mut file = match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(file) => file,
+        Err(e) => panic!("Cannot open days off {}: {}", path.display(), e),
+    };
+    let range = if first == last {
+        first.to_string()
+    } else {
+        format!("{}..{}", first, last)
+    };
+    if let Err(e) = writeln!(file, "{} {}", range, note) {
+        panic!("Cannot write days off {}: {}", path.display(), e);
+    }
+}
+
+/** Easter Sunday of a year, by the anonymous Gregorian algorithm
+ */
+fn easter(year: i32) -> NaiveDate {
+    let (a, b, c) = (year % 19, year / 100, year % 100);
+    let g = (b - (b + 8) / 25 + 1) / 3;
+    let h = (19 * a + b - b / 4 - g + 15) % 30;
+    let l = (32 + 2 * (b % 4) + 2 * (c / 4) - h - c % 4) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let n = h + l - 7 * m + 114;
+    NaiveDate::from_ymd_opt(year, (n / 31) as u32, (n % 31 + 1) as u32).unwrap()
+}
+
+/** The region if holidays knows it, as a region of the config is not checked by --holidays
+ */
+pub fn check_region(region: &str) -> Result<(), ParseError> {
+    choice("holidays", region, &[("de", ()), ("none", ())])
+}
+
+/** Public holidays of a region in a year: de for those of all of Germany, or none
+ */
+pub fn holidays(region: &str, year: i32) -> Vec<(NaiveDate, &'static str)> {
+    let fixed = |month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+    let easter_offset = |days| easter(year) + Duration::try_days(days).unwrap();
+    match region {
+        "de" => vec![
+            (fixed(1, 1), "New Year's Day"),
+            (easter_offset(-2), "Good Friday"),
+            (easter_offset(1), "Easter Monday"),
+            (fixed(5, 1), "Labour Day"),
+            (easter_offset(39), "Ascension Day"),
+            (easter_offset(50), "Whit Monday"),
+            (fixed(10, 3), "German Unity Day"),
+            (fixed(12, 25), "Christmas Day"),
+            (fixed(12, 26), "Second Christmas Day"),
+        ],
+        "none" => Vec::new(),
+        _ => panic!("Unknown holiday region '{}'.  Stop!", region),
+    }
+}
+
+/** Vacation and the holidays of a region, both without a goal
+ */
+pub struct DaysOff {
+    pub vacation: Vec<Vacation>,
+    pub region: Option<String>,
+}
+
+impl DaysOff {
+    /** Why the date has no goal, if it hasn't
+     */
+    pub fn reason(&self, date: NaiveDate) -> Option<String> {
+        let holiday = self.region.as_deref().and_then(|region| {
+            holidays(region, date.year())
+                .into_iter()
+                .find(|(day, _)| *day == date)
+                .map(|(_, name)| name.to_owned())
+        });
+        holiday.or_else(|| {
+            self.vacation
+                .iter()
+                .find(|v| v.first <= date && date <= v.last)
+                .map(|v| {
+                    if v.note.is_empty() {
+                        "vacation".to_owned()
+                    } else {
+                        v.note.clone()
+                    }
+                })
+        })
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.reason(date).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn easter_sundays() {
+        assert_eq!(easter(2024), date("2024-03-31"));
+        assert_eq!(easter(2025), date("2025-04-20"));
+        assert_eq!(easter(2038), date("2038-04-25"));
+    }
+
+    #[test]
+    fn vacation_and_holidays() {
+        let days_off = DaysOff {
+            vacation: vec![Vacation {
+                first: date("2024-12-23"),
+                last: date("2024-12-31"),
+                note: String::new(),
+            }],
+            region: Some("de".to_owned()),
+        };
+        assert_eq!(
+            days_off.reason(date("2024-12-25")).as_deref(),
+            Some("Christmas Day")
+        );
+        assert_eq!(
+            days_off.reason(date("2024-12-30")).as_deref(),
+            Some("vacation")
+        );
+        assert_eq!(
+            days_off.reason(date("2024-05-09")).as_deref(),
+            Some("Ascension Day")
+        );
+        assert!(!days_off.contains(date("2025-01-02")));
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(
+            parse_range("2024-12-23..2024-12-31"),
+            Ok((date("2024-12-23"), date("2024-12-31")))
+        );
+        assert_eq!(
+            parse_range("2024-05-10"),
+            Ok((date("2024-05-10"), date("2024-05-10")))
+        );
+        assert!(matches!(
+            parse_range("2024-12-31..2024-12-23"),
+            Err(ParseError::OutOfRange { .. })
+        ));
+    }
+}
@@ -0,0 +1,180 @@
+/*! Errors in what the user gave, each kind with its own exit status:
+ *
+ *     3  malformed time or duration, as in 9:ab
+ *     4  time, duration, or date out of range, as in 25:99
+ *     5  malformed interval, as in 12:00
+ *     6  malformed now offset, as in now*5
+ *     7  name not known, as in --targets of a list missing in the config or --notify-on bogus
+ *     8  malformed timesheet record or clock event, as in one with a work time of eight
+ *    10  no work segment left of the clock events, as after --start-from past the last one
+ *    11  record not there or not to be edited so, as with a break outside the working period
+ *    12  push to a time tracking service failed, as for one missing in the config or rejecting
+ *    13  break out of order under --require-sorted-breaks, as 12:00-12:30 after 15:00-15:15
+ *
+ * A warning under --strict exits with 9.
+ */
+use crate::i18n::trf;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// A field between the colons is empty, as in 9:
+    EmptyField { input: String },
+    /// A field contains something else than digits, as in 9:ab
+    NotANumber { input: String, field: String },
+    /// Neither HH:MM nor HH:MM:SS, as in 9 or 1:2:3:4
+    WrongFieldCount { input: String, count: usize },
+    /// More than one kind of separator, as in 9:30.15
+    MixedSeparators { input: String },
+    /// Digits fine, but no valid time of day, as in 25:00
+    OutOfRange { input: String },
+    /// No dash between the two times of an interval
+    NotAnInterval { input: String },
+    /// No valid date before the T of a date and time, as in 2024-13-01T08:00
+    NotADate { input: String },
+    /// Something else than minutes following now, as in now*5
+    BadNowOffset { input: String },
+    /// No list of targets of this name in the config
+    UnknownTargets { name: String },
+    /// A setting, of the command line or the config, with a value not among its choices
+    UnknownValue {
+        setting: String,
+        value: String,
+        expected: String,
+    },
+    /// No field of this name, see `fields`
+    UnknownField { name: String },
+    /// No --start-from mode of this name
+    UnknownStartFrom { mode: String },
+    /// A line of the timesheet that is no record, with what is wrong with it
+    MalformedRecord { line: String, reason: String },
+    /// A line of the clock file that is no event
+    MalformedEvent { line: String },
+    /// No work segment of the clock events left over by the filters
+    NoWorkSegment { path: String },
+    /// No record of the date in the timesheet
+    NoRecord { date: String, path: String },
+    /// An edit that would leave the record inconsistent, with why
+    BadEdit { reason: String },
+    /// A service not to be pushed to or rejecting a day, with why
+    PushFailed { reason: String },
+    /// A break starting before the one given before it ends
+    UnsortedBreak { input: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::EmptyField { input } => {
+                f.write_str(&trf("empty field in '{}'", &[input]))
+            }
+            ParseError::NotANumber { input, field } => {
+                f.write_str(&trf("'{}' in '{}' is not a number", &[field, input]))
+            }
+            ParseError::WrongFieldCount { input, count } if *count > 3 => {
+                f.write_str(&trf("too many parts in '{}', expected HH:MM[:SS]", &[input]))
+            }
+            ParseError::WrongFieldCount { input, .. } => {
+                f.write_str(&trf("too few parts in '{}', expected HH:MM[:SS]", &[input]))
+            }
+            ParseError::MixedSeparators { input } => f.write_str(&trf("mixed separators in '{}', use one of ':', '.' or ' ' throughout",
+                &[input],
+            )),
+            ParseError::OutOfRange { input } => {
+                f.write_str(&trf("'{}' is out of range", &[input]))
+            }
+            ParseError::NotAnInterval { input } => {
+                f.write_str(&trf("'{}' is no interval, expected <HH:MM[:SS]>-<HH:MM[:SS]>",
+                    &[input],
+                ))
+            }
+            ParseError::NotADate { input } => {
+                f.write_str(&trf("'{}' has no valid date, expected YYYY-MM-DD", &[input]))
+            }
+            ParseError::BadNowOffset { input } => f.write_str(&trf("malformed offset in '{}', expected now, now-<offset> or now+<offset>, as in now-15 or now-1h30m",
+                &[input],
+            )),
+            ParseError::UnknownTargets { name } => {
+                f.write_str(&trf("no targets '{}' in the config", &[name]))
+            }
+            ParseError::UnknownValue {
+                setting,
+                value,
+                expected,
+            } => f.write_str(&trf(
+                "unknown value '{}' for {}, expected one of {}",
+                &[value, setting, expected],
+            )),
+            ParseError::UnknownField { name } => {
+                f.write_str(&trf("unknown field '{}', see `fields`", &[name]))
+            }
+            ParseError::UnknownStartFrom { mode } => {
+                f.write_str(&trf("unknown --start-from mode '{}', expected first-after", &[mode]))
+            }
+            ParseError::MalformedRecord { line, reason } => {
+                f.write_str(&trf("malformed timesheet record '{}': {}", &[line, reason]))
+            }
+            ParseError::NoWorkSegment { path } => {
+                f.write_str(&trf("no work segment in {}", &[path]))
+            }
+            ParseError::NoRecord { date, path } => {
+                f.write_str(&trf("no record of {} in {}", &[date, path]))
+            }
+            ParseError::BadEdit { reason } | ParseError::PushFailed { reason } => {
+                f.write_str(reason)
+            }
+            ParseError::UnsortedBreak { input } => f.write_str(&trf(
+                "break {} is out of order or overlaps the one before",
+                &[input],
+            )),
+            ParseError::MalformedEvent { line } => f.write_str(&trf(
+                "malformed clock event '{}', expected <[YYYY-MM-DDT]HH:MM[:SS]> start [tag] or stop",
+                &[line],
+            )),
+        }
+    }
+}
+
+impl ParseError {
+    /** Exit status of the program failing with this error
+     */
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ParseError::EmptyField { .. }
+            | ParseError::NotANumber { .. }
+            | ParseError::WrongFieldCount { .. }
+            | ParseError::MixedSeparators { .. } => 3,
+            ParseError::OutOfRange { .. } | ParseError::NotADate { .. } => 4,
+            ParseError::NotAnInterval { .. } => 5,
+            ParseError::BadNowOffset { .. } => 6,
+            ParseError::UnknownTargets { .. }
+            | ParseError::UnknownValue { .. }
+            | ParseError::UnknownField { .. }
+            | ParseError::UnknownStartFrom { .. } => 7,
+            ParseError::MalformedRecord { .. } | ParseError::MalformedEvent { .. } => 8,
+            ParseError::NoWorkSegment { .. } => 10,
+            ParseError::NoRecord { .. } | ParseError::BadEdit { .. } => 11,
+            ParseError::PushFailed { .. } => 12,
+            ParseError::UnsortedBreak { .. } => 13,
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/** The value of a setting among its named choices, else an error listing them
+ */
+pub fn choice<T: Copy>(setting: &str, value: &str, choices: &[(&str, T)]) -> Result<T, ParseError> {
+    match choices.iter().find(|(name, _)| *name == value) {
+        Some(&(_, chosen)) => Ok(chosen),
+        None => Err(ParseError::UnknownValue {
+            setting: setting.to_owned(),
+            value: value.to_owned(),
+            expected: choices
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+                .join(", "),
+        }),
+    }
+}
@@ -0,0 +1,182 @@
+/*! Clock events imported from a file, one `<[YYYY-MM-DDT]HH:MM[:SS]> start [tag]|stop` per line,
+ * e.g.
+ *
+ *     08:00 start clientA
+ *     12:00 stop
+ *     12:30 start
+ *     17:00 stop
+ *
+ * A start may carry a tag, such as a project, for the work segment it opens.  Empty lines and lines
+ * starting with '#' are ignored.  The clock subcommands append dated events
+ * to the clock file, so it keeps one day after the other.
+ */
+use crate::create_time;
+use crate::error::ParseError;
+use chrono::{DateTime, Local, NaiveDate};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Kind {
+    Start,
+    Stop,
+}
+
+impl Kind {
+    fn name(&self) -> &'static str {
+        match self {
+            Kind::Start => "start",
+            Kind::Stop => "stop",
+        }
+    }
+}
+
+pub struct Event {
+    pub at: DateTime<Local>,
+    pub kind: Kind,
+    pub tag: Option<String>,
+}
+
+/** Clock file of the start/stop subcommands: $XDG_DATA_HOME/time_rust/events, by default in
+ * ~/.local/share
+ */
+pub fn default_path() -> PathBuf {
+    let data = match std::env::var("XDG_DATA_HOME") {
+        Ok(data) if !data.is_empty() => PathBuf::from(data),
+        _ => match std::env::var("HOME") {
+            Ok(home) => PathBuf::from(home).join(".local").join("share"),
+            Err(_) => panic!("Neither XDG_DATA_HOME nor HOME set for the clock file.  Stop!"),
+        },
+    };
+    data.join("time_rust").join("events")
+}
+
+/** Append a dated event, creating the file and its directory if needed
+ */
+pub fn append(path: &Path, at: DateTime<Local>, kind: Kind, tag: Option<&str>) {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        if let Err(e) = fs::create_dir_all(dir) {
+            panic!("Cannot create {}: {}", dir.display(), e);
+        }
+    }
+    let mut file = match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(file) => file,
+        Err(e) => panic!("Cannot open events {}: {}", path.display(), e),
+    };
+    let tag = tag.map(|tag| format!(" {}", tag)).unwrap_or_default();
+    if let Err(e) = writeln!(
+        file,
+        "{} {}{}",
+        at.format("%Y-%m-%dT%H:%M:%S"),
+        kind.name(),
+        tag
+    ) {
+        panic!("Cannot write events {}: {}", path.display(), e);
+    }
+}
+
+/** Events of the given date
+ */
+pub fn on(events: Vec<Event>, date: NaiveDate) -> Vec<Event> {
+    events
+        .into_iter()
+        .filter(|e| e.at.date_naive() == date)
+        .collect()
+}
+
+/** Whether the last event leaves the clock running
+ */
+pub fn is_running(events: &[Event]) -> bool {
+    events.last().is_some_and(|e| e.kind == Kind::Start)
+}
+
+fn parse_event(line: &str) -> Result<Event, ParseError> {
+    let malformed = || ParseError::MalformedEvent {
+        line: line.to_owned(),
+    };
+    let (time, kind) = line.split_once(char::is_whitespace).ok_or_else(malformed)?;
+    Ok(Event {
+        at: create_time(time).map_err(|_| malformed())?,
+        kind: match kind.split_whitespace().next() {
+            Some("start") => Kind::Start,
+            Some("stop") => Kind::Stop,
+            _ => return Err(malformed()),
+        },
+        tag: kind.split_whitespace().nth(1).map(str::to_owned),
+    })
+}
+
+/** Read all events, sorted by time
+ */
+pub fn read(path: &Path) -> Result<Vec<Event>, ParseError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => panic!("Cannot read events {}: {}", path.display(), e),
+    };
+    let mut events: Vec<Event> = content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(parse_event)
+        .collect::<Result<_, _>>()?;
+    events.sort_by_key(|e| e.at);
+    Ok(events)
+}
+
+/** Tag of the start event at the given time, the start of a work segment
+ */
+pub fn tag_at(events: &[Event], at: DateTime<Local>) -> Option<String> {
+    events
+        .iter()
+        .find(|e| e.kind == Kind::Start && e.at == at)
+        .and_then(|e| e.tag.clone())
+}
+
+/** Work segments from each start to the following stop; a still open last segment has no end.
+ * Repeated starts or stops are ignored.
+ */
+pub fn segments(events: &[Event]) -> Vec<(DateTime<Local>, Option<DateTime<Local>>)> {
+    let mut segments = Vec::new();
+    let mut open: Option<DateTime<Local>> = None;
+    for event in events {
+        match (event.kind, open) {
+            (Kind::Start, None) => open = Some(event.at),
+            (Kind::Stop, Some(start)) => {
+                segments.push((start, Some(event.at)));
+                open = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = open {
+        segments.push((start, None));
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_and_read_today() {
+        let path = std::env::temp_dir().join(format!("time_rust-events-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let at = |t: &str| create_time(t).unwrap();
+        append(&path, at("2024-01-15T16:00"), Kind::Stop, None);
+        append(&path, at("8:00"), Kind::Start, Some("clientA"));
+        append(&path, at("12:00"), Kind::Stop, None);
+        append(&path, at("12:30"), Kind::Start, None);
+        let today = on(read(&path).unwrap(), at("8:00").date_naive());
+        fs::remove_file(&path).unwrap();
+
+        assert!(is_running(&today));
+        assert_eq!(
+            segments(&today),
+            vec![(at("8:00"), Some(at("12:00"))), (at("12:30"), None)]
+        );
+        assert_eq!(tag_at(&today, at("8:00")).as_deref(), Some("clientA"));
+        assert_eq!(tag_at(&today, at("12:30")), None);
+    }
+}
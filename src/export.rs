@@ -0,0 +1,215 @@
+/*! Timesheet of the logged days of a date range in the layout of another template, one row per
+ * day, as CSV or as an Excel workbook.  The layout names the columns in order, each with an
+ * optional header, e.g.
+ *
+ *     date=Datum,start=Beginn,end=Ende,break=Pause,hours=Stunden,delta-hours=Saldo
+ *
+ * Columns are date, weekday, start, end, break (the total), breaks (each from-to), net, hours,
+ * goal, delta and delta-hours, durations as <HH:MM:SS> and hours in decimal.
+ */
+use crate::error::{choice, ParseError};
+use crate::timesheet::{self, Record};
+use crate::{format_duration, format_duration_signed};
+use chrono::{Duration, NaiveDate};
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Column {
+    Date,
+    Weekday,
+    Start,
+    End,
+    Break,
+    Breaks,
+    Net,
+    Hours,
+    Goal,
+    Delta,
+    DeltaHours,
+}
+
+/// Name of each column, as in --columns
+pub const NAMES: [(&str, Column); 11] = [
+    ("date", Column::Date),
+    ("weekday", Column::Weekday),
+    ("start", Column::Start),
+    ("end", Column::End),
+    ("break", Column::Break),
+    ("breaks", Column::Breaks),
+    ("net", Column::Net),
+    ("hours", Column::Hours),
+    ("goal", Column::Goal),
+    ("delta", Column::Delta),
+    ("delta-hours", Column::DeltaHours),
+];
+
+impl Column {
+    pub fn from_name(name: &str) -> Result<Column, ParseError> {
+        choice("columns", name, &NAMES)
+    }
+}
+
+/** Column layout with the header of each column, by default its name
+ */
+pub fn layout(spec: &str) -> Result<Vec<(Column, String)>, ParseError> {
+    spec.split(',')
+        .map(|column| column.trim())
+        .filter(|column| !column.is_empty())
+        .map(|column| match column.split_once('=') {
+            Some((name, header)) => Ok((Column::from_name(name.trim())?, header.trim().to_owned())),
+            None => Ok((Column::from_name(column)?, column.to_owned())),
+        })
+        .collect()
+}
+
+/** Value of a cell, kept a number for spreadsheets
+ */
+#[derive(Debug, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Number(f64),
+}
+
+impl Cell {
+    fn hours(duration: Duration) -> Cell {
+        Cell::Number((duration.num_seconds() as f64 / 36.0).round() / 100.0)
+    }
+
+    fn to_csv(&self, delimiter: char) -> String {
+        match self {
+            Cell::Text(text) if text.contains([delimiter, '"', '\n']) => {
+                format!("\"{}\"", text.replace('"', "\"\""))
+            }
+            Cell::Text(text) => text.clone(),
+            Cell::Number(number) => number.to_string(),
+        }
+    }
+}
+
+/** The rows of the days of the range that have a record, the last record of a day counting
+ */
+pub fn rows(
+    records: &[Record],
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    columns: &[(Column, String)],
+    goal: impl Fn(NaiveDate) -> Result<Duration, ParseError>,
+) -> Result<Vec<Vec<Cell>>, ParseError> {
+    let mut days: Vec<NaiveDate> = timesheet::between(records, from, to)
+        .iter()
+        .map(|r| r.date)
+        .collect();
+    days.dedup();
+    days.into_iter()
+        .map(|day| {
+            let record = timesheet::on(records, day).unwrap();
+            let goal = goal(day)?;
+            Ok(columns
+                .iter()
+                .map(|(column, _)| match column {
+                    Column::Date => Cell::Text(day.to_string()),
+                    Column::Weekday => Cell::Text(day.format("%a").to_string()),
+                    Column::Start => Cell::Text(record.start.format("%H:%M:%S").to_string()),
+                    Column::End => Cell::Text(record.end.format("%H:%M:%S").to_string()),
+                    Column::Break => Cell::Text(format_duration(&record.break_time)),
+                    Column::Breaks => Cell::Text(
+                        record
+                            .breaks
+                            .iter()
+                            .map(|(from, to)| {
+                                format!("{}-{}", from.format("%H:%M"), to.format("%H:%M"))
+                            })
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    ),
+                    Column::Net => Cell::Text(format_duration(&record.work_time)),
+                    Column::Hours => Cell::hours(record.work_time),
+                    Column::Goal => Cell::Text(format_duration(&goal)),
+                    Column::Delta => Cell::Text(format_duration_signed(&(record.work_time - goal))),
+                    Column::DeltaHours => Cell::hours(record.work_time - goal),
+                })
+                .collect())
+        })
+        .collect()
+}
+
+pub fn csv(columns: &[(Column, String)], rows: &[Vec<Cell>], delimiter: char) -> String {
+    let header: Vec<Cell> = columns
+        .iter()
+        .map(|(_, header)| Cell::Text(header.clone()))
+        .collect();
+    let mut out = String::new();
+    for row in std::iter::once(&header).chain(rows) {
+        let cells: Vec<String> = row.iter().map(|cell| cell.to_csv(delimiter)).collect();
+        out.push_str(&cells.join(&delimiter.to_string()));
+        out.push('\n');
+    }
+    out
+}
+
+pub fn xlsx(columns: &[(Column, String)], rows: &[Vec<Cell>], path: &Path) {
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    let sheet = workbook.add_worksheet();
+    let written = columns
+        .iter()
+        .enumerate()
+        .try_for_each(|(col, (_, header))| sheet.write_string(0, col as u16, header).map(|_| ()))
+        .and_then(|_| {
+            rows.iter().enumerate().try_for_each(|(row, cells)| {
+                cells.iter().enumerate().try_for_each(|(col, cell)| {
+                    let (row, col) = (row as u32 + 1, col as u16);
+                    match cell {
+                        Cell::Text(text) => sheet.write_string(row, col, text),
+                        Cell::Number(number) => sheet.write_number(row, col, *number),
+                    }
+                    .map(|_| ())
+                })
+            })
+        })
+        .and_then(|_| workbook.save(path));
+    if let Err(e) = written {
+        panic!("Cannot write {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn layout_and_rows() {
+        let columns = layout("date=Datum, weekday,hours=Stunden,delta-hours").unwrap();
+        assert_eq!(columns[0], (Column::Date, "Datum".to_owned()));
+        assert_eq!(columns[1], (Column::Weekday, "weekday".to_owned()));
+
+        let time = |t: &str| chrono::NaiveTime::parse_from_str(t, "%H:%M").unwrap();
+        let record = |date: &str, minutes| Record {
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            start: time("08:00"),
+            end: time("16:30"),
+            break_time: Duration::try_minutes(30).unwrap(),
+            work_time: Duration::try_minutes(minutes).unwrap(),
+            breaks: vec![(time("12:00"), time("12:30"))],
+            goal_met: false,
+            provisional: false,
+            tags: BTreeMap::new(),
+            zone: None,
+        };
+        let records = [
+            record("2024-01-15", 420),
+            record("2024-01-15", 480),
+            record("2024-01-16", 450),
+        ];
+        let rows = rows(&records, None, None, &columns, |_| {
+            Ok(Duration::try_hours(8).unwrap())
+        })
+        .unwrap();
+        assert_eq!(
+            csv(&columns, &rows, ';'),
+            "Datum;weekday;Stunden;delta-hours\n\
+             2024-01-15;Mon;8;0\n\
+             2024-01-16;Tue;7.5;-0.5\n"
+        );
+    }
+}
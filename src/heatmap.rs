@@ -0,0 +1,79 @@
+/*! Break density over the day: one CSV row per recorded day with a column per time slot, counting
+ * the breaks that touch the slot
+ */
+use crate::timesheet::Record;
+use chrono::{NaiveTime, Timelike};
+
+/** Render the matrix with slots of `slot` minutes; days without breaks are all-zero rows
+ */
+pub fn render(records: &[Record], slot: u32) -> String {
+    let slots = (24 * 60u32).div_ceil(slot);
+    let minute = |t: &NaiveTime| t.hour() * 60 + t.minute();
+
+    let mut header = vec!["date".to_string()];
+    header.extend((0..slots).map(|i| format!("{:02}:{:02}", i * slot / 60, i * slot % 60)));
+    let mut out = header.join(",");
+    out.push('\n');
+    for record in records {
+        let mut counts = vec![0; slots as usize];
+        for (from, to) in &record.breaks {
+            let (from, to) = (minute(from), minute(to));
+            for (i, count) in counts.iter_mut().enumerate() {
+                let slot_start = i as u32 * slot;
+                if from < slot_start + slot && to > slot_start {
+                    *count += 1;
+                }
+            }
+        }
+        let counts: Vec<String> = counts.iter().map(|c| c.to_string()).collect();
+        out.push_str(&format!("{},{}\n", record.date, counts.join(",")));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate};
+    use std::collections::BTreeMap;
+
+    fn record(date: &str, breaks: &[(&str, &str)]) -> Record {
+        let time = |t: &str| NaiveTime::parse_from_str(t, "%H:%M").unwrap();
+        Record {
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            start: time("08:00"),
+            end: time("16:30"),
+            break_time: Duration::try_minutes(30).unwrap(),
+            work_time: Duration::try_hours(8).unwrap(),
+            breaks: breaks
+                .iter()
+                .map(|&(from, to)| (time(from), time(to)))
+                .collect(),
+            goal_met: true,
+            provisional: false,
+            tags: BTreeMap::new(),
+            zone: None,
+        }
+    }
+
+    #[test]
+    fn breaks_counted_per_slot() {
+        let records = [
+            record("2024-01-15", &[("12:00", "12:30")]),
+            record("2024-01-16", &[("12:15", "12:45")]),
+            record("2024-01-17", &[]),
+        ];
+        let out = render(&records, 60);
+        let rows: Vec<Vec<&str>> = out.lines().map(|l| l.split(',').collect()).collect();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0][13], "12:00");
+        assert_eq!((rows[1][13], rows[2][13], rows[3][13]), ("1", "1", "0"));
+        assert_eq!(rows[1][14], "0");
+        assert!(rows[3][1..].iter().all(|&count| count == "0"));
+
+        let out = render(&records[..1], 15);
+        let row: Vec<&str> = out.lines().nth(1).unwrap().split(',').collect();
+        // The slots from 11:45 to 12:30, after the date column
+        assert_eq!(&row[48..52], ["0", "1", "1", "0"]);
+    }
+}
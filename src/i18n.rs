@@ -0,0 +1,506 @@
+/*! Language of the output, English or German.  Messages are looked up by their English text, so
+ * output without a translation stays English; placeholders {} are filled in order, e.g.
+ *
+ *     trf("Recorded break {}-{}.", &[&from, &to])
+ *
+ * The language comes from --lang, else lang of the config, else the environment's LC_ALL,
+ * LC_MESSAGES, or LANG, and is English unless one of them names German.  The help texts of the
+ * arguments stay English.
+ */
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Lang {
+    En,
+    De,
+}
+
+impl Lang {
+    /** Language of a name such as de or of a locale such as de_DE.UTF-8, if bundled
+     */
+    pub fn from_name(name: &str) -> Option<Lang> {
+        match name.split(['_', '.', '@', '-']).next().unwrap_or("") {
+            "en" | "C" | "POSIX" => Some(Lang::En),
+            "de" => Some(Lang::De),
+            _ => None,
+        }
+    }
+
+    /** Language of the environment, English unless it names a bundled one
+     */
+    pub fn from_env() -> Lang {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Lang::from_name(&value))
+            .unwrap_or(Lang::En)
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+/** Set the language of all further output
+ */
+pub fn set(lang: Lang) {
+    CURRENT.store(lang as u8, Ordering::Relaxed);
+}
+
+pub fn current() -> Lang {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Lang::De,
+        _ => Lang::En,
+    }
+}
+
+/// German messages keyed by their English text
+const DE: &[(&str, &str)] = &[
+    // Summary
+    ("start", "Beginn"),
+    ("end", "Ende"),
+    ("already done", "bereits erledigt"),
+    ("more", "mehr"),
+    ("remaining", "verbleibend"),
+    ("short, within tolerance", "zu wenig, innerhalb der Toleranz"),
+    ("cap exceeded by {} [{}]", "Höchstgrenze überschritten um {} [{}]"),
+    ("no longer than {} [{}]", "nicht länger als {} [{}]"),
+    ("total break time", "Pausen gesamt"),
+    ("longest break", "längste Pause"),
+    ("{} (estimated)", "{} (geschätzt)"),
+    ("total hours worked", "Stunden gearbeitet"),
+    ("in goals: no daily goal", "in Tageszielen: kein Tagesziel"),
+    ("in goals: {} done; {} {}", "in Tageszielen: {} erledigt; {} {}"),
+    (
+        "No breaks defined, using default.",
+        "Keine Pausen angegeben, die Standardpause gilt.",
+    ),
+    ("accumulated today: {} [{}]", "heute insgesamt: {} [{}]"),
+    (
+        "flex balance: {}; today: {} [weighted {}]",
+        "Gleitzeitsaldo: {}; heute: {} [gewichtet {}]",
+    ),
+    (
+        "compared to yesterday: {} [yesterday {}]",
+        "im Vergleich zu gestern: {} [gestern {}]",
+    ),
+    (
+        "compared to yesterday: no record of {}",
+        "im Vergleich zu gestern: kein Eintrag für {}",
+    ),
+    ("target {}: {} {}", "Ziel {}: {} {}"),
+    ("met", "erreicht"),
+    ("{} remaining", "noch {}"),
+    ("premium time {}: {} [{} min]", "Zuschlagszeit {}: {} [{} min]"),
+    ("meeting {}-{} {}", "Termin {}-{} {}"),
+    ("overtime", "Überstunden"),
+    ("today: {}", "heute: {}"),
+    ("week:  {}", "Woche: {}"),
+    ("week {}: {}h worked of {}h; {}h {}", "Woche {}: {} h von {} h gearbeitet; {} h {}"),
+    ("No work days left this week.", "Keine Arbeitstage mehr in dieser Woche."),
+    ("{} {} leave at {} [{} of work]", "{} {} Feierabend um {} [{} Arbeit]"),
+    ("policy", "Regel"),
+    (
+        "Daily goal of {} already met: {} beyond it.",
+        "Tagesziel von {} schon erreicht: {} darüber.",
+    ),
+    (
+        "Leave at {} to meet the daily goal of {}.",
+        "Feierabend um {}, um das Tagesziel von {} zu erreichen.",
+    ),
+    (
+        "Impossible: {} between start and end is less than the goal of {}.",
+        "Unmöglich: {} zwischen Beginn und Ende sind weniger als das Ziel von {}.",
+    ),
+    ("break for exactly {} of work: {}", "Pause für genau {} Arbeit: {}"),
+    (
+        "Only {} logged day(s), at least {} needed to derive the goal; using the default.",
+        "Nur {} erfasste(r) Tag(e), mindestens {} nötig, um das Ziel abzuleiten; der Standard gilt.",
+    ),
+    (
+        "Goal derived as the median of {} logged days: {}.",
+        "Ziel als Median von {} erfassten Tagen abgeleitet: {}.",
+    ),
+    (
+        "Start {} is before the earliest allowed start, counting from {}.",
+        "Beginn {} liegt vor dem frühesten erlaubten Beginn, gezählt wird ab {}.",
+    ),
+    (
+        "Discarded {} work segment(s) shorter than {}.",
+        "{} Arbeitsabschnitt(e) kürzer als {} verworfen.",
+    ),
+    ("in {}", "in {}"),
+    ("passed", "vorbei"),
+    (
+        "only {} rest since the shift of {} ended at {}, at least {} required",
+        "nur {} Ruhezeit seit dem Ende der Schicht vom {} um {}, mindestens {} nötig",
+    ),
+    (
+        "{} {} deviates from the scheduled {} by {}",
+        "{} {} weicht um {} vom geplanten {} ab",
+    ),
+    (
+        "break {} is out of order or overlaps the one before",
+        "Pause {} ist nicht in Reihenfolge oder überlappt die vorige",
+    ),
+    (
+        "core hours {}-{} not covered from {} to {}",
+        "Kernzeit {}-{} von {} bis {} nicht abgedeckt",
+    ),
+    (
+        "break {}-{} lies within core hours {}-{}",
+        "Pause {}-{} liegt in der Kernzeit {}-{}",
+    ),
+    (
+        "no continuous break of {}: the longest is {}, {} in total",
+        "keine durchgehende Pause von {}: die längste dauert {}, insgesamt {}",
+    ),
+    (
+        "no continuous break of {}: the longest is {}, {} in total but split into pieces",
+        "keine durchgehende Pause von {}: die längste dauert {}, insgesamt {}, aber aufgeteilt",
+    ),
+    (
+        "breaks of {} fall short of the {} required for {} of work",
+        "Pausen von {} unterschreiten die für {} Arbeit nötigen {}",
+    ),
+    (
+        "worked {} from {} to {} without a break, more than {}",
+        "{} von {} bis {} ohne Pause gearbeitet, mehr als {}",
+    ),
+    ("Daily goal of {} done", "Tagesziel von {} erreicht"),
+    (
+        "{} left until 9 hours of work, at {}",
+        "noch {} bis 9 Stunden Arbeit, um {}",
+    ),
+    (
+        "{} left until the daily maximum of {}, at {}",
+        "noch {} bis zur Tageshöchstgrenze von {}, um {}",
+    ),
+    ("A break is overdue", "Eine Pause ist überfällig"),
+    ("Cannot send notification: {}", "Benachrichtigung nicht sendbar: {}"),
+    ("Goal hook failed with {}.", "Ziel-Hook fehlgeschlagen mit {}."),
+    ("Cannot run goal hook: {}", "Ziel-Hook nicht ausführbar: {}"),
+    ("Error: {}", "Fehler: {}"),
+    ("Warning: {}", "Warnung: {}"),
+    // Explanations
+    ("present for {}", "anwesend für {}"),
+    (
+        "break total {} given for {} break(s), longest assumed to be {}",
+        "Pausensumme {} für {} Pause(n) angegeben, die längste als {} angenommen",
+    ),
+    ("no breaks given, {}", "keine Pausen angegeben, {}"),
+    ("break {}-{}: {}", "Pause {}-{}: {}"),
+    ("sum of {} break(s): {}", "Summe von {} Pause(n): {}"),
+    (
+        "break not taken yet, only deducted from the projections",
+        "Pause noch nicht genommen, nur von den Prognosen abgezogen",
+    ),
+    ("clocked in at {}", "eingestempelt um {}"),
+    (
+        "clocked in at {}, counted from the earliest start {}",
+        "eingestempelt um {}, gezählt ab dem frühesten Beginn {}",
+    ),
+    ("clocked out at {}", "ausgestempelt um {}"),
+    ("still working at {}", "noch bei der Arbeit um {}"),
+    (
+        "daily goal {} as the weekly goal {} split over {} workdays",
+        "Tagesziel {} als Wochenziel {} verteilt auf {} Arbeitstage",
+    ),
+    ("daily goal {} from {}", "Tagesziel {} aus {}"),
+    ("work time {}", "Arbeitszeit {}"),
+    (
+        "billing increment: work time rounded up to {} minutes",
+        "Abrechnungstakt: Arbeitszeit auf {} Minuten aufgerundet",
+    ),
+    ("{}, reported {}", "{}, ausgewiesen {}"),
+    ("short of the goal by {}", "{} unter dem Ziel"),
+    ("over the goal by {}", "{} über dem Ziel"),
+    ("Break: {}", "Pause: {}"),
+    ("day off", "freier Tag"),
+    ("history", "Verlauf"),
+    ("config", "Konfiguration"),
+    ("no rounding", "keine Rundung"),
+    (
+        "work time rounded up to a multiple of {} minutes",
+        "Arbeitszeit aufgerundet auf ein Vielfaches von {} Minuten",
+    ),
+    (
+        "work time rounded down to a multiple of {} minutes",
+        "Arbeitszeit abgerundet auf ein Vielfaches von {} Minuten",
+    ),
+    (
+        "work time rounded to the nearest multiple of {} minutes",
+        "Arbeitszeit gerundet auf das nächste Vielfache von {} Minuten",
+    ),
+    (
+        "work time rounded up in the employee's favor to a multiple of {} minutes",
+        "Arbeitszeit zugunsten der Beschäftigten aufgerundet auf ein Vielfaches von {} Minuten",
+    ),
+    (
+        "start and end rounded up to a multiple of {} minutes",
+        "Beginn und Ende aufgerundet auf ein Vielfaches von {} Minuten",
+    ),
+    (
+        "start and end rounded down to a multiple of {} minutes",
+        "Beginn und Ende abgerundet auf ein Vielfaches von {} Minuten",
+    ),
+    (
+        "start and end rounded to the nearest multiple of {} minutes",
+        "Beginn und Ende gerundet auf das nächste Vielfache von {} Minuten",
+    ),
+    (
+        "start rounded down and end up in the employee's favor, each to a multiple of {} minutes",
+        "Beginn ab- und Ende aufgerundet zugunsten der Beschäftigten, jeweils auf ein Vielfaches von {} Minuten",
+    ),
+    (
+        "{} reaches the threshold of {}: default break of {}",
+        "{} erreichen die Schwelle von {}: Standardpause von {}",
+    ),
+    (
+        "{} is below the threshold of {}: default break of {}",
+        "{} liegen unter der Schwelle von {}: Standardpause von {}",
+    ),
+    ("flat break of {}", "feste Pause von {}"),
+    (
+        "the break policy requires {} from {} up to {}",
+        "die Pausenregel verlangt {} ab {} bis {}",
+    ),
+    (
+        "the break policy requires {} from {} on",
+        "die Pausenregel verlangt {} ab {}",
+    ),
+    // Break notes
+    (
+        "break {} has no length, skipped",
+        "Pause {} hat keine Länge, übersprungen",
+    ),
+    (
+        "break {} is given twice, counted once",
+        "Pause {} ist doppelt angegeben, einmal gezählt",
+    ),
+    (
+        "break {} lies outside the working period, skipped",
+        "Pause {} liegt außerhalb der Arbeitszeit, übersprungen",
+    ),
+    (
+        "break {} lies partly outside the working period, counted as {}",
+        "Pause {} liegt teilweise außerhalb der Arbeitszeit, gezählt als {}",
+    ),
+    (
+        "breaks {} and {} overlap, counted once as {}",
+        "Pausen {} und {} überlappen, einmal gezählt als {}",
+    ),
+    (
+        "breaks of {} take up the whole working period {}",
+        "Pausen von {} füllen die ganze Arbeitszeit {}",
+    ),
+    // Errors of parsing
+    ("empty field in '{}'", "leeres Feld in '{}'"),
+    ("'{}' in '{}' is not a number", "'{}' in '{}' ist keine Zahl"),
+    (
+        "too many parts in '{}', expected HH:MM[:SS]",
+        "zu viele Teile in '{}', erwartet HH:MM[:SS]",
+    ),
+    (
+        "too few parts in '{}', expected HH:MM[:SS]",
+        "zu wenige Teile in '{}', erwartet HH:MM[:SS]",
+    ),
+    (
+        "mixed separators in '{}', use one of ':', '.' or ' ' throughout",
+        "gemischte Trennzeichen in '{}', durchgehend ':', '.' oder ' ' verwenden",
+    ),
+    ("'{}' is out of range", "'{}' liegt außerhalb des gültigen Bereichs"),
+    (
+        "'{}' is no interval, expected <HH:MM[:SS]>-<HH:MM[:SS]>",
+        "'{}' ist kein Intervall, erwartet <HH:MM[:SS]>-<HH:MM[:SS]>",
+    ),
+    (
+        "'{}' has no valid date, expected YYYY-MM-DD",
+        "'{}' hat kein gültiges Datum, erwartet YYYY-MM-DD",
+    ),
+    (
+        "malformed offset in '{}', expected now, now-<offset> or now+<offset>, as in now-15 or now-1h30m",
+        "ungültiger Versatz in '{}', erwartet now, now-<Versatz> oder now+<Versatz>, etwa now-15 oder now-1h30m",
+    ),
+    ("no targets '{}' in the config", "keine Ziele '{}' in der Konfiguration"),
+    (
+        "unknown value '{}' for {}, expected one of {}",
+        "unbekannter Wert '{}' für {}, erwartet einer von {}",
+    ),
+    ("unknown field '{}', see `fields`", "unbekanntes Feld '{}', siehe `fields`"),
+    (
+        "unknown --start-from mode '{}', expected first-after",
+        "unbekannter --start-from-Modus '{}', erwartet first-after",
+    ),
+    ("malformed timesheet record '{}': {}", "fehlerhafter Eintrag '{}' im Stundenzettel: {}"),
+    (
+        "malformed clock event '{}', expected <[YYYY-MM-DDT]HH:MM[:SS]> start [tag] or stop",
+        "fehlerhaftes Stempelereignis '{}', erwartet <[YYYY-MM-DDT]HH:MM[:SS]> start [Tag] oder stop",
+    ),
+    (
+        "no idle time on this system, so no breaks will be recorded",
+        "keine Leerlaufzeit auf diesem System, daher werden keine Pausen erfasst",
+    ),
+    (
+        "worked {} exceeds the daily maximum of {} by {}",
+        "die Arbeitszeit von {} überschreitet die Tageshöchstgrenze von {} um {}",
+    ),
+    ("no work segment in {}", "kein Arbeitsabschnitt in {}"),
+    ("fewer than five columns", "weniger als fünf Spalten"),
+    ("invalid work time '{}'", "ungültige Arbeitszeit '{}'"),
+    ("invalid time '{}'", "ungültige Uhrzeit '{}'"),
+    ("invalid break '{}'", "ungültige Pause '{}'"),
+    ("invalid flag '{}'", "ungültiger Wahrheitswert '{}'"),
+    ("invalid tag '{}'", "ungültiges Tag '{}'"),
+    ("invalid date '{}'", "ungültiges Datum '{}'"),
+    // Subcommands
+    ("Already clocked in.", "Bereits eingestempelt."),
+    ("Not clocked in.", "Nicht eingestempelt."),
+    ("Not clocked in today.", "Heute nicht eingestempelt."),
+    ("Clocked in", "Eingestempelt"),
+    ("Clocked out", "Ausgestempelt"),
+    ("Break started", "Pause begonnen"),
+    ("Break ended", "Pause beendet"),
+    ("{} at {}.", "{} um {}."),
+    ("Recorded break {}-{}.", "Pause {}-{} erfasst."),
+    ("Balance reset from {} to {}.", "Saldo von {} auf {} zurückgesetzt."),
+    ("Flex balance: {}", "Gleitzeitsaldo: {}"),
+    ("   this week: {}", "  diese Woche: {}"),
+    ("Added {} day(s) off to {}.", "{} freie(n) Tag(e) zu {} hinzugefügt."),
+    ("Removed {} entries.", "{} Einträge entfernt."),
+    ("Dry run, nothing deleted.", "Probelauf, nichts gelöscht."),
+    ("Dry run, nothing written.", "Probelauf, nichts geschrieben."),
+    ("Dry run, nothing pushed.", "Probelauf, nichts hochgeladen."),
+    ("create", "anlegen"),
+    ("update", "ändern"),
+    ("unchanged", "gleich"),
+    ("Listening on http://{}", "Erreichbar unter http://{}"),
+    ("{} {} started", "{} {} begonnen"),
+    ("{} {} until {}", "{} {} bis {}"),
+    ("Work", "Arbeit"),
+    ("Short break", "Kurze Pause"),
+    ("Long break", "Lange Pause"),
+    ("Done", "Fertig"),
+    ("no record of {} in {}", "kein Eintrag für {} in {}"),
+    ("no break {} on {}", "keine Pause {} am {}"),
+    ("start and end of {} are the same", "Beginn und Ende am {} sind gleich"),
+    (
+        "break {}-{} lies outside the working period",
+        "Pause {}-{} liegt außerhalb der Arbeitszeit",
+    ),
+    (
+        "break {}-{} overlaps the one after it",
+        "Pause {}-{} überlappt die folgende",
+    ),
+    ("no [push.{}] table in the config", "keine Tabelle [push.{}] in der Konfiguration"),
+    ("unknown service '{}'", "unbekannter Dienst '{}'"),
+    ("{} rejected {}: {}", "{} hat {} abgelehnt: {}"),
+    ("{} answered {} without an id", "{} hat {} ohne ID beantwortet"),
+    // Dashboard
+    (
+        "s: start/stop work, b: start/end break, q: quit",
+        "s: Arbeit beginnen/beenden, b: Pause beginnen/beenden, q: Beenden",
+    ),
+    ("working", "bei der Arbeit"),
+    ("not clocked in", "nicht eingestempelt"),
+    ("on a break or done", "in der Pause oder fertig"),
+    ("Today", "Heute"),
+    ("Goal", "Ziel"),
+    ("Week", "Woche"),
+    ("{} worked, {} overtime", "{} gearbeitet, {} Überstunden"),
+    ("{} worked, {} left, goal at {}", "{} gearbeitet, noch {}, Ziel um {}"),
+    ("nothing worked yet", "noch nichts gearbeitet"),
+    ("total {} of {}", "gesamt {} von {}"),
+    // Tables
+    ("date", "Datum"),
+    ("break", "Pause"),
+    ("work", "Arbeit"),
+    ("delta", "Saldo"),
+    ("total", "gesamt"),
+    ("days: {}", "Tage: {}"),
+    ("total work: {}", "Arbeit gesamt: {}"),
+    ("total break: {}", "Pausen gesamt: {}"),
+    ("average day: {}", "Tag im Schnitt: {}"),
+    ("shortest day: {}", "kürzester Tag: {}"),
+    ("longest day: {}", "längster Tag: {}"),
+    ("avg {} over {} day(s)", "Schnitt {} über {} Tag(e)"),
+    ("days", "Tage"),
+    ("avg start", "Ø Beginn"),
+    ("avg end", "Ø Ende"),
+    ("avg work", "Ø Arbeit"),
+    ("goal streak", "Zielserie"),
+    ("{} day(s)", "{} Tag(e)"),
+    ("breaks", "Pausen"),
+    ("week", "Woche"),
+    ("change", "Änderung"),
+];
+
+/** Message in the language, the English text itself without a translation
+ */
+pub fn translate(lang: Lang, text: &'static str) -> &'static str {
+    let catalog = match lang {
+        Lang::En => return text,
+        Lang::De => DE,
+    };
+    catalog
+        .iter()
+        .find(|(en, _)| *en == text)
+        .map_or(text, |(_, translated)| translated)
+}
+
+/** Message in the current language
+ */
+pub fn tr(text: &'static str) -> &'static str {
+    translate(current(), text)
+}
+
+/** Message in the current language, its placeholders {} filled with the arguments in order
+ */
+pub fn trf(text: &'static str, args: &[&dyn Display]) -> String {
+    substitute(tr(text), args)
+}
+
+fn substitute(message: &str, args: &[&dyn Display]) -> String {
+    let mut parts = message.split("{}");
+    let mut out = parts.next().unwrap_or_default().to_owned();
+    for (i, part) in parts.enumerate() {
+        if let Some(arg) = args.get(i) {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn german_or_english() {
+        assert_eq!(Lang::from_name("de_DE.UTF-8"), Some(Lang::De));
+        assert_eq!(Lang::from_name("fr_FR"), None);
+        assert_eq!(translate(Lang::De, "already done"), "bereits erledigt");
+        assert_eq!(translate(Lang::En, "already done"), "already done");
+        assert_eq!(translate(Lang::De, "no translation"), "no translation");
+        assert_eq!(
+            substitute(
+                translate(Lang::De, "Recorded break {}-{}."),
+                &[&"12:00", &"12:30"]
+            ),
+            "Pause 12:00-12:30 erfasst."
+        );
+    }
+
+    #[test]
+    fn every_message_translated_once() {
+        for (i, (en, de)) in DE.iter().enumerate() {
+            assert!(
+                !DE[..i].iter().any(|(other, _)| other == en),
+                "{} twice",
+                en
+            );
+            assert_eq!(en.matches("{}").count(), de.matches("{}").count(), "{}", en);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+/*! Events of an iCalendar file, e.g. exported from a work calendar:
+ *
+ *     BEGIN:VEVENT
+ *     DTSTART:20240115T120000
+ *     DTEND:20240115T124500
+ *     SUMMARY:Lunch
+ *     CATEGORIES:LUNCH
+ *     END:VEVENT
+ *
+ * Only events with a start and end time are read; all-day events have no place in a working day.
+ * Times in UTC, ending in Z, are converted, all others are taken as local time.  An event is a
+ * break when its summary or one of its categories contains a break tag, or when its class is
+ * PRIVATE and private is a break tag; all others are meetings.
+ */
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use std::fs;
+use std::path::Path;
+
+pub struct CalendarEvent {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub summary: String,
+    pub categories: Vec<String>,
+    pub private: bool,
+}
+
+impl CalendarEvent {
+    /** Whether the event is tagged as one of the given break tags, compared case-insensitively
+     */
+    pub fn is_break(&self, tags: &[String]) -> bool {
+        tags.iter().map(|tag| tag.to_lowercase()).any(|tag| {
+            self.summary.to_lowercase().contains(&tag)
+                || self.categories.iter().any(|c| c.to_lowercase() == tag)
+                || (self.private && tag == "private")
+        })
+    }
+}
+
+/** Date and time of a DTSTART or DTEND value, None for a date only
+ */
+fn parse_time(value: &str) -> Option<DateTime<Local>> {
+    match value.strip_suffix('Z') {
+        Some(utc) => NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(|t| Utc.from_utc_datetime(&t).with_timezone(&Local)),
+        None => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .ok()
+            .and_then(|t| t.and_local_timezone(Local).earliest()),
+    }
+}
+
+pub fn parse(content: &str) -> Vec<CalendarEvent> {
+    // Long lines are folded onto continuation lines starting with a space or tab
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continued), Some(last)) => last.push_str(continued),
+            _ => lines.push(line.trim_end().to_owned()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let (mut start, mut end, mut summary, mut categories, mut private) =
+        (None, None, String::new(), Vec::new(), false);
+    for line in &lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Parameters such as ;TZID=Europe/Berlin follow the property name
+        let name = name.split(';').next().unwrap().to_ascii_uppercase();
+        match name.as_str() {
+            "BEGIN" if value == "VEVENT" => {
+                (start, end, summary, categories, private) =
+                    (None, None, String::new(), Vec::new(), false);
+            }
+            "DTSTART" => start = parse_time(value),
+            "DTEND" => end = parse_time(value),
+            "SUMMARY" => summary = value.replace("\\,", ",").replace("\\;", ";"),
+            "CATEGORIES" => categories.extend(value.split(',').map(|c| c.trim().to_owned())),
+            "CLASS" => private = value.eq_ignore_ascii_case("PRIVATE"),
+            "END" if value == "VEVENT" => {
+                if let (Some(start), Some(end)) = (start, end) {
+                    events.push(CalendarEvent {
+                        start,
+                        end,
+                        summary: summary.clone(),
+                        categories: categories.clone(),
+                        private,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    events.sort_by_key(|e| e.start);
+    events
+}
+
+pub fn read(path: &Path) -> Vec<CalendarEvent> {
+    match fs::read_to_string(path) {
+        Ok(content) => parse(&content),
+        Err(e) => panic!("Cannot read calendar {}: {}", path.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breaks_and_meetings() {
+        let events = parse(
+            "BEGIN:VCALENDAR\r\n\
+             BEGIN:VEVENT\r\nDTSTART;TZID=Europe/Berlin:20240115T120000\r\n\
+             DTEND;TZID=Europe/Berlin:20240115T124500\r\nSUMMARY:Team lunch\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nDTSTART:20240115T090000\r\nDTEND:20240115T093000\r\n\
+             SUMMARY:Stand\r\n up\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20240115\r\nSUMMARY:Holiday\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nDTSTART:20240115T150000\r\nDTEND:20240115T153000\r\n\
+             SUMMARY:Dentist\r\nCLASS:PRIVATE\r\nEND:VEVENT\r\n\
+             END:VCALENDAR\r\n",
+        );
+        let tags = ["lunch".to_owned(), "private".to_owned()];
+        let summaries: Vec<&str> = events.iter().map(|e| e.summary.as_str()).collect();
+        assert_eq!(summaries, ["Standup", "Team lunch", "Dentist"]);
+        let breaks: Vec<bool> = events.iter().map(|e| e.is_break(&tags)).collect();
+        assert_eq!(breaks, [false, true, true]);
+        assert_eq!(
+            events[1].end - events[1].start,
+            chrono::Duration::try_minutes(45).unwrap()
+        );
+    }
+}
@@ -0,0 +1,155 @@
+/*! Away time of the daemon subcommand: how long the session has been idle or locked, asked from
+ * logind through loginctl on Linux and from the HID system through ioreg on macOS.  Other systems
+ * never report idle time.
+ */
+use crate::Interval;
+use chrono::{DateTime, Duration, Local, TimeZone};
+use std::process::Command;
+
+/** Moment the session went idle or was locked, from the properties loginctl shows, e.g.
+ *
+ *     IdleHint=yes
+ *     IdleSinceHint=1705305600000000
+ *     LockedHint=no
+ *
+ * with the moment in microseconds since the epoch
+ */
+pub fn parse_loginctl(output: &str) -> Option<DateTime<Local>> {
+    let property = |name: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+    };
+    if property("IdleHint") != Some("yes") && property("LockedHint") != Some("yes") {
+        return None;
+    }
+    let since: i64 = property("IdleSinceHint")?.parse().ok()?;
+    Local.timestamp_micros(since).single()
+}
+
+/** Moment of the last input, from the HIDIdleTime in nanoseconds ioreg shows, e.g.
+ *
+ *     |   "HIDIdleTime" = 63500000000
+ */
+pub fn parse_ioreg(output: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let idle: i64 = output
+        .lines()
+        .find_map(|line| line.split_once("\"HIDIdleTime\" = "))?
+        .1
+        .trim()
+        .parse()
+        .ok()?;
+    Some(now - Duration::nanoseconds(idle))
+}
+
+/// Whether idle_since can ask this system at all
+pub const SUPPORTED: bool = cfg!(any(target_os = "linux", target_os = "macos"));
+
+/** Moment the user went away, if they are, asked from the system
+ */
+pub fn idle_since(now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let run = |program: &str, args: &[&str]| {
+        Command::new(program)
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    if cfg!(target_os = "macos") {
+        parse_ioreg(&run("ioreg", &["-c", "IOHIDSystem", "-d", "4"])?, now)
+    } else if cfg!(target_os = "linux") {
+        let session = std::env::var("XDG_SESSION_ID").unwrap_or_else(|_| "auto".to_owned());
+        parse_loginctl(&run(
+            "loginctl",
+            &[
+                "show-session",
+                &session,
+                "-p",
+                "IdleHint",
+                "-p",
+                "IdleSinceHint",
+                "-p",
+                "LockedHint",
+            ],
+        )?)
+    } else {
+        None
+    }
+}
+
+/** Follows the idle state from poll to poll and yields an away time once it is over
+ */
+pub struct Tracker {
+    /// Away for longer than this counts as a break
+    pub threshold: Duration,
+    away_since: Option<DateTime<Local>>,
+}
+
+impl Tracker {
+    pub fn new(threshold: Duration) -> Tracker {
+        Tracker {
+            threshold,
+            away_since: None,
+        }
+    }
+
+    /** Take the idle state of a poll at now; the break when the user came back after long enough
+     */
+    pub fn observe(
+        &mut self,
+        idle_since: Option<DateTime<Local>>,
+        now: DateTime<Local>,
+    ) -> Option<Interval> {
+        match (idle_since, self.away_since) {
+            (Some(since), None) => {
+                self.away_since = Some(since);
+                None
+            }
+            (None, Some(since)) => {
+                self.away_since = None;
+                Some((since, now)).filter(|(from, to)| *to - *from > self.threshold)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_time;
+
+    #[test]
+    fn idle_from_loginctl_and_ioreg() {
+        let since = Local.timestamp_opt(1705305600, 0).unwrap();
+        assert_eq!(
+            parse_loginctl("IdleHint=yes\nIdleSinceHint=1705305600000000\nLockedHint=no\n"),
+            Some(since)
+        );
+        assert_eq!(
+            parse_loginctl("IdleHint=no\nIdleSinceHint=1705305600000000\nLockedHint=no\n"),
+            None
+        );
+        let now = since + Duration::try_minutes(1).unwrap();
+        assert_eq!(
+            parse_ioreg("    |   \"HIDIdleTime\" = 60000000000\n", now),
+            Some(since)
+        );
+    }
+
+    #[test]
+    fn break_after_threshold() {
+        let at = |t: &str| create_time(t).unwrap();
+        let mut tracker = Tracker::new(Duration::try_minutes(10).unwrap());
+        assert_eq!(tracker.observe(None, at("10:00")), None);
+        assert_eq!(tracker.observe(Some(at("10:01")), at("10:02")), None);
+        assert_eq!(tracker.observe(Some(at("10:01")), at("10:06")), None);
+        assert_eq!(tracker.observe(None, at("10:07")), None);
+        assert_eq!(tracker.observe(Some(at("12:00")), at("12:01")), None);
+        assert_eq!(
+            tracker.observe(None, at("12:30")),
+            Some((at("12:00"), at("12:30")))
+        );
+    }
+}
@@ -0,0 +1,1712 @@
+/*! Calculation of a working day: parsing the times given, summing up breaks and work time, and
+ * the projections the command line tool renders
+ */
+use break_policy::BreakPolicy;
+use chrono::{
+    DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Weekday,
+};
+use error::ParseError;
+use i18n::{tr, trf};
+use serde::{Deserialize, Serialize};
+use std::cmp::{max, min};
+
+pub mod audit;
+pub mod break_policy;
+pub mod config;
+pub mod dayfile;
+pub mod days_off;
+pub mod error;
+pub mod events;
+pub mod export;
+pub mod heatmap;
+pub mod i18n;
+pub mod ics;
+pub mod idle;
+pub mod notify;
+pub mod pomodoro;
+pub mod push;
+pub mod report;
+pub mod rules;
+pub mod schedule;
+pub mod state;
+pub mod stats;
+pub mod timesheet;
+pub mod week_grid;
+pub mod worktime;
+
+/// Separators between hours, minutes, and seconds, as in 9:30, 9.30, or 9 30
+const SEPARATORS: [char; 3] = [':', '.', ' '];
+
+/** Split <HH:MM[:SS]> at the separators into its two or three numbers
+ */
+fn fields<T: std::str::FromStr>(input: &str) -> Result<Vec<T>, ParseError> {
+    let trimmed = input.trim();
+    let mut used = SEPARATORS.iter().filter(|&&sep| trimmed.contains(sep));
+    let separator = used.next().copied().unwrap_or(':');
+    if used.next().is_some() {
+        return Err(ParseError::MixedSeparators {
+            input: input.to_owned(),
+        });
+    }
+    let fields: Vec<&str> = trimmed.split(separator).collect();
+    if !(2..=3).contains(&fields.len()) {
+        return Err(ParseError::WrongFieldCount {
+            input: input.to_owned(),
+            count: fields.len(),
+        });
+    }
+    fields
+        .iter()
+        .map(|field| {
+            let field = field.trim();
+            if field.is_empty() {
+                return Err(ParseError::EmptyField {
+                    input: input.to_owned(),
+                });
+            }
+            field.parse::<T>().map_err(|_| ParseError::NotANumber {
+                input: input.to_owned(),
+                field: field.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/** Extract hours, minutes, and seconds from String, return as vector of u32; takes bare hours as
+ * in 8, and 12-hour times as in 8:30am or 5pm
+ */
+pub fn extract(input: &str) -> Result<Vec<u32>, ParseError> {
+    let lower = input.trim().to_ascii_lowercase();
+    let (clock, offset) = match (lower.strip_suffix("am"), lower.strip_suffix("pm")) {
+        (Some(clock), _) => (clock.trim(), Some(0)),
+        (_, Some(clock)) => (clock.trim(), Some(12)),
+        _ => (lower.as_str(), None),
+    };
+    let mut hms = if !clock.is_empty() && clock.chars().all(|c| c.is_ascii_digit()) {
+        let hours = clock.parse().map_err(|_| ParseError::NotANumber {
+            input: input.to_owned(),
+            field: clock.to_owned(),
+        })?;
+        vec![hours, 0]
+    } else if offset.is_some() {
+        fields(clock)?
+    } else {
+        fields(input)?
+    };
+    if let Some(offset) = offset {
+        // 12am is midnight, 12pm noon
+        if !(1..=12).contains(&hms[0]) {
+            return Err(ParseError::OutOfRange {
+                input: input.to_owned(),
+            });
+        }
+        hms[0] = hms[0] % 12 + offset;
+    }
+    Ok(hms)
+}
+
+/** Whether a time is given with its date, as in 2024-01-15T22:00
+ */
+pub fn is_dated(input: &str) -> bool {
+    input.contains('T')
+}
+
+/** Split a leading day offset +<N>d off a time, as in +1d02:30 for 2:30 on the day after the start
+ */
+pub fn day_offset(input: &str) -> Result<(Option<i64>, &str), ParseError> {
+    let Some(rest) = input.trim().strip_prefix('+') else {
+        return Ok((None, input));
+    };
+    let not_a_date = || ParseError::NotADate {
+        input: input.to_owned(),
+    };
+    let (days, time) = rest.split_once('d').ok_or_else(not_a_date)?;
+    let days = days.parse().map_err(|_| not_a_date())?;
+    Ok((Some(days), time))
+}
+
+/** Create DateTime object from <HH:MM[:SS]> today or <YYYY-MM-DDTHH:MM[:SS]> in local timezone
+ */
+pub fn create_time(input: &str) -> Result<DateTime<Local>, ParseError> {
+    let now = Local::now();
+    let tz = now.timezone();
+    let (date, time) = match input.split_once('T') {
+        Some((date, time)) => (
+            NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").map_err(|_| {
+                ParseError::NotADate {
+                    input: input.to_owned(),
+                }
+            })?,
+            time,
+        ),
+        None => (now.date_naive(), input),
+    };
+    let hm = extract(time)?;
+    let out_of_range = || ParseError::OutOfRange {
+        input: input.to_owned(),
+    };
+    let dt = date
+        .and_hms_opt(hm[0], hm[1], hm.get(2).copied().unwrap_or(0))
+        .ok_or_else(out_of_range)?;
+    // A time repeated when daylight saving time ends is its first occurrence
+    dt.and_local_timezone(tz)
+        .earliest()
+        .ok_or_else(out_of_range)
+}
+
+/** Earliest allowed start <HH:MM[:SS]>, or none for no clamping
+ */
+pub fn earliest_start(input: &str) -> Result<Option<DateTime<Local>>, ParseError> {
+    match input.trim() {
+        "none" => Ok(None),
+        input => create_time(input).map(Some),
+    }
+}
+
+/** Create Duration from <HH:MM[:SS]>, decimal hours such as 7.5, or units such as 1h30m or 90m; a
+ * single dot and no other separator means decimal hours, so 9.30 is 9:18 while 9.30.00 is 9:30
+ */
+pub fn create_duration(input: &str) -> Result<Duration, ParseError> {
+    let trimmed = input.trim();
+    if trimmed.ends_with(['h', 'm', 's']) {
+        return unit_duration(trimmed).ok_or_else(|| ParseError::NotANumber {
+            input: input.to_owned(),
+            field: trimmed.to_owned(),
+        });
+    }
+    if trimmed.matches('.').count() == 1 && !trimmed.contains([':', ' ']) {
+        return decimal_hours(input);
+    }
+    let times: Vec<i64> = fields(input)?;
+    let out_of_range = || ParseError::OutOfRange {
+        input: input.to_owned(),
+    };
+    let mut duration = Duration::try_hours(times[0]).ok_or_else(out_of_range)?
+        + Duration::try_minutes(times[1]).ok_or_else(out_of_range)?;
+    if let Some(seconds) = times.get(2) {
+        duration += Duration::try_seconds(*seconds).ok_or_else(out_of_range)?;
+    }
+    Ok(duration)
+}
+
+/** Duration of numbers with units, hours before minutes before seconds, as in 1h30m, 90m, or 2h
+ */
+fn unit_duration(input: &str) -> Option<Duration> {
+    let mut units = ['h', 'm', 's'].iter();
+    let mut duration = Duration::zero();
+    let mut rest = input;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let unit = rest[digits..].chars().next()?;
+        units.find(|&&u| u == unit)?;
+        let amount: i64 = rest[..digits].parse().ok()?;
+        duration += match unit {
+            'h' => Duration::try_hours(amount)?,
+            'm' => Duration::try_minutes(amount)?,
+            _ => Duration::try_seconds(amount)?,
+        };
+        rest = &rest[digits + 1..];
+    }
+    Some(duration)
+}
+
+/** Duration of decimal hours, rounded to whole seconds
+ */
+fn decimal_hours(input: &str) -> Result<Duration, ParseError> {
+    let hours: f64 = input.trim().parse().map_err(|_| ParseError::NotANumber {
+        input: input.to_owned(),
+        field: input.trim().to_owned(),
+    })?;
+    if !hours.is_finite() {
+        return Err(ParseError::OutOfRange {
+            input: input.to_owned(),
+        });
+    }
+    Duration::try_seconds((hours * 3600.).round() as i64).ok_or_else(|| ParseError::OutOfRange {
+        input: input.to_owned(),
+    })
+}
+
+/** Create Duration from <[-]HH:MM[:SS]>, e.g. a balance that may be negative
+ */
+pub fn create_signed_duration(input: &str) -> Result<Duration, ParseError> {
+    match input.trim().strip_prefix('-') {
+        Some(magnitude) => Ok(-create_duration(magnitude)?),
+        None => create_duration(input),
+    }
+}
+
+/// Work days a weekly goal is spread over
+pub const WORKDAYS: i32 = 5;
+
+/** Daily share of a weekly goal
+ */
+pub fn weekly_share(weekly: Duration) -> Duration {
+    weekly / WORKDAYS
+}
+
+/** Replace each now, now-<minutes>, or now+<minutes> in a time or an interval such as now-30-now
+ * by the given time, truncated to the minute; the offset may have units, as in now-2h or now+1h30m
+ */
+pub fn resolve_now(input: &str, now: DateTime<Local>) -> Result<String, ParseError> {
+    let now = now.with_second(0).unwrap().with_nanosecond(0).unwrap();
+    let malformed = || ParseError::BadNowOffset {
+        input: input.to_owned(),
+    };
+    let mut resolved = String::new();
+    let mut rest = input;
+    while let Some(pos) = rest.find("now") {
+        resolved.push_str(&rest[..pos]);
+        rest = &rest[pos + 3..];
+        let mut time = now;
+        if let Some(sign @ ('+' | '-')) = rest.chars().next() {
+            let digits = rest[1..]
+                .find(|c: char| !c.is_ascii_digit() && !"hms".contains(c))
+                .unwrap_or(rest.len() - 1);
+            let amount = &rest[1..1 + digits];
+            // A dash followed by a time, as in now-12:30, separates an interval
+            if digits > 0 && !rest[1 + digits..].starts_with(SEPARATORS) {
+                let offset = if amount.chars().all(|c| c.is_ascii_digit()) {
+                    let minutes: i64 = amount.parse().map_err(|_| malformed())?;
+                    Duration::try_minutes(minutes).ok_or_else(malformed)?
+                } else {
+                    unit_duration(amount).ok_or_else(malformed)?
+                };
+                time = if sign == '+' {
+                    now + offset
+                } else {
+                    now - offset
+                };
+                rest = &rest[1 + digits..];
+            } else if sign == '+' {
+                return Err(malformed());
+            }
+        }
+        if !(rest.is_empty() || rest.starts_with('-')) {
+            return Err(malformed());
+        }
+        resolved.push_str(&time.format("%H:%M:%S").to_string());
+    }
+    resolved.push_str(rest);
+    Ok(resolved)
+}
+
+/** Time separator used by a locale such as "fi_FI.UTF-8", where 09.30 means 09:30
+ */
+pub fn locale_time_separator(locale: &str) -> char {
+    match locale.split(['_', '.', '@']).next().unwrap_or("") {
+        "da" | "fi" | "nb" | "nn" | "no" => '.',
+        _ => ':',
+    }
+}
+
+/** Short weekday name in the language of a locale; English unless known
+ */
+pub fn weekday_name(weekday: Weekday, locale: &str) -> &'static str {
+    const EN: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    const DE: [&str; 7] = ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"];
+    let names = match locale.split(['_', '.', '@']).next().unwrap_or("") {
+        "de" => DE,
+        _ => EN,
+    };
+    names[weekday.num_days_from_monday() as usize]
+}
+
+/** Locale of the environment, as set by LC_ALL, LC_TIME, or LANG
+ */
+pub fn env_locale() -> String {
+    ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default()
+}
+
+/** Floor a time to a multiple of the given minutes since midnight, 0 leaves it untouched
+ */
+pub fn floor_to_minutes(input: DateTime<Local>, granularity: u32) -> DateTime<Local> {
+    if granularity == 0 {
+        return input;
+    }
+    let excess = input.num_seconds_from_midnight() % (granularity * 60);
+    input.with_nanosecond(0).unwrap() - Duration::try_seconds(excess as i64).unwrap()
+}
+
+/** Round a duration up to a multiple of the given minutes, 0 leaves it untouched
+ */
+pub fn round_up_to_minutes(input: Duration, granularity: u32) -> Duration {
+    if granularity == 0 {
+        return input;
+    }
+    let step = granularity as i64 * 60;
+    let seconds = input.num_seconds();
+    Duration::try_seconds((seconds + step - 1).div_euclid(step) * step).unwrap()
+}
+
+/** Way a rounding policy rounds; in favor of the employee rounds the work time up, and with
+ * stamps the start down and the end up
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Up,
+    Down,
+    Nearest,
+    Favor,
+}
+
+/// Name of each direction, as in --rounding-mode
+pub const DIRECTIONS: [(&str, Direction); 4] = [
+    ("up", Direction::Up),
+    ("down", Direction::Down),
+    ("nearest", Direction::Nearest),
+    ("favor", Direction::Favor),
+];
+
+impl Direction {
+    pub fn from_name(name: &str) -> Result<Direction, ParseError> {
+        error::choice("rounding-mode", name, &DIRECTIONS)
+    }
+}
+
+/** Rounding policy of the reported work time to a multiple of some minutes, 0 being off, either
+ * of the total or of each start and end stamp
+ */
+#[derive(Clone, Copy)]
+pub struct Rounding {
+    pub minutes: u32,
+    pub direction: Direction,
+    pub stamps: bool,
+}
+
+/** Round seconds to a multiple of step: up, down, or with None to the nearest, halves up
+ */
+fn round_seconds(seconds: i64, step: i64, up: Option<bool>) -> i64 {
+    match up {
+        Some(true) => (seconds + step - 1).div_euclid(step) * step,
+        Some(false) => seconds.div_euclid(step) * step,
+        None => (seconds + step / 2).div_euclid(step) * step,
+    }
+}
+
+impl Rounding {
+    /** What the policy rounds and how, for explanations
+     */
+    pub fn describe(&self) -> String {
+        let how = match (self.direction, self.stamps) {
+            _ if self.minutes == 0 => return tr("no rounding").to_owned(),
+            (Direction::Up, false) => "work time rounded up to a multiple of {} minutes",
+            (Direction::Down, false) => "work time rounded down to a multiple of {} minutes",
+            (Direction::Nearest, false) => "work time rounded to the nearest multiple of {} minutes",
+            (Direction::Favor, false) => {
+                "work time rounded up in the employee's favor to a multiple of {} minutes"
+            }
+            (Direction::Up, true) => "start and end rounded up to a multiple of {} minutes",
+            (Direction::Down, true) => "start and end rounded down to a multiple of {} minutes",
+            (Direction::Nearest, true) => {
+                "start and end rounded to the nearest multiple of {} minutes"
+            }
+            (Direction::Favor, true) => {
+                "start rounded down and end up in the employee's favor, each to a multiple of {} minutes"
+            }
+        };
+        trf(how, &[&self.minutes])
+    }
+
+    /** Work time from start to end as reported under the policy
+     */
+    pub fn work(&self, start: NaiveDateTime, end: NaiveDateTime, work: Duration) -> Duration {
+        if self.minutes == 0 {
+            return work;
+        }
+        let step = self.minutes as i64 * 60;
+        let up = |favor| match self.direction {
+            Direction::Up => Some(true),
+            Direction::Down => Some(false),
+            Direction::Nearest => None,
+            Direction::Favor => Some(favor),
+        };
+        if self.stamps {
+            // Shift of a stamp to its multiple of the minutes since midnight
+            let shift = |stamp: NaiveDateTime, favor| {
+                let seconds = stamp.num_seconds_from_midnight() as i64;
+                Duration::try_seconds(round_seconds(seconds, step, up(favor)) - seconds).unwrap()
+            };
+            work + shift(end, true) - shift(start, false)
+        } else {
+            Duration::try_seconds(round_seconds(work.num_seconds(), step, up(true))).unwrap()
+        }
+    }
+}
+
+/** Given two timestamps <HH:MM[:SS]>-<HH:MM[:SS]> return them as interval; an end before the
+ * start is on the following day, as in 23:30-00:15.  Either may have a date, as in
+ * 2024-01-15T23:30-2024-01-16T00:15
+ */
+pub fn parse_interval(input: &str) -> Result<(DateTime<Local>, DateTime<Local>), ParseError> {
+    let not_an_interval = || ParseError::NotAnInterval {
+        input: input.to_owned(),
+    };
+    let split = interval_split(input).ok_or_else(not_an_interval)?;
+    let (start, end) = (&input[..split], &input[split + 1..]);
+    if start.trim().is_empty() || end.trim().is_empty() || interval_split(end).is_some() {
+        return Err(not_an_interval());
+    }
+    let start = create_time(start)?;
+    let end = create_time(end)?;
+    Ok((start, roll_past(end, start)))
+}
+
+/** Position of the dash between start and end of an interval; the dashes of a date come before
+ * anything but digits, and without a date the first dash follows a bare hour, as in 12-13
+ */
+fn interval_split(input: &str) -> Option<usize> {
+    input
+        .match_indices('-')
+        .map(|(pos, _)| pos)
+        .find(|&pos| input[..pos].contains(|c: char| !c.is_ascii_digit() && c != '-'))
+        .or_else(|| input.find('-').filter(|_| !is_dated(input)))
+}
+
+/** Give the times of a time or interval without a date the given one, as in 12:00-12:30 on
+ * 2024-05-17 being 2024-05-17T12:00-2024-05-17T12:30
+ */
+pub fn on_date(input: &str, date: NaiveDate) -> String {
+    let qualify = |time: &str| {
+        if is_dated(time) {
+            time.to_owned()
+        } else {
+            format!("{}T{}", date, time.trim())
+        }
+    };
+    match interval_split(input) {
+        Some(split) => format!(
+            "{}-{}",
+            qualify(&input[..split]),
+            qualify(&input[split + 1..])
+        ),
+        None => qualify(input),
+    }
+}
+
+/** Move a time by whole calendar days, keeping its time of day even across a change of daylight
+ * saving time, so that a day may last 23 or 25 hours; a time skipped on the new day is moved by
+ * 24 hours per day instead
+ */
+pub fn add_days<Tz: TimeZone>(time: DateTime<Tz>, days: i64) -> DateTime<Tz> {
+    let shift = Duration::try_days(days).unwrap();
+    (time.naive_local() + shift)
+        .and_local_timezone(time.timezone())
+        .earliest()
+        .unwrap_or(time + shift)
+}
+
+/** Move a time to the following day if it is before the given start, for shifts crossing midnight
+ */
+pub fn roll_past(time: DateTime<Local>, start: DateTime<Local>) -> DateTime<Local> {
+    if time < start {
+        add_days(time, 1)
+    } else {
+        time
+    }
+}
+
+/** Given two timestamps <HH:MM[:SS]>-<HH:MM[:SS]> extract the time between them and return as duration
+ */
+pub fn calculate_duration_from_string_ts(input: &str) -> Result<Duration, ParseError> {
+    let (start, end) = parse_interval(input)?;
+    Ok(end - start)
+}
+
+/** Intersection of two intervals, if they overlap
+ */
+pub fn overlap(
+    a: (DateTime<Local>, DateTime<Local>),
+    b: (DateTime<Local>, DateTime<Local>),
+) -> Option<(DateTime<Local>, DateTime<Local>)> {
+    let start = max(a.0, b.0);
+    let end = min(a.1, b.1);
+    if start < end {
+        Some((start, end))
+    } else {
+        None
+    }
+}
+
+/** Sort intervals and merge the overlapping or adjacent ones
+ */
+pub fn merge_intervals(
+    mut intervals: Vec<(DateTime<Local>, DateTime<Local>)>,
+) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+    intervals.sort();
+    let mut merged: Vec<(DateTime<Local>, DateTime<Local>)> = Vec::new();
+    for interval in intervals {
+        match merged.last_mut() {
+            Some(last) if interval.0 <= last.1 => last.1 = max(last.1, interval.1),
+            _ => merged.push(interval),
+        }
+    }
+    merged
+}
+
+/// Start and end of a break or other period of the day
+pub type Interval = (DateTime<Local>, DateTime<Local>);
+
+/** Clamp breaks to the working period and merge the overlapping ones, so nothing outside it or
+ * twice is deducted; without an end, breaks still to come are kept.  Breaks without length and
+ * repeated breaks are dropped.  Returns the breaks in order and a note for each one changed, and
+ * one if the breaks take up the whole working period
+ */
+pub fn clamp_breaks(
+    breaks: &[Interval],
+    start: DateTime<Local>,
+    end: Option<DateTime<Local>>,
+) -> (Vec<Interval>, Vec<String>) {
+    let period = (start, end.unwrap_or(DateTime::<Local>::MAX_UTC.into()));
+    let show = |b: Interval| format!("{}-{}", b.0.format("%H:%M:%S"), b.1.format("%H:%M:%S"));
+    let mut notes = Vec::new();
+    let mut clamped = Vec::new();
+    for (i, &break_) in breaks.iter().enumerate() {
+        if break_.0 >= break_.1 {
+            notes.push(trf("break {} has no length, skipped", &[&show(break_)]));
+            continue;
+        }
+        if breaks[..i].contains(&break_) {
+            notes.push(trf(
+                "break {} is given twice, counted once",
+                &[&show(break_)],
+            ));
+            continue;
+        }
+        match overlap(period, break_) {
+            None => notes.push(trf(
+                "break {} lies outside the working period, skipped",
+                &[&show(break_)],
+            )),
+            Some(part) if part != break_ => {
+                notes.push(trf(
+                    "break {} lies partly outside the working period, counted as {}",
+                    &[&show(break_), &show(part)],
+                ));
+                clamped.push(part);
+            }
+            Some(part) => clamped.push(part),
+        }
+    }
+    clamped.sort();
+    let mut merged: Vec<Interval> = Vec::new();
+    for break_ in clamped {
+        match merged.last_mut() {
+            Some(last) if break_.0 < last.1 => {
+                let union = (last.0, max(last.1, break_.1));
+                notes.push(trf(
+                    "breaks {} and {} overlap, counted once as {}",
+                    &[&show(*last), &show(break_), &show(union)],
+                ));
+                *last = union;
+            }
+            _ => merged.push(break_),
+        }
+    }
+    if let Some(end) = end.filter(|end| *end > start) {
+        let total: Duration = merged.iter().map(|(from, to)| *to - *from).sum();
+        if total >= end - start {
+            notes.push(trf(
+                "breaks of {} take up the whole working period {}",
+                &[&format_duration(&total), &show((start, end))],
+            ));
+        }
+    }
+    (merged, notes)
+}
+
+/** Print duration struct in a human-readable way
+ */
+pub fn format_duration(input: &Duration) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        input.num_hours().abs(),
+        (*input - Duration::try_hours(input.num_hours()).unwrap())
+            .num_minutes()
+            .abs(),
+        (*input - Duration::try_minutes(input.num_minutes()).unwrap())
+            .num_seconds()
+            .abs()
+    )
+}
+
+/** Print duration struct as hours and minutes without padding, e.g. 6:12 or 38:30
+ */
+pub fn format_duration_short(input: &Duration) -> String {
+    format!(
+        "{}{}:{:02}",
+        if *input < Duration::zero() { "-" } else { "" },
+        input.num_hours().abs(),
+        (input.num_minutes() % 60).abs()
+    )
+}
+
+/** Print duration struct with an explicit sign, e.g. +01:30:00 or -00:15:00
+ */
+pub fn format_duration_signed(input: &Duration) -> String {
+    let sign = if *input < Duration::zero() { "-" } else { "+" };
+    format!("{}{}", sign, format_duration(input))
+}
+
+/** How ties are rounded in decimal output
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RoundMode {
+    /// 0.125 -> 0.13, as f64::round does
+    #[default]
+    HalfAway,
+    /// 0.125 -> 0.12, banker's rounding to avoid bias in sums
+    HalfEven,
+}
+
+impl RoundMode {
+    pub fn from_name(name: &str) -> Result<RoundMode, ParseError> {
+        error::choice(
+            "round-mode",
+            name,
+            &[
+                ("half-away", RoundMode::HalfAway),
+                ("half-even", RoundMode::HalfEven),
+            ],
+        )
+    }
+}
+
+pub fn round(input: f64, digit: i32, mode: RoundMode) -> f64 {
+    let scaled = input * (10_f64.powi(digit));
+    let tmp: f64 = match mode {
+        RoundMode::HalfAway => scaled.round(),
+        RoundMode::HalfEven => scaled.round_ties_even(),
+    };
+    tmp / (10_f64.powi(digit))
+}
+
+pub fn format_duration_hours(input: &Duration, mode: RoundMode) -> String {
+    format!(
+        "{}",
+        round(
+            input.num_hours().abs() as f64
+                + (*input - Duration::try_hours(input.num_hours()).unwrap())
+                    .num_minutes()
+                    .abs() as f64
+                    / 60.,
+            2,
+            mode
+        )
+    )
+}
+
+/** Serialize durations as whole seconds, so consumers don't have to parse HH:MM:SS
+ */
+mod seconds {
+    use chrono::Duration;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(input: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(input.num_seconds())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let secs = i64::deserialize(deserializer)?;
+        Duration::try_seconds(secs).ok_or_else(|| de::Error::custom("duration out of range"))
+    }
+}
+
+/** Durations keyed by name, as whole seconds
+ */
+mod seconds_by_name {
+    use chrono::Duration;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::collections::BTreeMap;
+
+    pub fn serialize<S: Serializer>(
+        input: &BTreeMap<String, Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(input.iter().map(|(name, d)| (name, d.num_seconds())))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<String, Duration>, D::Error> {
+        BTreeMap::<String, i64>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(name, secs)| {
+                Duration::try_seconds(secs)
+                    .map(|d| (name, d))
+                    .ok_or_else(|| de::Error::custom("duration out of range"))
+            })
+            .collect()
+    }
+}
+
+/** Everything computed for a single working day; all output is rendered from this
+ */
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+pub struct Summary {
+    pub now: DateTime<Local>,
+    /// Actual clock-in, used for display and the 9h and maximum projections
+    pub clock_in: DateTime<Local>,
+    /// Start used for the worked time and goal, possibly clamped to the earliest allowed start
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+    #[serde(rename = "goal_seconds", with = "seconds")]
+    pub workday: Duration,
+    #[serde(rename = "break_time_seconds", with = "seconds")]
+    pub break_time: Duration,
+    #[serde(rename = "longest_break_seconds", with = "seconds")]
+    pub longest_break_time: Duration,
+    /// Longest break guessed from a break total and count rather than measured
+    pub longest_break_estimated: bool,
+    #[serde(rename = "work_time_seconds", with = "seconds")]
+    pub work_time: Duration,
+    /// Work time minus daily goal, positive once the goal is exceeded
+    #[serde(rename = "delta_seconds", with = "seconds")]
+    pub delta: Duration,
+    /// Time left until the goal is done, zero from then on
+    #[serde(rename = "remaining_seconds", with = "seconds")]
+    pub remaining: Duration,
+    pub done: bool,
+    /// Share of the daily goal already worked; kept unrounded, only text output rounds
+    pub percent: f64,
+    /// Most a day may be worked
+    #[serde(rename = "max_daily_seconds", with = "seconds")]
+    pub max_daily: Duration,
+    /// Time left until the daily maximum is reached
+    #[serde(rename = "max_remaining_seconds", with = "seconds")]
+    pub max_dur: Duration,
+    /// Clock time at which the daily goal is reached
+    pub projected_goal: DateTime<Local>,
+    pub projected_9h: DateTime<Local>,
+    /// Clock time at which the daily maximum is reached; named for the default of 10h
+    pub projected_10h: DateTime<Local>,
+    #[serde(skip)]
+    pub round_mode: RoundMode,
+    /// How the break time was arrived at, for --explain-breaks
+    #[serde(skip)]
+    pub break_explanation: Vec<String>,
+}
+
+impl Summary {
+    pub const CSV_HEADER: &'static str =
+        "date,start,end,work_time,break_time,remaining,projected_goal,projected_9h,projected_10h,done";
+
+    /** One CSV row for spreadsheets, durations and times as <HH:MM:SS>; without end time the
+     * end column stays empty
+     */
+    pub fn to_csv(&self) -> String {
+        let time = |t: DateTime<Local>| t.format("%H:%M:%S").to_string();
+        format!(
+            "{},{},{},{},{},{},{},{},{},{}",
+            self.start.date_naive(),
+            time(self.clock_in),
+            self.end.map(time).unwrap_or_default(),
+            format_duration(&self.work_time),
+            format_duration(&self.break_time),
+            format_duration(&self.remaining),
+            time(self.projected_goal),
+            time(self.projected_9h),
+            time(self.projected_10h),
+            self.done
+        )
+    }
+}
+
+/** Knobs of the calculation besides the times themselves
+ */
+pub struct Options {
+    pub round_mode: RoundMode,
+    /// Breaks required by worked duration, replacing the default 30/45 minutes
+    pub break_policy: Option<Box<dyn BreakPolicy>>,
+    /// Being short of the goal by up to this much still counts as done
+    pub goal_tolerance: Duration,
+    /// Total break time and number of breaks, when the single breaks aren't known
+    pub break_total: Option<(Duration, u32)>,
+    /// Without end time and breaks, deduct the default break only from the projections,
+    /// not from the time worked so far
+    pub break_not_yet: bool,
+    /// Most a day may be worked, the latest projection
+    pub max_daily: Duration,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            round_mode: RoundMode::default(),
+            break_policy: None,
+            goal_tolerance: Duration::zero(),
+            break_total: None,
+            break_not_yet: false,
+            max_daily: Duration::try_hours(10).unwrap(),
+        }
+    }
+}
+
+pub fn summarize(
+    now: DateTime<Local>,
+    clock_in: DateTime<Local>,
+    start: DateTime<Local>,
+    end: Option<DateTime<Local>>,
+    workday: Duration,
+    breaks: &[Interval],
+    options: &Options,
+) -> Summary {
+    let standard = break_policy::Standard::default();
+    let policy = options.break_policy.as_deref().unwrap_or(&standard);
+    let break_large = policy.longest();
+
+    let total_time = end.unwrap_or(now) - start;
+    let mut break_time = Duration::try_seconds(0).unwrap();
+    let mut longest_break_time = Duration::try_seconds(0).unwrap();
+    let mut explanation = vec![trf("present for {}", &[&format_duration(&total_time)])];
+    if let Some((total, count)) = options.break_total {
+        break_time = total;
+        longest_break_time = total / count as i32;
+        explanation.push(trf(
+            "break total {} given for {} break(s), longest assumed to be {}",
+            &[
+                &format_duration(&total),
+                &count,
+                &format_duration(&longest_break_time),
+            ],
+        ));
+    } else if breaks.is_empty() {
+        // The default break is taken in one piece
+        break_time = policy.required(total_time);
+        longest_break_time = break_time;
+        explanation.push(trf("no breaks given, {}", &[&policy.explain(total_time)]));
+    } else {
+        for &(from, to) in breaks {
+            let break_duration = to - from;
+            if break_duration > longest_break_time {
+                longest_break_time = break_duration;
+            }
+            break_time += break_duration;
+            explanation.push(trf(
+                "break {}-{}: {}",
+                &[
+                    &from.format("%H:%M:%S"),
+                    &to.format("%H:%M:%S"),
+                    &format_duration(&break_duration),
+                ],
+            ));
+        }
+        explanation.push(trf(
+            "sum of {} break(s): {}",
+            &[&breaks.len(), &format_duration(&break_time)],
+        ));
+    }
+
+    // Break already taken by now, as opposed to the one planned for the whole day
+    let default_break = breaks.is_empty() && options.break_total.is_none();
+    let taken_break = if default_break && end.is_none() && options.break_not_yet {
+        explanation.push(tr("break not taken yet, only deducted from the projections").to_owned());
+        Duration::zero()
+    } else {
+        break_time
+    };
+
+    let work_time = total_time - taken_break;
+    let done = work_time > workday - options.goal_tolerance;
+    let projected_10h = clock_in + options.max_daily + max(break_large, break_time);
+    Summary {
+        now,
+        clock_in,
+        start,
+        end,
+        workday,
+        break_time: taken_break,
+        longest_break_time,
+        longest_break_estimated: options.break_total.is_some(),
+        work_time,
+        delta: work_time - workday,
+        remaining: if done {
+            Duration::zero()
+        } else {
+            workday - work_time
+        },
+        done,
+        // Nothing to do is all done, as on a day off
+        percent: if workday.is_zero() {
+            100.0
+        } else {
+            100.0 * (work_time.num_nanoseconds().unwrap() as f64)
+                / (workday.num_nanoseconds().unwrap() as f64)
+        },
+        max_daily: options.max_daily,
+        max_dur: projected_10h - now,
+        projected_goal: start + workday + break_time,
+        projected_9h: clock_in + Duration::try_hours(9).unwrap() + max(break_large, break_time),
+        projected_10h,
+        round_mode: options.round_mode,
+        break_explanation: explanation,
+    }
+}
+
+/** Parts of the core window not covered by work: before the start, after the end, or during breaks
+ */
+pub fn core_gaps(
+    core: (DateTime<Local>, DateTime<Local>),
+    start: DateTime<Local>,
+    end: Option<DateTime<Local>>,
+    breaks: &[(DateTime<Local>, DateTime<Local>)],
+) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+    let mut gaps = Vec::new();
+    if start > core.0 {
+        gaps.push((core.0, min(start, core.1)));
+    }
+    if let Some(end) = end {
+        if end < core.1 {
+            gaps.push((max(end, core.0), core.1));
+        }
+    }
+    gaps.extend(breaks.iter().filter_map(|b| overlap(core, *b)));
+    gaps.sort();
+    gaps
+}
+
+/** Work time falling into the daily premium window, e.g. 20:00-06:00 for night shifts
+ *
+ * Both the shift and the window may cross midnight; times before the start are taken to be on
+ * the following day.
+ */
+pub fn premium_time(
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    breaks: &[(DateTime<Local>, DateTime<Local>)],
+    window: (NaiveTime, NaiveTime),
+) -> Duration {
+    let roll = |t: DateTime<Local>| roll_past(t, start);
+    let work = (start, roll(end));
+    let breaks = merge_intervals(breaks.iter().map(|b| (roll(b.0), roll(b.1))).collect());
+
+    let mut premium = Duration::zero();
+    let mut date = start.date_naive().pred_opt().unwrap();
+    while date <= work.1.date_naive() {
+        let local = |time: NaiveTime| {
+            let at = date.and_time(time);
+            // A window edge skipped by daylight saving time falls an hour later
+            at.and_local_timezone(Local)
+                .earliest()
+                .or_else(|| {
+                    (at + Duration::try_hours(1).unwrap())
+                        .and_local_timezone(Local)
+                        .earliest()
+                })
+                .unwrap()
+        };
+        let from = local(window.0);
+        let mut to = local(window.1);
+        if to <= from {
+            to = add_days(to, 1);
+        }
+        if let Some(worked) = overlap(work, (from, to)) {
+            premium += worked.1 - worked.0;
+            for break_ in breaks.iter().filter_map(|b| overlap(worked, *b)) {
+                premium -= break_.1 - break_.0;
+            }
+        }
+        date = date.succ_opt().unwrap();
+    }
+    premium
+}
+
+/** A single value of the summary that can be printed on its own via `--field`
+ */
+pub struct Field {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub value: fn(&Summary) -> String,
+}
+
+pub const FIELDS: &[Field] = &[
+    Field {
+        name: "work_time",
+        description: "Time worked, breaks deducted <HH:MM:SS>",
+        value: |s| format_duration(&s.work_time),
+    },
+    Field {
+        name: "remaining",
+        description: "Time left until the daily goal is reached, 00:00:00 once done <HH:MM:SS>",
+        value: |s| format_duration(&s.remaining),
+    },
+    Field {
+        name: "percent",
+        description: "Share of the daily goal already worked, in percent",
+        value: |s| format!("{}", round(s.percent, 2, s.round_mode)),
+    },
+    Field {
+        name: "delta",
+        description: "Work time minus daily goal <+/-HH:MM:SS>",
+        value: |s| format_duration_signed(&s.delta),
+    },
+    Field {
+        name: "end_time",
+        description: "Clock time at which the daily goal is reached <HH:MM:SS>",
+        value: |s| s.projected_goal.format("%H:%M:%S").to_string(),
+    },
+    Field {
+        name: "worked",
+        description: "Time worked, breaks deducted <H:MM>",
+        value: |s| format_duration_short(&s.work_time),
+    },
+    Field {
+        name: "goal",
+        description: "Daily goal <H:MM>",
+        value: |s| format_duration_short(&s.workday),
+    },
+    Field {
+        name: "left",
+        description: "Time left until the daily goal is reached, 0:00 once done <H:MM>",
+        value: |s| format_duration_short(&s.remaining),
+    },
+    Field {
+        name: "end",
+        description: "Clock time at which the daily goal is reached <HH:MM>",
+        value: |s| s.projected_goal.format("%H:%M").to_string(),
+    },
+    Field {
+        name: "end_9h",
+        description: "Clock time at which 9 hours are worked <HH:MM>",
+        value: |s| s.projected_9h.format("%H:%M").to_string(),
+    },
+    Field {
+        name: "end_max",
+        description: "Clock time at which the daily maximum is reached <HH:MM>",
+        value: |s| s.projected_10h.format("%H:%M").to_string(),
+    },
+    Field {
+        name: "total-minutes",
+        description: "Time worked in whole minutes",
+        value: |s| format!("{}", s.work_time.num_minutes()),
+    },
+    Field {
+        name: "work_seconds",
+        description: "Time worked in whole seconds",
+        value: |s| s.work_time.num_seconds().to_string(),
+    },
+    Field {
+        name: "break_seconds",
+        description: "Break time in whole seconds",
+        value: |s| s.break_time.num_seconds().to_string(),
+    },
+    Field {
+        name: "remaining_seconds",
+        description: "Time left until the daily goal in whole seconds, 0 once done",
+        value: |s| s.remaining.num_seconds().to_string(),
+    },
+    Field {
+        name: "delta_seconds",
+        description: "Work time minus daily goal in whole seconds, negative while short",
+        value: |s| s.delta.num_seconds().to_string(),
+    },
+];
+
+/** Look up a field by name, treating '-' and '_' as equivalent
+ */
+pub fn find_field(name: &str) -> Option<&'static Field> {
+    let normalize = |n: &str| n.replace('-', "_");
+    FIELDS.iter().find(|f| normalize(f.name) == normalize(name))
+}
+
+/** Replace each {field} of a template by the value of the field, see `fields`; anything else is
+ * kept as written
+ */
+pub fn fill(template: &str, s: &Summary) -> String {
+    FIELDS.iter().fold(template.to_owned(), |filled, field| {
+        filled.replace(&format!("{{{}}}", field.name), &(field.value)(s))
+    })
+}
+
+/** Cut a line down to the given number of columns
+ */
+pub fn fit(line: &str, width: usize) -> String {
+    let line: String = line.chars().take(width).collect();
+    line.trim_end().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hms(h: i64, m: i64, s: i64) -> Duration {
+        Duration::try_hours(h).unwrap()
+            + Duration::try_minutes(m).unwrap()
+            + Duration::try_seconds(s).unwrap()
+    }
+
+    #[test]
+    fn format_duration_keeps_remainders() {
+        assert_eq!(format_duration(&hms(1, 0, 59)), "01:00:59");
+        assert_eq!(format_duration(&hms(8, 59, 0)), "08:59:00");
+        assert_eq!(format_duration(&hms(0, 0, 0)), "00:00:00");
+    }
+
+    #[test]
+    fn format_duration_signed_negative() {
+        assert_eq!(format_duration_signed(&-hms(0, 15, 0)), "-00:15:00");
+        assert_eq!(format_duration_signed(&hms(1, 30, 0)), "+01:30:00");
+        assert_eq!(format_duration(&-hms(1, 30, 5)), "01:30:05");
+    }
+
+    #[test]
+    fn round_modes() {
+        assert_eq!(round(0.125, 2, RoundMode::HalfAway), 0.13);
+        assert_eq!(round(0.125, 2, RoundMode::HalfEven), 0.12);
+        assert_eq!(round(7.125, 2, RoundMode::HalfEven), 7.12);
+        assert_eq!(round(7.135, 2, RoundMode::HalfEven), 7.14);
+        assert_eq!(
+            format_duration_hours(&hms(7, 45, 0), RoundMode::HalfAway),
+            "7.75"
+        );
+        assert_eq!(
+            format_duration_hours(&hms(8, 0, 0), RoundMode::HalfAway),
+            "8"
+        );
+    }
+
+    #[test]
+    fn create_duration_parses_and_rejects() {
+        assert_eq!(create_duration("7:48"), Ok(hms(7, 48, 0)));
+        assert_eq!(create_duration("0:00:30"), Ok(hms(0, 0, 30)));
+        assert!(matches!(
+            create_duration("9"),
+            Err(ParseError::WrongFieldCount { .. })
+        ));
+        assert!(matches!(
+            create_duration("9:ab"),
+            Err(ParseError::NotANumber { .. })
+        ));
+        assert!(matches!(
+            create_duration("9:"),
+            Err(ParseError::EmptyField { .. })
+        ));
+    }
+
+    #[test]
+    fn create_duration_decimal_hours() {
+        assert_eq!(create_duration("7.5"), create_duration("7:30"));
+        assert_eq!(create_duration("38.5"), Ok(hms(38, 30, 0)));
+        assert_eq!(create_duration("0.25"), Ok(hms(0, 15, 0)));
+        assert!(matches!(
+            create_duration("7.5:30"),
+            Err(ParseError::MixedSeparators { .. })
+        ));
+        assert!(matches!(
+            create_duration("7.x"),
+            Err(ParseError::NotANumber { .. })
+        ));
+    }
+
+    #[test]
+    fn create_duration_units() {
+        assert_eq!(create_duration("1h30m"), Ok(hms(1, 30, 0)));
+        assert_eq!(create_duration("90m"), Ok(hms(1, 30, 0)));
+        assert_eq!(create_duration("2h"), Ok(hms(2, 0, 0)));
+        assert_eq!(create_duration("45s"), Ok(hms(0, 0, 45)));
+        for input in ["30m1h", "1h1h", "h", "1x30m"] {
+            assert!(matches!(
+                create_duration(input),
+                Err(ParseError::NotANumber { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn create_signed_duration_negates() {
+        assert_eq!(create_signed_duration("-1:30"), Ok(-hms(1, 30, 0)));
+        assert_eq!(create_signed_duration("2:00"), Ok(hms(2, 0, 0)));
+    }
+
+    #[test]
+    fn clamp_breaks_to_period() {
+        let interval = |s: &str| parse_interval(s).unwrap();
+        let (breaks, notes) = clamp_breaks(
+            &[interval("12:00-12:30"), interval("12:50-13:20")],
+            create_time("13:00").unwrap(),
+            Some(create_time("17:00").unwrap()),
+        );
+        assert_eq!(breaks, vec![interval("13:00-13:20")]);
+        assert_eq!(notes.len(), 2);
+
+        let (breaks, _) = clamp_breaks(
+            &[interval("16:30-17:30")],
+            create_time("08:00").unwrap(),
+            None,
+        );
+        assert_eq!(breaks, vec![interval("16:30-17:30")]);
+    }
+
+    #[test]
+    fn clamp_breaks_drops_duplicates_and_empty() {
+        let interval = |s: &str| parse_interval(s).unwrap();
+        let noon = create_time("12:00").unwrap();
+        let (breaks, notes) = clamp_breaks(
+            &[
+                interval("12:00-12:30"),
+                interval("12:00-12:30"),
+                (noon, noon),
+            ],
+            create_time("08:00").unwrap(),
+            Some(create_time("17:00").unwrap()),
+        );
+        assert_eq!(breaks, vec![interval("12:00-12:30")]);
+        assert_eq!(
+            notes,
+            [
+                "break 12:00:00-12:30:00 is given twice, counted once",
+                "break 12:00:00-12:00:00 has no length, skipped"
+            ]
+        );
+
+        let (breaks, notes) = clamp_breaks(
+            &[interval("07:00-10:00")],
+            create_time("08:00").unwrap(),
+            Some(create_time("09:00").unwrap()),
+        );
+        assert_eq!(breaks, vec![interval("08:00-09:00")]);
+        assert_eq!(
+            notes.last().unwrap(),
+            "breaks of 01:00:00 take up the whole working period 08:00:00-09:00:00"
+        );
+    }
+
+    #[test]
+    fn clamp_breaks_merges_overlaps() {
+        let interval = |s: &str| parse_interval(s).unwrap();
+        let (breaks, notes) = clamp_breaks(
+            &[
+                interval("12:30-13:00"),
+                interval("12:00-12:45"),
+                interval("15:00-15:10"),
+                interval("15:10-15:20"),
+            ],
+            create_time("08:00").unwrap(),
+            Some(create_time("17:00").unwrap()),
+        );
+        assert_eq!(
+            breaks,
+            vec![
+                interval("12:00-13:00"),
+                interval("15:00-15:10"),
+                interval("15:10-15:20")
+            ]
+        );
+        assert_eq!(notes.len(), 1);
+    }
+
+    #[test]
+    fn resolve_now_offsets() {
+        let now = create_time("12:00:42").unwrap();
+        assert_eq!(resolve_now("now", now), Ok("12:00:00".to_string()));
+        assert_eq!(resolve_now("now-15", now), Ok("11:45:00".to_string()));
+        assert_eq!(resolve_now("now+30", now), Ok("12:30:00".to_string()));
+        assert_eq!(
+            resolve_now("now-30-now", now),
+            Ok("11:30:00-12:00:00".to_string())
+        );
+        assert_eq!(
+            resolve_now("now-12:30", now),
+            Ok("12:00:00-12:30".to_string())
+        );
+        assert_eq!(resolve_now("now-2h", now), Ok("10:00:00".to_string()));
+        assert_eq!(
+            resolve_now("now-1h30m-now", now),
+            Ok("10:30:00-12:00:00".to_string())
+        );
+        assert_eq!(resolve_now("8:15", now), Ok("8:15".to_string()));
+        assert!(matches!(
+            resolve_now("now*5", now),
+            Err(ParseError::BadNowOffset { .. })
+        ));
+        assert!(matches!(
+            resolve_now("now+", now),
+            Err(ParseError::BadNowOffset { .. })
+        ));
+    }
+
+    #[test]
+    fn days_across_daylight_saving_time() {
+        use chrono_tz::Europe::Berlin;
+        let at = |s: &str| {
+            NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M")
+                .unwrap()
+                .and_local_timezone(Berlin)
+                .unwrap()
+        };
+        let fall = add_days(at("2024-10-26T23:30"), 1);
+        assert_eq!(fall, at("2024-10-27T23:30"));
+        assert_eq!(
+            fall - at("2024-10-26T23:30"),
+            Duration::try_hours(25).unwrap()
+        );
+        let spring = add_days(at("2024-03-30T22:00"), 1);
+        assert_eq!(
+            spring - at("2024-03-30T22:00"),
+            Duration::try_hours(23).unwrap()
+        );
+        // 2:30 doesn't exist on the day clocks go forward
+        assert_eq!(
+            add_days(at("2024-03-30T02:30"), 1) - at("2024-03-30T02:30"),
+            Duration::try_hours(24).unwrap()
+        );
+    }
+
+    #[test]
+    fn rounding_policies() {
+        let at = |t: &str| create_time(t).unwrap().naive_local();
+        let (start, end) = (at("8:07"), at("16:38"));
+        let work = hms(8, 1, 0);
+        let rounding = |minutes, direction, stamps| Rounding {
+            minutes,
+            direction,
+            stamps,
+        };
+        assert_eq!(
+            rounding(0, Direction::Up, false).work(start, end, work),
+            work
+        );
+        assert_eq!(
+            rounding(15, Direction::Nearest, false).work(start, end, work),
+            hms(8, 0, 0)
+        );
+        assert_eq!(
+            rounding(15, Direction::Up, false).work(start, end, work),
+            hms(8, 15, 0)
+        );
+        // 8:00 to 16:45 both in the employee's favor and to the nearest, 8:00 to 16:30 down
+        assert_eq!(
+            rounding(15, Direction::Favor, true).work(start, end, work),
+            hms(8, 15, 0)
+        );
+        assert_eq!(
+            rounding(15, Direction::Nearest, true).work(start, end, work),
+            hms(8, 15, 0)
+        );
+        assert_eq!(
+            rounding(15, Direction::Down, true).work(start, end, work),
+            hms(8, 0, 0)
+        );
+        assert_eq!(rounding(0, Direction::Up, false).describe(), "no rounding");
+        assert_eq!(
+            rounding(15, Direction::Nearest, true).describe(),
+            "start and end rounded to the nearest multiple of 15 minutes"
+        );
+    }
+
+    #[test]
+    fn round_up_to_billing_increment() {
+        assert_eq!(round_up_to_minutes(hms(7, 7, 0), 15), hms(7, 15, 0));
+        assert_eq!(round_up_to_minutes(hms(7, 15, 0), 15), hms(7, 15, 0));
+        assert_eq!(round_up_to_minutes(hms(7, 15, 1), 15), hms(7, 30, 0));
+        assert_eq!(round_up_to_minutes(hms(7, 7, 30), 0), hms(7, 7, 30));
+        assert_eq!(
+            format_duration_hours(&round_up_to_minutes(hms(7, 7, 0), 15), RoundMode::HalfAway),
+            "7.25"
+        );
+    }
+
+    #[test]
+    fn earliest_start_none_keeps_start() {
+        let start = create_time("5:30").unwrap();
+        let clamp = |input: &str| match earliest_start(input).unwrap() {
+            Some(earliest) => max(start, earliest),
+            None => start,
+        };
+        assert_eq!(clamp("none"), start);
+        assert_eq!(clamp("6:00"), create_time("6:00").unwrap());
+        assert_eq!(clamp("5:00"), start);
+    }
+
+    #[test]
+    fn dated_times_cross_days() {
+        let span = |from: &str, to: &str| create_time(to).unwrap() - create_time(from).unwrap();
+        assert_eq!(span("2024-01-15T22:00", "2024-01-16T06:00"), hms(8, 0, 0));
+        assert_eq!(
+            span("2024-01-31T22:00", "2024-02-01T06:30:15"),
+            hms(8, 30, 15)
+        );
+        assert_eq!(
+            create_time("2024-01-15T22:00").unwrap().date_naive(),
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()
+        );
+        assert!(matches!(
+            create_time("2024-13-01T08:00"),
+            Err(ParseError::NotADate { .. })
+        ));
+    }
+
+    #[test]
+    fn day_offsets() {
+        assert_eq!(day_offset("+1d02:30"), Ok((Some(1), "02:30")));
+        assert_eq!(day_offset("02:30"), Ok((None, "02:30")));
+        assert!(matches!(
+            day_offset("+xd02:30"),
+            Err(ParseError::NotADate { .. })
+        ));
+        assert!(matches!(
+            day_offset("+02:30"),
+            Err(ParseError::NotADate { .. })
+        ));
+    }
+
+    #[test]
+    fn times_on_date() {
+        let date = NaiveDate::from_ymd_opt(2024, 5, 17).unwrap();
+        assert_eq!(on_date("8:00", date), "2024-05-17T8:00");
+        assert_eq!(
+            on_date("12:00-2024-05-18T00:30", date),
+            "2024-05-17T12:00-2024-05-18T00:30"
+        );
+        let (from, to) = parse_interval(&on_date("23:30-00:15", date)).unwrap();
+        assert_eq!(from.date_naive(), date);
+        assert_eq!(to - from, hms(0, 45, 0));
+    }
+
+    #[test]
+    fn malformed_intervals() {
+        for input in ["12:00", "12:00-", "12:00-13:00-14:00"] {
+            assert_eq!(
+                parse_interval(input),
+                Err(ParseError::NotAnInterval {
+                    input: input.to_owned()
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn dated_intervals() {
+        let (from, to) = parse_interval("2024-01-31T23:30-2024-02-01T00:15").unwrap();
+        assert_eq!(to - from, hms(0, 45, 0));
+        let (from, to) = parse_interval("23:30-00:15").unwrap();
+        assert_eq!(to - from, hms(0, 45, 0));
+        assert!(matches!(
+            parse_interval("12:00"),
+            Err(ParseError::NotAnInterval { .. })
+        ));
+    }
+
+    #[test]
+    fn flexible_separators() {
+        assert_eq!(extract("9.30"), Ok(vec![9, 30]));
+        assert_eq!(extract("9 30"), Ok(vec![9, 30]));
+        assert_eq!(extract("9:30"), Ok(vec![9, 30]));
+        assert_eq!(extract("9.30.15"), Ok(vec![9, 30, 15]));
+        assert_eq!(extract("8"), Ok(vec![8, 0]));
+        assert_eq!(extract("8:30am"), Ok(vec![8, 30]));
+        assert_eq!(extract("5 PM"), Ok(vec![17, 0]));
+        assert_eq!(extract("12am"), Ok(vec![0, 0]));
+        assert_eq!(extract("12:15pm"), Ok(vec![12, 15]));
+        assert!(matches!(
+            extract("13pm"),
+            Err(ParseError::OutOfRange { .. })
+        ));
+        assert_eq!(create_duration("9 30"), Ok(hms(9, 30, 0)));
+        assert_eq!(create_duration("9.30"), Ok(hms(9, 18, 0)));
+        assert_eq!(create_duration("9.30.00"), Ok(hms(9, 30, 0)));
+        assert!(matches!(
+            extract("9:30.15"),
+            Err(ParseError::MixedSeparators { .. })
+        ));
+        assert!(matches!(
+            extract("9 30:15"),
+            Err(ParseError::MixedSeparators { .. })
+        ));
+        let (from, to) = parse_interval("9.30-10.00").unwrap();
+        assert_eq!(to - from, hms(0, 30, 0));
+        assert_eq!(
+            calculate_duration_from_string_ts("12 00-12 45"),
+            Ok(hms(0, 45, 0))
+        );
+    }
+
+    #[test]
+    fn weekly_goal_share() {
+        assert_eq!(weekly_share(hms(39, 0, 0)), hms(7, 48, 0));
+        assert_eq!(weekly_share(hms(40, 0, 0)), hms(8, 0, 0));
+    }
+
+    #[test]
+    fn summarize_sums_breaks() {
+        let at = |t: &str| create_time(t).unwrap();
+        let breaks = [
+            parse_interval("12:00-12:30").unwrap(),
+            parse_interval("15:00-15:10").unwrap(),
+        ];
+        let summary = summarize(
+            at("17:00"),
+            at("08:00"),
+            at("08:00"),
+            None,
+            hms(8, 0, 0),
+            &breaks,
+            &Options::default(),
+        );
+        assert_eq!(summary.break_time, hms(0, 40, 0));
+        assert_eq!(summary.longest_break_time, hms(0, 30, 0));
+        assert_eq!(summary.work_time, hms(8, 20, 0));
+        assert!(summary.done);
+    }
+
+    #[test]
+    fn summary_csv_row() {
+        let at = |t: &str| create_time(t).unwrap();
+        let summary = summarize(
+            at("12:00"),
+            at("08:00"),
+            at("08:00"),
+            None,
+            hms(8, 0, 0),
+            &[],
+            &Options::default(),
+        );
+        let row = summary.to_csv();
+        assert_eq!(
+            row.split(',').count(),
+            Summary::CSV_HEADER.split(',').count()
+        );
+        assert!(
+            row.ends_with(",08:00:00,,03:30:00,00:30:00,04:30:00,16:30:00,17:45:00,18:45:00,false")
+        );
+    }
+
+    #[test]
+    fn summarize_default_break() {
+        let at = |t: &str| create_time(t).unwrap();
+        let summary = summarize(
+            at("18:00"),
+            at("08:00"),
+            at("08:00"),
+            None,
+            hms(8, 0, 0),
+            &[],
+            &Options::default(),
+        );
+        assert_eq!(summary.break_time, hms(0, 45, 0));
+        assert_eq!(summary.longest_break_time, hms(0, 45, 0));
+        assert_eq!(summary.projected_goal, at("16:45"));
+    }
+
+    #[test]
+    fn fill_template() {
+        let at = |t: &str| create_time(t).unwrap();
+        let summary = summarize(
+            at("14:42"),
+            at("08:00"),
+            at("08:00"),
+            None,
+            hms(7, 48, 0),
+            &[],
+            &Options::default(),
+        );
+        assert_eq!(
+            fill("{worked}/{goal} > {end} {unknown}", &summary),
+            "6:12/7:48 > 16:18 {unknown}"
+        );
+        assert_eq!(format_duration_short(&-hms(0, 5, 0)), "-0:05");
+    }
+
+    #[test]
+    fn break_in_core_hours_is_a_gap() {
+        let at = |t: &str| create_time(t).unwrap();
+        let core = parse_interval("10:00-15:00").unwrap();
+        let lunch = parse_interval("12:00-12:30").unwrap();
+        assert_eq!(
+            core_gaps(core, at("08:00"), Some(at("17:00")), &[lunch]),
+            [lunch]
+        );
+        assert_eq!(
+            core_gaps(core, at("10:30"), Some(at("17:00")), &[lunch]),
+            [(at("10:00"), at("10:30")), lunch]
+        );
+        assert!(core_gaps(core, at("08:00"), Some(at("17:00")), &[]).is_empty());
+    }
+
+    #[test]
+    fn end_floored_to_granularity() {
+        let at = |t: &str| create_time(t).unwrap();
+        assert_eq!(floor_to_minutes(at("17:07:30"), 15), at("17:00"));
+        assert_eq!(floor_to_minutes(at("17:15"), 15), at("17:15"));
+        assert_eq!(floor_to_minutes(at("17:07"), 0), at("17:07"));
+    }
+
+    #[test]
+    fn summary_round_trips_through_msgpack() {
+        let at = |t: &str| create_time(t).unwrap();
+        let summary = Summary {
+            // Not serialized
+            break_explanation: Vec::new(),
+            ..summarize(
+                at("17:00"),
+                at("08:00"),
+                at("08:00"),
+                Some(at("16:30")),
+                hms(8, 0, 0),
+                &[parse_interval("12:00-12:30").unwrap()],
+                &Options::default(),
+            )
+        };
+        let bytes = rmp_serde::to_vec_named(&summary).unwrap();
+        assert_eq!(rmp_serde::from_slice::<Summary>(&bytes).unwrap(), summary);
+    }
+
+    #[test]
+    fn premium_portion_of_a_night_shift() {
+        let at = |t: &str| create_time(t).unwrap();
+        let window = |t: &str| at(t).time();
+        let night = (window("20:00"), window("06:00"));
+        assert_eq!(
+            premium_time(at("22:00"), at("02:00"), &[], night),
+            hms(4, 0, 0)
+        );
+        assert_eq!(
+            premium_time(
+                at("22:00"),
+                at("02:00"),
+                &[parse_interval("23:45-00:15").unwrap()],
+                night
+            ),
+            hms(3, 30, 0)
+        );
+        assert_eq!(
+            premium_time(at("18:00"), at("23:00"), &[], night),
+            hms(3, 0, 0)
+        );
+    }
+
+    #[test]
+    fn weekday_names_by_locale() {
+        assert_eq!(weekday_name(Weekday::Mon, "de"), "Mo");
+        assert_eq!(weekday_name(Weekday::Sun, "de_DE.UTF-8"), "So");
+        assert_eq!(weekday_name(Weekday::Mon, "en_US"), "Mon");
+        assert_eq!(weekday_name(Weekday::Mon, "C"), "Mon");
+    }
+
+    #[test]
+    fn seconds_fields_unformatted() {
+        let at = |t: &str| create_time(t).unwrap();
+        let value = |name: &str, end: &str| {
+            let summary = summarize(
+                at("17:00"),
+                at("08:00"),
+                at("08:00"),
+                Some(at(end)),
+                hms(8, 0, 0),
+                &[],
+                &Options::default(),
+            );
+            (find_field(name).unwrap().value)(&summary)
+        };
+        assert_eq!(value("work-seconds", "16:00"), "27000");
+        assert_eq!(value("break_seconds", "16:00"), "1800");
+        assert_eq!(value("remaining-seconds", "16:00"), "1800");
+        assert_eq!(value("remaining-seconds", "17:00"), "0");
+        assert_eq!(value("delta-seconds", "16:00"), "-1800");
+        assert_eq!(value("delta-seconds", "17:00"), "1800");
+    }
+}
@@ -1,271 +1,3422 @@
-use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime};
-use clap::{Arg, ArgAction, Command};
-use std::{cmp::max, panic};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Timelike, Weekday};
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::cmp::{max, min};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use time_rust::break_policy::{self, BreakPolicy};
+use time_rust::error::{choice, ParseError};
+use time_rust::i18n::{self, tr, trf};
+use time_rust::{
+    add_days, clamp_breaks, core_gaps, create_duration, create_signed_duration, create_time,
+    day_offset, earliest_start, env_locale, fill, find_field, fit, floor_to_minutes,
+    format_duration, format_duration_hours, format_duration_signed, is_dated,
+    locale_time_separator, on_date, overlap, parse_interval, premium_time, resolve_now, roll_past,
+    round, round_up_to_minutes, summarize, weekday_name, weekly_share, Direction, Options,
+    RoundMode, Rounding, Summary, FIELDS, WORKDAYS,
+};
+use time_rust::{
+    audit, config, dayfile, days_off, events, export, heatmap, ics, idle, notify, pomodoro, push,
+    report, rules, schedule, state, stats, timesheet, week_grid,
+};
 
-/** Extract hours, minutes, and seconds from String, return as vector of u32
+mod markdown;
+mod serve;
+mod tui;
+
+/** Clock file of the start/stop subcommands
  */
-fn extract(input: &str) -> Vec<u32> {
-    return input
-        .split(":")
-        .map(|x| x.parse::<u32>().unwrap())
-        .collect();
+fn clock_file(m: &ArgMatches) -> PathBuf {
+    m.get_one::<String>("clock-file")
+        .map(PathBuf::from)
+        .unwrap_or_else(events::default_path)
 }
 
-/** Create DateTime object from vector of hours, minutes, and seconds [optional] in local timezone
+/** Vacation file of --days-off or the config
  */
-fn create_time(input: &str) -> DateTime<Local> {
-    let now = Local::now();
-    let tz = now.timezone();
-    let hm = extract(input);
-    let dt: NaiveDateTime;
-    match hm.len() {
-        2 => {
-            dt = NaiveDate::from_ymd_opt(
-                now.date_naive().year(),
-                now.date_naive().month(),
-                now.date_naive().day(),
-            )
-            .unwrap()
-            .and_hms_opt(hm[0], hm[1], 0)
-            .unwrap()
-        }
-        3 => {
-            dt = NaiveDate::from_ymd_opt(
-                now.date_naive().year(),
-                now.date_naive().month(),
-                now.date_naive().day(),
-            )
-            .unwrap()
-            .and_hms_opt(hm[0], hm[1], hm[2])
-            .unwrap()
-        }
-        _ => panic!("Invalid format.  Stop!"),
-    };
-    let res = dt.and_local_timezone(tz).single().unwrap();
-    return res;
+fn days_off_path(m: &ArgMatches, config: &config::Config) -> PathBuf {
+    m.get_one::<String>("days-off")
+        .or(config.days_off.as_ref())
+        .map(PathBuf::from)
+        .unwrap_or_else(days_off::default_path)
 }
 
-fn create_duration(input: &str) -> Duration {
-    let times_str: Vec<i64> = input
-        .split(":")
-        .map(|x| x.parse::<i64>().unwrap())
-        .collect();
-    let res: Duration;
-    match times_str.len() {
-        2 => {
-            res = Duration::try_hours(times_str[0]).unwrap()
-                + Duration::try_minutes(times_str[1]).unwrap();
-        }
-        3 => {
-            res = Duration::try_hours(times_str[0]).unwrap()
-                + Duration::try_minutes(times_str[1]).unwrap()
-                + Duration::try_seconds(times_str[2]).unwrap();
-        }
-        _ => panic!("Invalid format.  Stop!"),
+/** Vacation and holidays from --days-off and --holidays, or the config
+ */
+fn days_off(m: &ArgMatches, config: &config::Config) -> Result<days_off::DaysOff, ParseError> {
+    let region = setting(m, "holidays", &config.holidays);
+    if let Some(region) = &region {
+        days_off::check_region(region)?;
     }
-    return res;
+    Ok(days_off::DaysOff {
+        vacation: days_off::read(&days_off_path(m, config)),
+        region,
+    })
 }
 
-/** Given two timestamps <HH:MM[:SS]>-<HH:MM[:SS]> extract the time between them and return as duration
+/** Rounding policy of --rounding and its mode, or the config
  */
-fn calculate_duration_from_string_ts(input: &String) -> Duration {
-    let times_str: Vec<&str> = input.split("-").into_iter().collect();
-    let start = create_time(times_str[0]);
-    let end = create_time(times_str[1]);
+fn rounding(m: &ArgMatches, config: &config::Config) -> Result<Rounding, ParseError> {
+    let minutes = setting(m, "rounding", &config.rounding).unwrap();
+    Ok(Rounding {
+        minutes: minutes.parse().map_err(|_| ParseError::NotANumber {
+            input: minutes.clone(),
+            field: "rounding".to_owned(),
+        })?,
+        direction: Direction::from_name(
+            &setting(m, "rounding-mode", &config.rounding_mode).unwrap(),
+        )?,
+        stamps: choice(
+            "rounding-to",
+            &setting(m, "rounding-to", &config.rounding_to).unwrap(),
+            &[("total", false), ("stamps", true)],
+        )?,
+    })
+}
 
-    return if end < start {
-        start - end
+/** Append a start or stop event at the given time to the clock file, tagging a start, unless the
+ * clock is already in that state; whether it was appended
+ */
+fn clock_event(
+    path: &Path,
+    kind: events::Kind,
+    at: DateTime<Local>,
+    tag: Option<&str>,
+) -> Result<bool, ParseError> {
+    let today = if path.exists() {
+        events::on(events::read(path)?, at.date_naive())
     } else {
-        end - start
+        Vec::new()
     };
+    if events::is_running(&today) == (kind == events::Kind::Start) {
+        return Ok(false);
+    }
+    events::append(path, at, kind, tag);
+    Ok(true)
 }
 
-/** Print duration struct in a human-readable way
+/** Tag of the last start on the clock file, for the work going on after a break
  */
-fn format_duration(input: &Duration) -> String {
-    let res = format!(
-        "{:02}:{:02}:{:02}",
-        input.num_hours().abs(),
-        (*input - Duration::try_hours(input.num_hours()).unwrap())
-            .num_minutes()
-            .abs(),
-        (*input - Duration::try_minutes(input.num_minutes()).unwrap())
-            .num_seconds()
-            .abs()
-    );
-    return res;
+fn last_tag(path: &Path) -> Result<Option<String>, ParseError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(events::read(path)?
+        .iter()
+        .rev()
+        .find(|e| e.kind == events::Kind::Start)
+        .and_then(|e| e.tag.clone()))
 }
 
-fn round(input: f64, digit: i32) -> f64 {
-    let tmp: f64 = (input * (10_f64.powi(digit))).round();
-    return tmp / (10_f64.powi(digit));
+/** Summary of work segments of the clock file, their gaps being the breaks
+ */
+fn clock_summary(
+    segments: &[(DateTime<Local>, Option<DateTime<Local>>)],
+    now: DateTime<Local>,
+    goal: Duration,
+) -> Option<Summary> {
+    let clock_in = segments.first()?.0;
+    let breaks: Vec<_> = segments
+        .windows(2)
+        .map(|pair| (pair[0].1.unwrap(), pair[1].0))
+        .collect();
+    Some(summarize(
+        now,
+        clock_in,
+        clock_in,
+        segments.last()?.1,
+        goal,
+        &breaks,
+        &Options::default(),
+    ))
 }
 
-fn format_duration_hours(input: &Duration) -> String {
-    let res = format!(
-        "{}",
-        round(
-            input.num_hours().abs() as f64
-                + (*input - Duration::try_hours(input.num_hours()).unwrap())
-                    .num_minutes()
-                    .abs() as f64
-                    / 60.,
-            2
-        )
-    );
-    return res;
+/** Daily goal of a date from the goal overrides of the config, the days off, and the weekly goal,
+ * for the subcommands that stay running; the overrides are checked up front
+ */
+fn daily_goals(
+    m: &ArgMatches,
+    config: config::Config,
+) -> Result<impl Fn(NaiveDate) -> Duration, ParseError> {
+    let weekly = create_duration(&setting(m, "weekly-goal", &config.weekly_goal).unwrap())?;
+    let days_off = days_off(m, &config)?;
+    for goal_s in config.goal_overrides.values() {
+        create_duration(goal_s)?;
+    }
+    Ok(move |date| match config.goal_override(date) {
+        _ if days_off.contains(date) => Duration::zero(),
+        Some(goal_s) => create_duration(goal_s).unwrap(),
+        None => weekly_share(weekly),
+    })
 }
 
-fn main() {
-    let m = Command::new(env!("CARGO_PKG_NAME"))
-        .version(env!("CARGO_PKG_VERSION"))
-        .author("Michael Lahnert <michael.lahnert@gmail.com>")
-        .arg(
-            Arg::new("starttime")
-                .short('s')
-                .required(true)
-                .help("Time when work started <HH:MM[:SS]>"),
-        )
-        .arg(
-            Arg::new("endtime")
-                .short('e')
-                .help("Time when work ended <HH:MM[:SS]>"),
-        )
-        .arg(
-            Arg::new("daily-goal")
-                .short('d')
-                .help("Daily work goal <HH:MM[:SS]>"),
-        )
-        .arg(
-            Arg::new("weekly-goal")
-                .short('w')
-                .default_value("39:00")
-                .help("Weekly work goal <HH:MM[:SS]>"),
-        )
-        .arg(
-            Arg::new("breaks")
-                .short('b')
-                .num_args(1)
-                .action(ArgAction::Append)
-                .help("Break start and end <HH:MM[:SS]-HH:MM[:SS]>"),
-        )
-        .get_matches();
+/** Append a start or stop event at the current time to the clock file, tagging a start, unless the
+ * clock is already in that state
+ */
+fn clock(
+    m: &ArgMatches,
+    kind: events::Kind,
+    done: &str,
+    tag: Option<&str>,
+) -> Result<(), ParseError> {
+    let now = Local::now();
+    if !clock_event(&clock_file(m), kind, now, tag)? {
+        println!(
+            "{}",
+            tr(if kind == events::Kind::Start {
+                "Already clocked in."
+            } else {
+                "Not clocked in."
+            })
+        );
+        return Ok(());
+    }
+    println!("{}", trf("{} at {}.", &[&done, &now.format("%H:%M:%S")]));
+    Ok(())
+}
 
-    let now: DateTime<Local> = Local::now();
-    let break_short = Duration::try_minutes(30).unwrap();
-    let break_large = Duration::try_minutes(45).unwrap();
+/** Report a compliance problem; fatal when running with --strict
+ */
+fn warn(strict: bool, message: &str) {
+    if strict {
+        eprintln!("{}", trf("Error: {}", &[&message]));
+        std::process::exit(9);
+    }
+    eprintln!("{}", trf("Warning: {}", &[&message]));
+}
 
-    // Build start and end time from commandline
-    let start: DateTime<Local>;
-    if let Some(start_s) = m.get_one::<String>("starttime") {
-        start = create_time(start_s);
-    } else {
-        panic!("Start time not defined");
+/** Warn when the rest since the previously recorded shift is shorter than min_rest
+ */
+fn check_rest(
+    records: &[timesheet::Record],
+    start: DateTime<Local>,
+    min_rest: Duration,
+    strict: bool,
+) {
+    if let Some(previous) = timesheet::previous(records, start.date_naive()) {
+        let rest = start.naive_local() - previous.end_datetime();
+        if rest < min_rest {
+            warn(
+                strict,
+                &trf(
+                    "only {} rest since the shift of {} ended at {}, at least {} required",
+                    &[
+                        &format_duration(&rest),
+                        &previous.date,
+                        &previous.end,
+                        &format_duration(&min_rest),
+                    ],
+                ),
+            );
+        }
     }
+}
 
-    let mut end = DateTime::<Local>::default();
-    if let Some(end_s) = m.get_one::<String>("endtime") {
-        end = create_time(end_s);
+fn print_fields() {
+    let width = FIELDS.iter().map(|f| f.name.len()).max().unwrap_or(0) + 2;
+    for field in FIELDS {
+        println!("{:<width$}{}", field.name, field.description);
     }
+}
 
-    // Build daily worktime goal
-    let workday: Duration;
-    if let Some(workday_s) = m.get_one::<String>("daily-goal") {
-        workday = create_duration(workday_s);
-    } else if let Some(workweek_s) = m.get_one::<String>("weekly-goal") {
-        workday = create_duration(workweek_s) / 5;
+/** Render a projected clock time, or the time left until it is reached in countdown mode
+ */
+fn format_projection(s: &Summary, at: DateTime<Local>, countdown: bool) -> String {
+    if !countdown || s.end.is_some() {
+        at.time().to_string()
+    } else if at > s.now {
+        trf("in {}", &[&format_duration(&(at - s.now))])
     } else {
-        panic!("Working-hour goal undefined")
+        tr("passed").to_owned()
     }
+}
 
-    // Build breaks
-    let breaks_input = m.get_many::<String>("breaks");
-    let mut breaks_s = Vec::new();
-    match breaks_input {
-        None => println!("No breaks defined, using default."),
-        Some(x) => x.for_each(|s| breaks_s.push(s)),
-    }
+/** Columns to render tables and bars in: --width, else the terminal's, else $COLUMNS, else 80
+ */
+fn output_width(m: &ArgMatches) -> usize {
+    m.get_one::<usize>("width")
+        .copied()
+        .or_else(|| terminal_size::terminal_size().map(|(w, _)| w.0 as usize))
+        .or_else(|| std::env::var("COLUMNS").ok()?.parse().ok())
+        .unwrap_or(80)
+}
 
-    let total_time: Duration;
-    if end != DateTime::<Local>::default() {
-        total_time = end - start;
-    } else {
-        total_time = now - start;
+/** Table of the records; a narrow width drops the start, then the end, then the break column
+ * before anything is cut, so the work column stays
+ */
+fn print_records(records: &[&timesheet::Record], width: usize) {
+    let columns = [tr("date"), tr("start"), tr("end"), tr("break"), tr("work")];
+    let widths = [12, 10, 10, 10, 8];
+    let mut shown = vec![true; columns.len()];
+    for dropped in [1, 2, 3] {
+        let total: usize = widths
+            .iter()
+            .zip(&shown)
+            .filter(|(_, &s)| s)
+            .map(|(w, _)| w)
+            .sum();
+        if total <= width {
+            break;
+        }
+        shown[dropped] = false;
     }
-    let mut break_time = Duration::try_seconds(0).unwrap();
-    let mut longest_break_time = Duration::try_seconds(0).unwrap();
-    if breaks_s.is_empty() {
-        break_time = if total_time >= (Duration::try_hours(9).unwrap() + break_short) {
-            break_large
-        } else {
-            break_short
-        };
-    } else {
-        let breaks = breaks_s.iter();
-        for break_ in breaks {
-            let break_duration = calculate_duration_from_string_ts(break_);
-            if break_duration > longest_break_time {
-                longest_break_time = break_duration;
+    let row = |cells: [String; 5]| {
+        let mut line = String::new();
+        for (i, cell) in cells.iter().enumerate().filter(|&(i, _)| shown[i]) {
+            if i + 1 < cells.len() {
+                line.push_str(&format!("{:<1$}", cell, widths[i]));
+            } else {
+                line.push_str(cell);
             }
-            break_time = break_time + break_duration;
         }
+        fit(&line, width)
+    };
+    println!("{}", row(columns.map(str::to_owned)));
+    for r in records {
+        println!(
+            "{}",
+            row([
+                r.date.to_string(),
+                r.start.format("%H:%M:%S").to_string(),
+                r.end.format("%H:%M:%S").to_string(),
+                format_duration(&r.break_time),
+                format_duration(&r.work_time),
+            ])
+        );
     }
+}
+
+fn print_totals(t: &timesheet::Totals, width: usize) {
+    let lines = [
+        trf("days: {}", &[&t.count]),
+        trf("total work: {}", &[&format_duration(&t.total_work)]),
+        trf("total break: {}", &[&format_duration(&t.total_break)]),
+        trf("average day: {}", &[&format_duration(&t.average_work)]),
+        trf("shortest day: {}", &[&format_duration(&t.min_work)]),
+        trf("longest day: {}", &[&format_duration(&t.max_work)]),
+    ];
+    for line in lines {
+        println!("{}", fit(&line, width));
+    }
+}
 
-    let work_time = total_time - break_time;
-    let done = work_time > workday;
-    let remainder = if done {
-        workday + break_time - total_time
+/** Presentation choices of the human-readable summary
+ */
+struct TextOptions {
+    /// Show projections as time left instead of clock times
+    countdown: bool,
+    /// Once the daily maximum is passed, say by how much instead of showing a negative remainder
+    truncate_future: bool,
+    /// Also express done and remaining as multiples of the daily goal
+    goal_unit: bool,
+}
+
+fn print_summary(s: &Summary, text: &TextOptions) {
+    let text_rem = tr(if s.done && s.delta < Duration::zero() {
+        "short, within tolerance"
+    } else if s.done {
+        "more"
+    } else {
+        "remaining"
+    });
+    let countdown = text.countdown;
+    let cap = if text.truncate_future && s.end.is_none() && s.max_dur < Duration::zero() {
+        "cap exceeded by {} [{}]"
     } else {
-        total_time - (workday + break_time)
+        "no longer than {} [{}]"
     };
-    let text_rem = if done { "more" } else { "remaining" };
-    let max_dur = (start + Duration::try_hours(10).unwrap() + max(break_large, break_time)) - now;
+    let cap = trf(
+        cap,
+        &[
+            &format_duration(&s.max_dur),
+            &format_duration_hours(&s.max_dur, s.round_mode),
+        ],
+    );
 
     let mut end_time_str: String = "".to_owned();
-    if end != DateTime::<Local>::default() {
-        end_time_str.push_str("end: ");
+    if let Some(end) = s.end {
+        end_time_str.push_str(tr("end"));
+        end_time_str.push_str(": ");
         end_time_str.push_str(&end.time().to_string());
         end_time_str.push_str("; ");
     }
 
     println!(
-        "[{}] start: {}; {}{}h: {}, 9h: {}, 10h: {}",
-        now.format("%H:%M:%S"),
-        start.time(),
+        "[{}] {}: {}; {}{}h: {}, 9h: {}, {}h: {}",
+        s.now.format("%H:%M:%S"),
+        tr("start"),
+        s.clock_in.time(),
         end_time_str,
-        format_duration_hours(&workday),
-        (start + workday + break_time).time(),
-        (start + Duration::try_hours(9).unwrap() + max(break_large, break_time)).time(),
-        (start + Duration::try_hours(10).unwrap() + max(break_large, break_time)).time()
+        format_duration_hours(&s.workday, s.round_mode),
+        format_projection(s, s.projected_goal, countdown),
+        format_projection(s, s.projected_9h, countdown),
+        format_duration_hours(&s.max_daily, s.round_mode),
+        format_projection(s, s.projected_10h, countdown)
     );
     println!(
-        "           already done: {} [{} -> {} %]; {} [{}] {}; no longer than {} [{}]",
-        format_duration(&work_time),
-        format_duration_hours(&(work_time)),
-        round(
-            100.0 * (work_time.num_nanoseconds().unwrap() as f64)
-                / (workday.num_nanoseconds().unwrap() as f64),
-            2
-        ),
-        format_duration(&remainder),
-        format_duration_hours(&remainder),
+        "           {}: {} [{} -> {} %]; {} [{}] {}; {}",
+        tr("already done"),
+        format_duration(&s.work_time),
+        format_duration_hours(&s.work_time, s.round_mode),
+        round(s.percent, 2, s.round_mode),
+        format_duration(&s.delta),
+        format_duration_hours(&s.delta, s.round_mode),
         text_rem,
-        format_duration(&max_dur),
-        format_duration_hours(&max_dur)
+        cap
     );
     println!(
-        "           total break time: {}; longest break: {}",
-        format_duration(&break_time),
-        if longest_break_time == Duration::try_seconds(0).unwrap() {
-            format_duration(&break_time)
+        "           {}: {}; {}: {}",
+        tr("total break time"),
+        format_duration(&s.break_time),
+        tr("longest break"),
+        if s.longest_break_estimated {
+            trf("{} (estimated)", &[&format_duration(&s.longest_break_time)])
         } else {
-            format_duration(&longest_break_time)
+            format_duration(&s.longest_break_time)
         }
     );
-    if end != DateTime::<Local>::default() {
+    if s.end.is_some() {
         println!(
-            "           total hours worked: {}",
-            format_duration_hours(&(total_time - break_time))
+            "           {}: {}",
+            tr("total hours worked"),
+            format_duration_hours(&s.work_time, s.round_mode)
+        );
+    }
+    if text.goal_unit {
+        if s.workday.is_zero() {
+            println!("           {}", tr("in goals: no daily goal"));
+        } else {
+            let ratio = |d: &Duration| {
+                d.num_nanoseconds().unwrap() as f64 / s.workday.num_nanoseconds().unwrap() as f64
+            };
+            println!(
+                "           {}",
+                trf(
+                    "in goals: {} done; {} {}",
+                    &[
+                        &round(ratio(&s.work_time), 2, s.round_mode),
+                        &round(ratio(&s.delta).abs(), 2, s.round_mode),
+                        &tr(if s.delta > Duration::zero() {
+                            "more"
+                        } else {
+                            "remaining"
+                        }),
+                    ],
+                )
+            );
+        }
+    }
+}
+
+/** Fewest logged days to derive the daily goal from
+ */
+const MIN_HISTORY: usize = 3;
+
+/** Snap an actual time to the planned one when within the tolerance, otherwise report the deviation
+ */
+fn snap_to_schedule(
+    actual: DateTime<Local>,
+    planned_s: &str,
+    tolerance: Duration,
+    what: &'static str,
+    strict: bool,
+) -> Result<DateTime<Local>, ParseError> {
+    let planned = create_time(planned_s)?;
+    let planned = actual
+        .date_naive()
+        .and_time(planned.time())
+        .and_local_timezone(Local)
+        .earliest()
+        .unwrap();
+    let deviation = actual - planned;
+    if deviation.abs() <= tolerance {
+        Ok(planned)
+    } else {
+        warn(
+            strict,
+            &trf(
+                "{} {} deviates from the scheduled {} by {}",
+                &[
+                    &tr(what),
+                    &actual.time(),
+                    &planned.time(),
+                    &format_duration_signed(&deviation),
+                ],
+            ),
         );
+        Ok(actual)
+    }
+}
+
+/** Where the value of a command-line argument came from
+ */
+fn value_source(m: &ArgMatches, id: &str) -> &'static str {
+    match m.value_source(id) {
+        Some(ValueSource::CommandLine) => "cli",
+        Some(ValueSource::EnvVariable) => "env",
+        Some(ValueSource::DefaultValue) => "builtin",
+        _ => "unset",
+    }
+}
+
+/** Value of an argument given on the command line or in the environment, else of the config
+ * file, else the argument's default
+ */
+fn setting(m: &ArgMatches, id: &str, configured: &Option<String>) -> Option<String> {
+    match m.value_source(id) {
+        Some(ValueSource::CommandLine | ValueSource::EnvVariable) => {
+            m.get_one::<String>(id).cloned()
+        }
+        _ => configured
+            .clone()
+            .or_else(|| m.get_one::<String>(id).cloned()),
+    }
+}
+
+/** Name of a break policy known to break_policy::named
+ */
+fn parse_policy(input: &str) -> Result<String, String> {
+    match break_policy::named(input.trim()) {
+        Some(_) => Ok(input.trim().to_owned()),
+        None => Err(format!(
+            "unknown break policy '{}', expected default, de-default, none, or flat<minutes>",
+            input.trim()
+        )),
+    }
+}
+
+/** value_parser of a setting parsed later by the given function, as it may come from the config
+ * instead; keeps the input as given
+ */
+fn checked<T: 'static>(
+    parse: fn(&str) -> Result<T, ParseError>,
+) -> impl Fn(&str) -> Result<String, String> + Clone + Send + Sync + 'static {
+    move |input| {
+        parse(input)
+            .map(|_| input.to_owned())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/** Comma-separated names of break policies, each one known to break_policy::named
+ */
+fn parse_policies(input: &str) -> Result<Vec<String>, String> {
+    input.split(',').map(parse_policy).collect()
+}
+
+fn parse_date(input: &str) -> Result<NaiveDate, chrono::ParseError> {
+    NaiveDate::parse_from_str(input, "%Y-%m-%d")
+}
+
+fn cli() -> Command {
+    Command::new(env!("CARGO_PKG_NAME"))
+        .version(env!("CARGO_PKG_VERSION"))
+        .author("Michael Lahnert <michael.lahnert@gmail.com>")
+        .about("Simple application to calculate working day.")
+        .after_help("Exit status: 1 for a goal not done yet under --exit-status, 2 for bad arguments, 3 for a malformed time or duration, 4 for one out of range, 5 for a malformed interval, 6 for a malformed now offset, 7 for an unknown name such as that of a field or a list of targets, 8 for a malformed timesheet record or clock event, 9 for a warning under --strict, 10 for no work segment left of the clock events, 11 for a record to edit that is missing or would not be consistent after the edit, 12 for a failed push, 13 for a break out of order under --require-sorted-breaks.")
+        .subcommand_negates_reqs(true)
+        .subcommand(Command::new("fields").about("List the values selectable via --field"))
+        .subcommand(
+            Command::new("completions")
+                .about("Print a completion script for the shell, e.g. for ~/.local/share/bash-completion/completions/time_rust")
+                .arg(
+                    Arg::new("shell")
+                        .required(true)
+                        .value_parser(clap::value_parser!(clap_complete::Shell))
+                        .help("Shell to complete in"),
+                ),
+        )
+        .subcommand(
+            Command::new("help-markdown")
+                .about("Print a Markdown reference of all arguments and subcommands"),
+        )
+        .subcommand(
+            Command::new("delete-state")
+                .about("Remove the records of a date from a timesheet, keeping a .bak copy")
+                .arg(
+                    Arg::new("csv")
+                        .long("csv")
+                        .required(true)
+                        .help("Timesheet with one record per day, as CSV or JSON lines"),
+                )
+                .arg(
+                    Arg::new("date")
+                        .long("date")
+                        .required(true)
+                        .value_parser(parse_date)
+                        .help("Date of the records to remove <YYYY-MM-DD>"),
+                ),
+        )
+        .subcommand(
+            Command::new("edit")
+                .visible_alias("amend")
+                .about("Change the start, end, or breaks of the record of a date in a timesheet, or delete it, recalculating its break and work time and whether it met the goal; goal overrides of the config apply, and --config goes before it; keeps a .bak copy when deleting")
+                .arg(
+                    Arg::new("date")
+                        .required(true)
+                        .value_parser(parse_date)
+                        .help("Date of the record <YYYY-MM-DD>"),
+                )
+                .arg(
+                    Arg::new("csv")
+                        .long("csv")
+                        .required(true)
+                        .help("Timesheet with one record per day, as CSV or JSON lines"),
+                )
+                .arg(
+                    Arg::new("set-start")
+                        .long("set-start")
+                        .help("New start <HH:MM[:SS]>"),
+                )
+                .arg(
+                    Arg::new("set-end")
+                        .long("set-end")
+                        .help("New end <HH:MM[:SS]>, before the start for an overnight shift"),
+                )
+                .arg(
+                    Arg::new("add-break")
+                        .long("add-break")
+                        .action(ArgAction::Append)
+                        .help("Break to add <HH:MM[:SS]-HH:MM[:SS]>, repeatable"),
+                )
+                .arg(
+                    Arg::new("remove-break")
+                        .long("remove-break")
+                        .action(ArgAction::Append)
+                        .help("Break to remove, exactly as stored <HH:MM[:SS]-HH:MM[:SS]>, repeatable"),
+                )
+                .arg(
+                    Arg::new("delete")
+                        .long("delete")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["set-start", "set-end", "add-break", "remove-break"])
+                        .help("Delete the record instead"),
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .action(ArgAction::SetTrue)
+                        .help("Show the record before and after without writing it"),
+                )
+                .arg(
+                    Arg::new("weekly-goal")
+                        .short('w')
+                        .env("TIME_RUST_WEEKLY_GOAL")
+                        .default_value("39:00")
+                        .help("Weekly work goal <HH:MM[:SS]> or decimal hours, e.g. 38.5, whose daily share decides whether the goal was met"),
+                )
+                .arg(
+                    Arg::new("break-policy")
+                        .long("break-policy")
+                        .value_parser(parse_policy)
+                        .default_value("default")
+                        .help("Break policy deducting the break of a record without single breaks: default, de-default, none, or flat<minutes>"),
+                ),
+        )
+        .subcommand(
+            Command::new("reset-balance")
+                .about("Set the flex balance of a state file to zero or a given value, keeping a .bak copy")
+                .arg(
+                    Arg::new("state")
+                        .long("state")
+                        .required(true)
+                        .help("State file with the flex balance"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .allow_hyphen_values(true)
+                        .help("Balance to start over from instead of zero <[-]HH:MM[:SS]>"),
+                ),
+        )
+        .subcommand(
+            Command::new("balance")
+                .about("Show the flex balance of a state file, this week's share, and the last booked days; --starting-balance goes before it")
+                .arg(
+                    Arg::new("state")
+                        .long("state")
+                        .required(true)
+                        .help("State file with the flex balance"),
+                )
+                .arg(
+                    Arg::new("days")
+                        .long("days")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("5")
+                        .help("Number of last booked days to list"),
+                ),
+        )
+        .subcommand(
+            Command::new("week-grid")
+                .about("Draw each day of a week from a timesheet as a bar against the daily goal")
+                .arg(
+                    Arg::new("csv")
+                        .long("csv")
+                        .required(true)
+                        .help("Timesheet with one record per day, as CSV or JSON lines"),
+                )
+                .arg(
+                    Arg::new("week-of")
+                        .long("week-of")
+                        .value_parser(parse_date)
+                        .help("Any date of the week to draw, default today <YYYY-MM-DD>"),
+                )
+                .arg(
+                    Arg::new("days")
+                        .long("days")
+                        .value_parser(clap::value_parser!(u32).range(1..=7))
+                        .default_value("5")
+                        .help("Number of days to draw, starting on Monday"),
+                )
+                .arg(
+                    Arg::new("daily-goal")
+                        .short('d')
+                        .long("daily-goal")
+                        .default_value("7:48")
+                        .help("Daily work goal a full bar stands for <HH:MM[:SS]> or decimal hours"),
+                ),
+        )
+        .subcommand(
+            Command::new("heatmap")
+                .about("Export the breaks of a timesheet per time slot and day as CSV")
+                .arg(
+                    Arg::new("csv")
+                        .long("csv")
+                        .required(true)
+                        .help("Timesheet with one record per day, as CSV or JSON lines"),
+                )
+                .arg(
+                    Arg::new("slot")
+                        .long("slot")
+                        .value_parser(clap::value_parser!(u32).range(1..=1440))
+                        .default_value("15")
+                        .help("Length of a slot in minutes"),
+                ),
+        )
+        .subcommand(
+            Command::new("list-state")
+                .about("Show the records of a timesheet as a table")
+                .arg(
+                    Arg::new("csv")
+                        .long("csv")
+                        .required(true)
+                        .help("Timesheet with one record per day, as CSV or JSON lines"),
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_parser(parse_date)
+                        .help("Only records on or after this date <YYYY-MM-DD>"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_parser(parse_date)
+                        .help("Only records on or before this date <YYYY-MM-DD>"),
+                ),
+        )
+        .subcommand(
+            Command::new("normalize")
+                .about("Rewrite a day file canonically: <HH:MM:SS>, merged breaks, derived values")
+                .arg(Arg::new("input").required(true).help("Day file in YAML"))
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .help("Write the canonical day file here instead of to stdout"),
+                ),
+        )
+        .subcommand(
+            Command::new("start")
+                .about("Clock in now, appending to the clock file")
+                .arg(
+                    Arg::new("tag")
+                        .long("tag")
+                        .visible_alias("project")
+                        .help("Label the work from now on, e.g. with a project, for the breakdown by tag of reports"),
+                ),
+        )
+        .subcommand(Command::new("stop").about("Clock out now, appending to the clock file"))
+        .subcommand(
+            Command::new("break")
+                .about("Clock out for a break or back in after it")
+                .subcommand_required(true)
+                .subcommand(Command::new("start").about("Clock out for a break"))
+                .subcommand(
+                    Command::new("stop")
+                        .about("Clock back in after a break, with the tag of the work before it"),
+                ),
+        )
+        .subcommand(
+            Command::new("pomodoro")
+                .about("Run focus cycles of work and breaks, clocking the work in and out on the clock file and ringing the bell at each change; interrupting it during work leaves the clock running")
+                .arg(
+                    Arg::new("work")
+                        .long("work")
+                        .default_value("0:25")
+                        .help("Length of a work phase <HH:MM[:SS]>"),
+                )
+                .arg(
+                    Arg::new("short-break")
+                        .long("short-break")
+                        .default_value("0:05")
+                        .help("Length of the break after a work phase <HH:MM[:SS]>"),
+                )
+                .arg(
+                    Arg::new("long-break")
+                        .long("long-break")
+                        .default_value("0:15")
+                        .help("Length of the break after every --long-every work phases <HH:MM[:SS]>"),
+                )
+                .arg(
+                    Arg::new("long-every")
+                        .long("long-every")
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .default_value("4")
+                        .help("Number of work phases up to a long break"),
+                )
+                .arg(
+                    Arg::new("cycles")
+                        .long("cycles")
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .help("Stop after this many work phases instead of running until interrupted"),
+                )
+                .arg(
+                    Arg::new("tag")
+                        .long("tag")
+                        .visible_alias("project")
+                        .help("Label the work phases, as with start --tag"),
+                )
+                .arg(
+                    Arg::new("notify")
+                        .long("notify")
+                        .action(ArgAction::SetTrue)
+                        .help("Also send a desktop notification at each change"),
+                ),
+        )
+        .subcommand(
+            Command::new("daemon")
+                .about("Stay running and, while clocked in, record the time away from an idle or locked session as a break on the clock file, asking logind on Linux and the HID system on macOS")
+                .after_help("Only Linux and macOS report idle time; on other systems, such as Windows, the daemon runs but never records a break.")
+                .arg(
+                    Arg::new("idle-after")
+                        .long("idle-after")
+                        .default_value("0:10")
+                        .help("Record only time away longer than this <HH:MM[:SS]>"),
+                )
+                .arg(
+                    Arg::new("poll")
+                        .long("poll")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .default_value("30")
+                        .help("Seconds between two looks at the session"),
+                ),
+        )
+        .subcommand(
+            Command::new("status")
+                .about("Summarize today from the clock file, like --events with today's events; options of the calculation go before it"),
+        )
+        .arg(
+            Arg::new("clock-file")
+                .long("clock-file")
+                .global(true)
+                .env("TIME_RUST_CLOCK_FILE")
+                .help("Events of the clock subcommands, by default $XDG_DATA_HOME/time_rust/events"),
+        )
+        .subcommand(
+            Command::new("vacation")
+                .about("Add or list days off, which have no goal")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("add")
+                        .about("Add a vacation day or range of days")
+                        .arg(
+                            Arg::new("range")
+                                .required(true)
+                                .help("Day or days off <YYYY-MM-DD[..YYYY-MM-DD]>, both inclusive"),
+                        )
+                        .arg(Arg::new("note").long("note").default_value("").help("Why, e.g. vacation")),
+                )
+                .subcommand(
+                    Command::new("list")
+                        .about("List the days off of a year, vacation and holidays")
+                        .arg(
+                            Arg::new("year")
+                                .long("year")
+                                .value_parser(clap::value_parser!(i32))
+                                .help("Year to list, default this one"),
+                        ),
+                ),
+        )
+        .arg(
+            Arg::new("days-off")
+                .long("days-off")
+                .global(true)
+                .env("TIME_RUST_DAYS_OFF")
+                .help("Vacation file of the vacation subcommand, by default $XDG_DATA_HOME/time_rust/days-off"),
+        )
+        .arg(
+            Arg::new("holidays")
+                .long("holidays")
+                .global(true)
+                .value_parser(["de", "none"])
+                .help("Public holidays without a goal: de for those of all of Germany"),
+        )
+        .arg(
+            Arg::new("rounding")
+                .long("rounding")
+                .global(true)
+                .default_value("0")
+                .help("Round the reported work time to a multiple of these minutes, also in reports and exports; 0 is off"),
+        )
+        .arg(
+            Arg::new("rounding-mode")
+                .long("rounding-mode")
+                .global(true)
+                .value_parser(["up", "down", "nearest", "favor"])
+                .default_value("nearest")
+                .help("How --rounding rounds; favor rounds the work time up, or the start down and the end up"),
+        )
+        .arg(
+            Arg::new("rounding-to")
+                .long("rounding-to")
+                .global(true)
+                .value_parser(["total", "stamps"])
+                .default_value("total")
+                .help("Whether --rounding rounds the work time of a day or its start and end"),
+        )
+        .subcommand(
+            Command::new("report")
+                .about("Tabulate the logged days of a week, month, or date range with weekly subtotals against the daily goals; goal overrides of the config apply, and --config goes before it")
+                .arg(
+                    Arg::new("csv")
+                        .long("csv")
+                        .required(true)
+                        .help("Timesheet with one record per day, as CSV or JSON lines"),
+                )
+                .arg(
+                    Arg::new("week")
+                        .long("week")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["month", "from", "to"])
+                        .help("Report the week of --date, Monday to Sunday"),
+                )
+                .arg(
+                    Arg::new("month")
+                        .long("month")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["from", "to"])
+                        .help("Report the calendar month of --date"),
+                )
+                .arg(
+                    Arg::new("date")
+                        .long("date")
+                        .value_parser(parse_date)
+                        .help("Any date of the week or month to report, default today <YYYY-MM-DD>"),
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_parser(parse_date)
+                        .help("First date to report <YYYY-MM-DD>"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_parser(parse_date)
+                        .help("Last date to report <YYYY-MM-DD>"),
+                )
+                .arg(
+                    Arg::new("by-tag")
+                        .long("by-tag")
+                        .action(ArgAction::SetTrue)
+                        .help("Break the work of each day, week, and the total down by tag"),
+                )
+                .arg(
+                    Arg::new("weekly-goal")
+                        .short('w')
+                        .env("TIME_RUST_WEEKLY_GOAL")
+                        .default_value("39:00")
+                        .help("Weekly work goal <HH:MM[:SS]> or decimal hours, e.g. 38.5"),
+                ),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("Write the logged days of a week, month, or date range as a timesheet in another layout, one row per day; goal overrides of the config apply, and --config goes before it")
+                .arg(
+                    Arg::new("csv")
+                        .long("csv")
+                        .required(true)
+                        .help("Timesheet with one record per day, as CSV or JSON lines"),
+                )
+                .arg(
+                    Arg::new("week")
+                        .long("week")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["month", "from", "to"])
+                        .help("Export the week of --date, Monday to Sunday"),
+                )
+                .arg(
+                    Arg::new("month")
+                        .long("month")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["from", "to"])
+                        .help("Export the calendar month of --date"),
+                )
+                .arg(
+                    Arg::new("date")
+                        .long("date")
+                        .value_parser(parse_date)
+                        .help("Any date of the week or month to export, default today <YYYY-MM-DD>"),
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_parser(parse_date)
+                        .help("First date to export <YYYY-MM-DD>"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_parser(parse_date)
+                        .help("Last date to export <YYYY-MM-DD>"),
+                )
+                .arg(
+                    Arg::new("columns")
+                        .long("columns")
+                        .default_value("date,start,end,break,net,delta")
+                        .help("Comma-separated columns, each <name[=header]>: date, weekday, start, end, break, breaks, net, hours, goal, delta, delta-hours"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(["csv", "xlsx"])
+                        .default_value("csv")
+                        .help("Write CSV or an Excel workbook, which needs --output"),
+                )
+                .arg(
+                    Arg::new("delimiter")
+                        .long("delimiter")
+                        .value_parser(clap::value_parser!(char))
+                        .default_value(",")
+                        .help("Separator of the CSV columns, e.g. ; for a German Excel"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .required_if_eq("format", "xlsx")
+                        .help("File to write instead of standard output"),
+                )
+                .arg(
+                    Arg::new("weekly-goal")
+                        .short('w')
+                        .env("TIME_RUST_WEEKLY_GOAL")
+                        .default_value("39:00")
+                        .help("Weekly work goal <HH:MM[:SS]> or decimal hours, e.g. 38.5"),
+                ),
+        )
+        .subcommand(
+            Command::new("tui")
+                .about("Open a dashboard of today from the clock file and of the week, to clock in and out by key; goal overrides of the config apply, and --config goes before it")
+                .arg(
+                    Arg::new("csv")
+                        .long("csv")
+                        .help("Timesheet with the days before today for the week view"),
+                )
+                .arg(
+                    Arg::new("weekly-goal")
+                        .short('w')
+                        .env("TIME_RUST_WEEKLY_GOAL")
+                        .default_value("39:00")
+                        .help("Weekly work goal <HH:MM[:SS]> or decimal hours, e.g. 38.5"),
+                ),
+        )
+        .subcommand(
+            Command::new("stats")
+                .about("Show statistics of the logged days: average start, end, and work, the longest run of days meeting the goal, break lengths, and the work of each week with its change from the week before; goal overrides of the config apply, and --config goes before it")
+                .arg(
+                    Arg::new("csv")
+                        .long("csv")
+                        .required(true)
+                        .help("Timesheet with one record per day, as CSV or JSON lines"),
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_parser(parse_date)
+                        .help("First date to include <YYYY-MM-DD>"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_parser(parse_date)
+                        .help("Last date to include <YYYY-MM-DD>"),
+                )
+                .arg(
+                    Arg::new("chart")
+                        .long("chart")
+                        .action(ArgAction::SetTrue)
+                        .help("Draw a bar per break length and per week"),
+                )
+                .arg(
+                    Arg::new("weekly-goal")
+                        .short('w')
+                        .env("TIME_RUST_WEEKLY_GOAL")
+                        .default_value("39:00")
+                        .help("Weekly work goal <HH:MM[:SS]> or decimal hours, e.g. 38.5"),
+                ),
+        )
+        .subcommand(
+            Command::new("push")
+                .about("Upload the logged days of a week, month, or date range to Toggl Track or Jira Tempo, one entry per day, with the credentials of the [push] table of the config; days pushed before are updated if they changed, and --config goes before it")
+                .arg(
+                    Arg::new("service")
+                        .required(true)
+                        .value_parser(["toggl", "tempo"])
+                        .help("Service to push to"),
+                )
+                .arg(
+                    Arg::new("csv")
+                        .long("csv")
+                        .required(true)
+                        .help("Timesheet with one record per day, as CSV or JSON lines"),
+                )
+                .arg(
+                    Arg::new("week")
+                        .long("week")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["month", "from", "to"])
+                        .help("Push the week of --date, Monday to Sunday"),
+                )
+                .arg(
+                    Arg::new("month")
+                        .long("month")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all(["from", "to"])
+                        .help("Push the calendar month of --date"),
+                )
+                .arg(
+                    Arg::new("date")
+                        .long("date")
+                        .value_parser(parse_date)
+                        .help("Any date of the week or month to push, default today <YYYY-MM-DD>"),
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_parser(parse_date)
+                        .help("First date to push <YYYY-MM-DD>"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_parser(parse_date)
+                        .help("Last date to push <YYYY-MM-DD>"),
+                )
+                .arg(
+                    Arg::new("ledger")
+                        .long("ledger")
+                        .help("Record of the pushed days, by default $XDG_DATA_HOME/time_rust/pushed"),
+                )
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .action(ArgAction::SetTrue)
+                        .help("Show what would be uploaded without uploading it"),
+                ),
+        )
+        .subcommand(
+            Command::new("serve")
+                .about("Answer a JSON API on the local machine: GET /status, POST /start and /stop on the clock file, GET /report?week=<date> of the timesheet; goal overrides of the config apply, and --config goes before it")
+                .arg(
+                    Arg::new("bind")
+                        .long("bind")
+                        .default_value("127.0.0.1:7878")
+                        .help("Address and port to listen on; there is no authentication, so keep it local"),
+                )
+                .arg(
+                    Arg::new("csv")
+                        .long("csv")
+                        .help("Timesheet with one record per day for /report, as CSV or JSON lines"),
+                )
+                .arg(
+                    Arg::new("weekly-goal")
+                        .short('w')
+                        .env("TIME_RUST_WEEKLY_GOAL")
+                        .default_value("39:00")
+                        .help("Weekly work goal <HH:MM[:SS]> or decimal hours, e.g. 38.5"),
+                ),
+        )
+        .subcommand(
+            Command::new("summarize")
+                .about("Fold all records of a timesheet into totals")
+                .arg(
+                    Arg::new("csv")
+                        .long("csv")
+                        .required(true)
+                        .help("Timesheet with one record per day, as CSV or JSON lines"),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the totals as a JSON object"),
+                ),
+        )
+        .arg(
+            Arg::new("starttime")
+                .short('s')
+                .long("start")
+                .required_unless_present_any(["events", "session"])
+                .allow_hyphen_values(true)
+                .help("Time when work started <[YYYY-MM-DDT]HH:MM[:SS]>, 8, 8:30am, or now[+-<minutes>|<N>h<N>m], or -<HH:MM[:SS]> for that long before now"),
+        )
+        .arg(
+            Arg::new("events")
+                .long("events")
+                .conflicts_with_all(["starttime", "endtime", "breaks", "break-total"])
+                .help("File of <HH:MM[:SS]> start|stop events to derive start, end, and breaks from"),
+        )
+        .arg(
+            Arg::new("session")
+                .long("session")
+                .action(ArgAction::Append)
+                .conflicts_with_all(["starttime", "endtime", "breaks", "break-total", "events"])
+                .help("Work session <HH:MM[:SS]-HH:MM[:SS]>, repeatable for split shifts; the gaps between sessions are the breaks"),
+        )
+        .arg(
+            Arg::new("tag")
+                .long("tag")
+                .visible_alias("project")
+                .help("Label the work of the day in the --log, e.g. with a project, instead of the tags of the work segments"),
+        )
+        .arg(
+            Arg::new("start-from")
+                .long("start-from")
+                .num_args(2)
+                .value_names(["MODE", "TIME"])
+                .requires("events")
+                .help("Ignore earlier stray events: start with the first segment at or after TIME <first-after HH:MM[:SS]>"),
+        )
+        .arg(
+            Arg::new("min-segment")
+                .long("min-segment")
+                .requires("events")
+                .help("Treat work segments shorter than this as gaps <HH:MM[:SS]>"),
+        )
+        .arg(
+            Arg::new("min-start")
+                .long("min-start")
+                .visible_alias("earliest-start")
+                .help("Earliest allowed start, earlier starts are clamped to it <HH:MM[:SS]>; none, the default, disables clamping"),
+        )
+        .arg(
+            Arg::new("min-start-applies-only-to-clockin")
+                .long("min-start-applies-only-to-clockin")
+                .action(ArgAction::SetTrue)
+                .help("Clamp only the worked time and goal, but show and project from the actual start"),
+        )
+        .arg(
+            Arg::new("endtime")
+                .short('e')
+                .help("Time when work ended <[YYYY-MM-DDT|+<days>d]HH:MM[:SS]>, 17, 5pm, or now[+-<minutes>|<N>h<N>m]; before the start means the next day"),
+        )
+        .arg(
+            Arg::new("overnight")
+                .long("overnight")
+                .action(ArgAction::SetTrue)
+                .help("The end is on the day after the start, even if its time is later than the start's"),
+        )
+        .arg(
+            Arg::new("round-end-down")
+                .long("round-end-down")
+                .value_parser(clap::value_parser!(u32))
+                .default_value("0")
+                .help("Floor the end time to a multiple of these minutes, 0 is off"),
+        )
+        .arg(
+            Arg::new("round")
+                .long("round")
+                .value_parser(clap::value_parser!(u32))
+                .default_value("0")
+                .help("Round the reported work time up to a multiple of these minutes, e.g. 15 for billing; 0 is off"),
+        )
+        .arg(
+            Arg::new("schedule")
+                .long("schedule")
+                .help("TOML file with the planned shift start and end per weekday to snap to"),
+        )
+        .arg(
+            Arg::new("schedule-tolerance")
+                .long("schedule-tolerance")
+                .default_value("0:15")
+                .help("Largest deviation from the schedule that is still snapped <HH:MM[:SS]>"),
+        )
+        .arg(
+            Arg::new("daily-goal")
+                .short('d')
+                .env("TIME_RUST_DAILY_GOAL")
+                .help("Daily work goal <HH:MM[:SS]> or decimal hours, e.g. 7.5"),
+        )
+        .arg(
+            Arg::new("weekly-goal")
+                .short('w')
+                .env("TIME_RUST_WEEKLY_GOAL")
+                .default_value("39:00")
+                .help("Weekly work goal <HH:MM[:SS]> or decimal hours, e.g. 38.5"),
+        )
+        .arg(
+            Arg::new("goal")
+                .long("goal")
+                .value_parser(["from-history"])
+                .requires("csv")
+                .help("Derive the daily goal from the median work time of the last logged days in --csv"),
+        )
+        .arg(
+            Arg::new("history-days")
+                .long("history-days")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("10")
+                .help("Number of logged days --goal from-history looks at"),
+        )
+        .arg(
+            Arg::new("goal-tolerance")
+                .long("goal-tolerance")
+                .default_value("0:00")
+                .help("Being short of the daily goal by up to this much still counts as done <HH:MM[:SS]>"),
+        )
+        .arg(
+            Arg::new("goal-unit")
+                .long("goal-unit")
+                .action(ArgAction::SetTrue)
+                .help("Also show done and remaining as multiples of the daily goal"),
+        )
+        .arg(
+            Arg::new("breaks")
+                .short('b')
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help("Break start and end <HH:MM[:SS]-HH:MM[:SS]>, either may be now[+-<minutes>] as in now-30-now"),
+        )
+        .arg(
+            Arg::new("now")
+                .long("now")
+                .conflicts_with("time-source")
+                .help("Use this time instead of the current time, same as --time-source fixed:<HH:MM[:SS]>"),
+        )
+        .arg(
+            Arg::new("date")
+                .long("date")
+                .value_parser(parse_date)
+                .help("Calculate this day instead of today; times without a date and now are on it, and --log and --state book it <YYYY-MM-DD>"),
+        )
+        .arg(
+            Arg::new("time-source")
+                .long("time-source")
+                .value_parser(|s: &str| match s {
+                    "local" | "utc" => Ok(s.to_owned()),
+                    _ if s.starts_with("fixed:") => Ok(s.to_owned()),
+                    _ => Err("expected local, utc, or fixed:<HH:MM[:SS]>"),
+                })
+                .default_value("local")
+                .help("Where the current time comes from: the local clock, the clock in UTC (inputs and output in UTC as well, overriding --tz), or a fixed time <local|utc|fixed:HH:MM[:SS]>"),
+        )
+        .arg(
+            Arg::new("tz")
+                .long("tz")
+                .visible_alias("timezone")
+                .value_parser(|s: &str| {
+                    s.parse::<chrono_tz::Tz>()
+                        .map(|_| s.to_owned())
+                        .map_err(|_| "expected a zone of the tz database, e.g. Europe/Berlin")
+                })
+                .help("Time zone for inputs and output instead of the system's, e.g. Europe/Berlin, kept with the records of the --log; ignored with --time-source utc"),
+        )
+        .arg(
+            Arg::new("projections")
+                .long("projections")
+                .value_parser(["absolute", "countdown"])
+                .default_value("absolute")
+                .help("Show projected end times as clock times or, without end time, as time left"),
+        )
+        .arg(
+            Arg::new("width")
+                .long("width")
+                .global(true)
+                .value_parser(clap::value_parser!(usize))
+                .help("Columns of tables and bars, by default the terminal's width"),
+        )
+        .arg(
+            Arg::new("locale")
+                .long("locale")
+                .global(true)
+                .help("Locale to use instead of the environment's, e.g. fi_FI"),
+        )
+        .arg(
+            Arg::new("lang")
+                .long("lang")
+                .global(true)
+                .value_parser(["en", "de"])
+                .help("Language of the output instead of the config's or the environment's"),
+        )
+        .arg(
+            Arg::new("locale-parse")
+                .long("locale-parse")
+                .action(ArgAction::SetTrue)
+                .help("Also accept the locale's time separator, e.g. 09.30"),
+        )
+        .arg(
+            Arg::new("no-truncate-future")
+                .long("no-truncate-future")
+                .action(ArgAction::SetTrue)
+                .help("Keep the plain 'no longer than' remainder even once the daily maximum is passed"),
+        )
+        .arg(
+            Arg::new("round-mode")
+                .long("round-mode")
+                .value_parser(["half-away", "half-even"])
+                .default_value("half-away")
+                .help("Rounding of decimal hours and percentages"),
+        )
+        .arg(
+            Arg::new("show-config-sources")
+                .long("show-config-sources")
+                .action(ArgAction::SetTrue)
+                .help("List every setting on stderr with the source it came from: builtin, config, env, or cli"),
+        )
+        .arg(
+            Arg::new("week-target-leave")
+                .long("week-target-leave")
+                .action(ArgAction::SetTrue)
+                .requires("log")
+                .conflicts_with_all(["field", "json", "json-pretty", "output"])
+                .help("For today and each remaining work day of the week, print the leave time that meets the weekly goal when starting at the start time, by the work of the earlier days in the --log timesheet and the goal of each day"),
+        )
+        .arg(
+            Arg::new("compare-policies")
+                .long("compare-policies")
+                .value_parser(parse_policies)
+                .conflicts_with_all(["break-total", "field", "json", "json-pretty", "output"])
+                .help("Print work time and remaining under each of these comma-separated break policies: default, de-default, none, flat<minutes>"),
+        )
+        .arg(
+            Arg::new("explain-breaks")
+                .long("explain-breaks")
+                .action(ArgAction::SetTrue)
+                .help("Explain on stderr how the break time was determined"),
+        )
+        .arg(
+            Arg::new("explain")
+                .long("explain")
+                .action(ArgAction::SetTrue)
+                .help("Explain on stderr step by step how the result was computed: start, goal, breaks, and rounding"),
+        )
+        .arg(
+            Arg::new("break-realized")
+                .long("break-realized")
+                .value_parser(["assume", "not-yet"])
+                .default_value("assume")
+                .help("Whether the default break counts as taken already when no end time is given"),
+        )
+        .arg(
+            Arg::new("solve-break")
+                .long("solve-break")
+                .action(ArgAction::SetTrue)
+                .requires("endtime")
+                .help("Print the total break that makes the time worked equal the daily goal"),
+        )
+        .arg(
+            Arg::new("rules")
+                .long("rules")
+                .value_parser(["de-arbzg", "none"])
+                .help("Check the logged breaks against this rule set instead of the [rules] of the config; de-arbzg is the German working hours act"),
+        )
+        .arg(
+            Arg::new("import-ics")
+                .long("import-ics")
+                .help("Take the breaks from the events of the day in this iCalendar file, see --ics-break-tags"),
+        )
+        .arg(
+            Arg::new("ics-break-tags")
+                .long("ics-break-tags")
+                .default_value("lunch,private")
+                .help("Comma-separated tags of the calendar events that are breaks, matched in the summary and categories; private also matches private events"),
+        )
+        .arg(
+            Arg::new("meetings")
+                .long("meetings")
+                .action(ArgAction::SetTrue)
+                .help("List the other calendar events of the day with the summary"),
+        )
+        .arg(
+            Arg::new("break-policy-file")
+                .long("break-policy-file")
+                .help("Table of breaks required by worked duration, one <from-to: break> per line"),
+        )
+        .arg(
+            Arg::new("break-short")
+                .long("break-short")
+                .conflicts_with("break-policy-file")
+                .help("Default break below --break-threshold <HH:MM[:SS]> [default: 00:30]"),
+        )
+        .arg(
+            Arg::new("break-long")
+                .long("break-long")
+                .conflicts_with("break-policy-file")
+                .help("Default break from --break-threshold on <HH:MM[:SS]> [default: 00:45]"),
+        )
+        .arg(
+            Arg::new("break-threshold")
+                .long("break-threshold")
+                .conflicts_with("break-policy-file")
+                .help("Time present from which the long default break applies <HH:MM[:SS]> [default: 09:30]"),
+        )
+        .arg(
+            Arg::new("max-daily")
+                .long("max-daily")
+                .default_value("10:00")
+                .help("Most a day may be worked, the latest projection <HH:MM[:SS]>"),
+        )
+        .arg(
+            Arg::new("break-total")
+                .long("break-total")
+                .conflicts_with("breaks")
+                .help("Total break time, when the single breaks aren't known <HH:MM[:SS]>"),
+        )
+        .arg(
+            Arg::new("break-count")
+                .long("break-count")
+                .value_parser(clap::value_parser!(u32).range(1..))
+                .default_value("1")
+                .requires("break-total")
+                .help("Number of breaks making up --break-total, to estimate the longest one"),
+        )
+        .arg(
+            Arg::new("field")
+                .long("field")
+                .num_args(1)
+                .action(ArgAction::Append)
+                .help("Print only the given value, one per line; see `fields` for names"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("field")
+                .help("Same as --output json, kept for existing scripts"),
+        )
+        .arg(
+            Arg::new("format")
+                .short('f')
+                .long("format")
+                .value_parser(["text", "json"])
+                .default_value("text")
+                .help("Print the summary as human-readable text or, same as --output json, as a JSON object; kept for existing scripts and the format setting of the config"),
+        )
+        .arg(
+            Arg::new("json-pretty")
+                .long("json-pretty")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("field")
+                .help("Print the summary as an indented JSON object"),
+        )
+        .arg(
+            Arg::new("overtime-only")
+                .long("overtime-only")
+                .action(ArgAction::SetTrue)
+                .requires("state")
+                .conflicts_with_all(["field", "json", "json-pretty", "output"])
+                .help("Print only today's over- or undertime and the balance of the week so far"),
+        )
+        .arg(
+            Arg::new("week-summary")
+                .long("week-summary")
+                .action(ArgAction::SetTrue)
+                .requires("log")
+                .conflicts_with_all(["field", "json", "json-pretty", "output"])
+                .help("Print the work time of this week, Monday on, from the --log timesheet against the weekly goal"),
+        )
+        .arg(
+            Arg::new("short")
+                .long("short")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["field", "json", "json-pretty", "output"])
+                .help("Print a single line for status bars, see --template"),
+        )
+        .arg(
+            Arg::new("template")
+                .long("template")
+                .default_value("{worked}/{goal} ▸ ends {end} (max {end_max})")
+                .help("Line printed by --short; each {field} is replaced by its value, see `fields`"),
+        )
+        .arg(
+            Arg::new("bar")
+                .long("bar")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["field", "json", "json-pretty", "output"])
+                .help("Print only a progress bar of the daily goal, e.g. [############........] 62%"),
+        )
+        .arg(
+            Arg::new("target")
+                .short('t')
+                .long("target")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["field", "json", "json-pretty", "output"])
+                .help("Print only the clock time at which the daily goal is reached, or by how much it is exceeded"),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .value_parser(["json", "msgpack", "csv", "plain"])
+                .conflicts_with_all(["field", "json", "json-pretty"])
+                .help("Serialize the summary in the given format: JSON, which --json and --format json are older spellings of, MessagePack for bulk logging, a CSV row, or one <field value> per line"),
+        )
+        .arg(
+            Arg::new("output-file")
+                .long("output-file")
+                .requires("output")
+                .help("Write the --output serialization to this file instead of stdout; CSV rows are appended, after a header row in a new file"),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .help("TOML configuration of defaults and goal overrides, instead of $XDG_CONFIG_HOME/time_rust/config.toml"),
+        )
+        .arg(
+            Arg::new("on-goal")
+                .long("on-goal")
+                .help("Shell command to run once the daily goal is done; {field} is replaced by the value of a field, see `fields`"),
+        )
+        .arg(
+            Arg::new("dry-run-hook")
+                .long("dry-run-hook")
+                .action(ArgAction::SetTrue)
+                .requires("on-goal")
+                .help("Print the --on-goal command instead of running it"),
+        )
+        .arg(
+            Arg::new("exit-status")
+                .long("exit-status")
+                .action(ArgAction::SetTrue)
+                .help("Exit with status 1 while the daily goal isn't done yet"),
+        )
+        .arg(
+            Arg::new("targets")
+                .long("targets")
+                .requires("config")
+                .help("Report the status against each target of this named list in the config"),
+        )
+        .arg(
+            Arg::new("csv")
+                .long("csv")
+                .help("Timesheet with one record per day, as CSV or JSON lines"),
+        )
+        .arg(
+            Arg::new("log")
+                .long("log")
+                .help("Append the day's record to this timesheet, marked provisional without an end time"),
+        )
+        .arg(
+            Arg::new("accumulate")
+                .long("accumulate")
+                .action(ArgAction::SetTrue)
+                .requires("log")
+                .help("Add this run to today's record in the --log timesheet instead of logging it separately"),
+        )
+        .arg(
+            Arg::new("persist-format")
+                .long("persist-format")
+                .value_parser(["csv", "jsonl"])
+                .default_value("csv")
+                .help("Format of records appended via --log"),
+        )
+        .arg(
+            Arg::new("audit-log")
+                .long("audit-log")
+                .help("Append notes on compliance-relevant events, e.g. a clamped early start"),
+        )
+        .arg(
+            Arg::new("state")
+                .long("state")
+                .help("Flextime account (JSON) to book the day's over- or undertime into"),
+        )
+        .arg(
+            Arg::new("starting-balance")
+                .long("starting-balance")
+                .env("TIME_RUST_STARTING_BALANCE")
+                .allow_hyphen_values(true)
+                .default_value("0:00")
+                .help("Balance the flextime account opens with until its first reset <[-]HH:MM[:SS]>"),
+        )
+        .arg(
+            Arg::new("overtime-factor")
+                .long("overtime-factor")
+                .value_parser(clap::value_parser!(f64))
+                .default_value("1")
+                .help("Factor overtime is credited with in the flextime account"),
+        )
+        .arg(
+            Arg::new("rest-check")
+                .long("rest-check")
+                .action(ArgAction::SetTrue)
+                .requires("csv")
+                .help("Warn if the rest since the previous shift in the timesheet is too short"),
+        )
+        .arg(
+            Arg::new("compare-to-yesterday")
+                .long("compare-to-yesterday")
+                .action(ArgAction::SetTrue)
+                .requires("csv")
+                .help("Compare the time worked with yesterday's record in the timesheet"),
+        )
+        .arg(
+            Arg::new("min-rest")
+                .long("min-rest")
+                .default_value("11:00")
+                .help("Minimum rest between two shifts <HH:MM[:SS]>"),
+        )
+        .arg(
+            Arg::new("premium-window")
+                .long("premium-window")
+                .help("Report the work time within this daily window, which may cross midnight <HH:MM-HH:MM>"),
+        )
+        .arg(
+            Arg::new("core")
+                .long("core")
+                .help("Core hours that must be covered by work <HH:MM[:SS]-HH:MM[:SS]>"),
+        )
+        .arg(
+            Arg::new("no-break-in-core")
+                .long("no-break-in-core")
+                .action(ArgAction::SetTrue)
+                .requires("core")
+                .help("Warn about every break overlapping the core hours"),
+        )
+        .arg(
+            Arg::new("require-sorted-breaks")
+                .long("require-sorted-breaks")
+                .action(ArgAction::SetTrue)
+                .help("Fail unless the breaks are given in order and without overlaps"),
+        )
+        .arg(
+            Arg::new("require-continuous-break")
+                .long("require-continuous-break")
+                .conflicts_with("break-total")
+                .help("Warn unless at least one break lasts this long in one piece <HH:MM[:SS]>"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .action(ArgAction::SetTrue)
+                .help("Treat compliance warnings as errors"),
+        )
+        .arg(
+            Arg::new("watch")
+                .long("watch")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["now", "log", "state", "audit-log", "output-file"])
+                .help("Stay running and redraw every minute, until interrupted"),
+        )
+        .arg(
+            Arg::new("notify")
+                .long("notify")
+                .action(ArgAction::SetTrue)
+                .requires("watch")
+                .help("Send desktop notifications while watching, see --notify-on"),
+        )
+        .arg(
+            Arg::new("notify-on")
+                .long("notify-on")
+                .default_value("goal,9h,max,break")
+                .value_parser(checked(notify::enabled))
+                .help("Comma-separated events to notify of: goal done, 9h and max some time before 9 hours and the daily maximum, break overdue under the --rules"),
+        )
+        .arg(
+            Arg::new("notify-lead")
+                .long("notify-lead")
+                .default_value("0:15")
+                .help("How long before 9 hours and the daily maximum to notify <HH:MM[:SS]>"),
+        )
+}
+
+fn run(m: &ArgMatches) -> Result<(), ParseError> {
+    // Zone of all inputs and output; has to be set before Local is used for the first time
+    if m.get_one::<String>("time-source").unwrap() == "utc" {
+        std::env::set_var("TZ", "UTC");
+    } else if let Some(tz) = m.get_one::<String>("tz") {
+        std::env::set_var("TZ", tz);
+    }
+
+    match m.subcommand() {
+        Some(("fields", _)) => {
+            print_fields();
+            return Ok(());
+        }
+        Some(("completions", sub)) => {
+            let shell = *sub.get_one::<clap_complete::Shell>("shell").unwrap();
+            clap_complete::generate(
+                shell,
+                &mut cli(),
+                env!("CARGO_PKG_NAME"),
+                &mut std::io::stdout(),
+            );
+            return Ok(());
+        }
+        Some(("help-markdown", _)) => {
+            print!("{}", markdown::render(&cli()));
+            return Ok(());
+        }
+        Some(("delete-state", sub)) => {
+            let removed = timesheet::delete(
+                Path::new(sub.get_one::<String>("csv").unwrap()),
+                *sub.get_one::<NaiveDate>("date").unwrap(),
+            )?;
+            println!("{}", trf("Removed {} entries.", &[&removed]));
+            return Ok(());
+        }
+        Some(("edit", sub)) => {
+            let path = Path::new(sub.get_one::<String>("csv").unwrap());
+            let date = *sub.get_one::<NaiveDate>("date").unwrap();
+            let records = timesheet::read(path)?;
+            let Some(record) = timesheet::on(&records, date) else {
+                return Err(ParseError::NoRecord {
+                    date: date.to_string(),
+                    path: path.display().to_string(),
+                });
+            };
+            let width = output_width(sub);
+            if sub.get_flag("delete") {
+                print_records(&[record], width);
+                if sub.get_flag("dry-run") {
+                    println!("{}", tr("Dry run, nothing deleted."));
+                } else {
+                    let removed = timesheet::delete(path, date)?;
+                    println!("{}", trf("Removed {} entries.", &[&removed]));
+                }
+                return Ok(());
+            }
+            let time = |id: &str| {
+                sub.get_one::<String>(id)
+                    .map(|t| create_time(t).map(|t| t.time()))
+                    .transpose()
+            };
+            let breaks = |id: &str| {
+                sub.get_many::<String>(id)
+                    .unwrap_or_default()
+                    .map(|b| parse_interval(b).map(|(from, to)| (from.time(), to.time())))
+                    .collect::<Result<Vec<_>, ParseError>>()
+            };
+            let amendment = timesheet::Amendment {
+                start: time("set-start")?,
+                end: time("set-end")?,
+                add_breaks: breaks("add-break")?,
+                remove_breaks: breaks("remove-break")?,
+            };
+            let goal = daily_goals(sub, load_config(m))?(date);
+            let policy =
+                break_policy::named(sub.get_one::<String>("break-policy").unwrap()).unwrap();
+            let amended = timesheet::amend(record, &amendment, goal, policy.as_ref())?;
+            print_records(&[record, &amended], width);
+            if sub.get_flag("dry-run") {
+                println!("{}", tr("Dry run, nothing written."));
+            } else {
+                timesheet::replace(path, &amended, timesheet::format_of(path))?;
+            }
+            return Ok(());
+        }
+        Some(("reset-balance", sub)) => {
+            let path = Path::new(sub.get_one::<String>("state").unwrap());
+            let mut state = state::State::load(path);
+            let previous = state.balance();
+            let balance = match sub.get_one::<String>("to") {
+                Some(to_s) => create_signed_duration(to_s)?,
+                None => Duration::zero(),
+            };
+            if path.exists() {
+                let mut backup = path.as_os_str().to_owned();
+                backup.push(".bak");
+                if let Err(e) = std::fs::copy(path, &backup) {
+                    panic!("Cannot back up state {}: {}", path.display(), e);
+                }
+            }
+            state.reset(balance, Local::now());
+            state.save(path);
+            println!(
+                "{}",
+                trf(
+                    "Balance reset from {} to {}.",
+                    &[
+                        &format_duration_signed(&previous),
+                        &format_duration_signed(&balance),
+                    ],
+                )
+            );
+            return Ok(());
+        }
+        Some(("week-grid", sub)) => {
+            let records = timesheet::read(Path::new(sub.get_one::<String>("csv").unwrap()))?;
+            let date = sub
+                .get_one::<NaiveDate>("week-of")
+                .copied()
+                .unwrap_or_else(|| Local::now().date_naive());
+            print!(
+                "{}",
+                week_grid::render(
+                    &records,
+                    date,
+                    *sub.get_one::<u32>("days").unwrap(),
+                    create_duration(sub.get_one::<String>("daily-goal").unwrap())?,
+                    &sub.get_one::<String>("locale")
+                        .cloned()
+                        .unwrap_or_else(env_locale),
+                    output_width(sub),
+                )
+            );
+            return Ok(());
+        }
+        Some(("heatmap", sub)) => {
+            let records = timesheet::read(Path::new(sub.get_one::<String>("csv").unwrap()))?;
+            print!(
+                "{}",
+                heatmap::render(&records, *sub.get_one::<u32>("slot").unwrap())
+            );
+            return Ok(());
+        }
+        Some(("list-state", sub)) => {
+            let records = timesheet::read(Path::new(sub.get_one::<String>("csv").unwrap()))?;
+            print_records(
+                &timesheet::between(
+                    &records,
+                    sub.get_one::<NaiveDate>("from").copied(),
+                    sub.get_one::<NaiveDate>("to").copied(),
+                ),
+                output_width(sub),
+            );
+            return Ok(());
+        }
+        Some(("normalize", sub)) => {
+            let day = dayfile::normalize(&dayfile::read(Path::new(
+                sub.get_one::<String>("input").unwrap(),
+            )))?;
+            match sub.get_one::<String>("output") {
+                Some(path) => dayfile::write(Path::new(path), &day),
+                None => print!("{}", dayfile::to_yaml(&day)),
+            }
+            return Ok(());
+        }
+        Some(("summarize", sub)) => {
+            let records = timesheet::read(Path::new(sub.get_one::<String>("csv").unwrap()))?;
+            let totals = timesheet::totals(&records);
+            if sub.get_flag("json") {
+                println!("{}", serde_json::to_string(&totals).unwrap());
+            } else {
+                print_totals(&totals, output_width(sub));
+            }
+            return Ok(());
+        }
+        Some(("pomodoro", sub)) => {
+            let cycle = pomodoro::Cycle {
+                work: create_duration(sub.get_one::<String>("work").unwrap())?,
+                short_break: create_duration(sub.get_one::<String>("short-break").unwrap())?,
+                long_break: create_duration(sub.get_one::<String>("long-break").unwrap())?,
+                long_every: *sub.get_one::<u32>("long-every").unwrap(),
+            };
+            let cycles = sub.get_one::<u32>("cycles").copied();
+            let tag = sub.get_one::<String>("tag").map(String::as_str);
+            // The last work phase ends the run, without the break after it
+            let phases = cycle.phases().take_while(|&(n, phase, _)| {
+                cycles.is_none_or(|cycles| {
+                    n < cycles || (n == cycles && phase == pomodoro::Phase::Work)
+                })
+            });
+            for (n, phase, length) in phases {
+                print!("\x07");
+                let started = trf("{} {} started", &[&tr(phase.name()), &n]);
+                match phase {
+                    pomodoro::Phase::Work => clock(sub, events::Kind::Start, &started, tag)?,
+                    _ => clock(sub, events::Kind::Stop, &started, None)?,
+                }
+                std::io::stdout().flush().unwrap();
+                if sub.get_flag("notify") {
+                    notify::send(&trf(
+                        "{} {} until {}",
+                        &[
+                            &tr(phase.name()),
+                            &n,
+                            &(Local::now() + length).format("%H:%M"),
+                        ],
+                    ));
+                }
+                std::thread::sleep(length.to_std().unwrap());
+            }
+            clock(sub, events::Kind::Stop, tr("Done"), None)?;
+            return Ok(());
+        }
+        Some(("daemon", sub)) => {
+            if !idle::SUPPORTED {
+                warn(
+                    false,
+                    tr("no idle time on this system, so no breaks will be recorded"),
+                );
+            }
+            let mut tracker = idle::Tracker::new(create_duration(
+                sub.get_one::<String>("idle-after").unwrap(),
+            )?);
+            let poll = std::time::Duration::from_secs(*sub.get_one::<u64>("poll").unwrap());
+            loop {
+                let now = Local::now();
+                let path = clock_file(sub);
+                if let (Some((from, to)), true) =
+                    (tracker.observe(idle::idle_since(now), now), path.exists())
+                {
+                    // Clocked in when going away, and neither in nor out while away
+                    let events = events::on(events::read(&path)?, from.date_naive());
+                    let (before, after): (Vec<_>, Vec<_>) =
+                        events.into_iter().partition(|e| e.at <= from);
+                    if events::is_running(&before) && after.iter().all(|e| e.at > to) {
+                        let tag = before.last().and_then(|e| e.tag.clone());
+                        events::append(&path, from, events::Kind::Stop, None);
+                        events::append(&path, to, events::Kind::Start, tag.as_deref());
+                        println!(
+                            "{}",
+                            trf(
+                                "Recorded break {}-{}.",
+                                &[&from.format("%H:%M:%S"), &to.format("%H:%M:%S")],
+                            )
+                        );
+                    }
+                }
+                std::thread::sleep(poll);
+            }
+        }
+        Some(("start", sub)) => {
+            clock(
+                sub,
+                events::Kind::Start,
+                tr("Clocked in"),
+                sub.get_one::<String>("tag").map(String::as_str),
+            )?;
+            return Ok(());
+        }
+        Some(("stop", sub)) => {
+            clock(sub, events::Kind::Stop, tr("Clocked out"), None)?;
+            return Ok(());
+        }
+        Some(("break", sub)) => {
+            match sub.subcommand() {
+                Some(("start", sub)) => clock(sub, events::Kind::Stop, tr("Break started"), None)?,
+                Some(("stop", sub)) => {
+                    let tag = last_tag(&clock_file(sub))?;
+                    clock(sub, events::Kind::Start, tr("Break ended"), tag.as_deref())?
+                }
+                _ => unreachable!(),
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    // Times given in the locale's format are translated to <HH:MM[:SS]>
+    let separator = if m.get_flag("locale-parse") {
+        locale_time_separator(
+            &m.get_one::<String>("locale")
+                .cloned()
+                .unwrap_or_else(env_locale),
+        )
+    } else {
+        ':'
+    };
+    let localized = |input: &String| input.replace(separator, ":");
+
+    let time_source = m.get_one::<String>("time-source").unwrap();
+    let fixed_now = m
+        .get_one::<String>("now")
+        .map(String::as_str)
+        .or_else(|| time_source.strip_prefix("fixed:"));
+    // Times without a date are on --date, by default today
+    let date = m.get_one::<NaiveDate>("date").copied();
+    let dated = |input: String| match date {
+        Some(date) => on_date(&input, date),
+        None => input,
+    };
+    let now: DateTime<Local> = match fixed_now {
+        Some(now_s) => create_time(&dated(localized(&now_s.to_owned())))?,
+        None => match date {
+            Some(date) => date
+                .and_time(Local::now().time())
+                .and_local_timezone(Local)
+                .earliest()
+                .unwrap(),
+            None => Local::now(),
+        },
+    };
+    // ... and now, now-<minutes>, or now+<minutes> to the time of the run
+    let relative = |input: &String| resolve_now(&localized(input), now).map(dated);
+
+    let config = load_config(m);
+    // Looked up before anything is printed
+    let targets = match m.get_one::<String>("targets") {
+        Some(list) => Some(
+            config
+                .targets
+                .get(list)
+                .ok_or_else(|| ParseError::UnknownTargets {
+                    name: list.to_owned(),
+                })?,
+        ),
+        None => None,
+    };
+
+    let starting_balance =
+        create_signed_duration(&setting(m, "starting-balance", &config.starting_balance).unwrap())?;
+    if let Some(sub) = m.subcommand_matches("balance") {
+        let state = state::State::load(Path::new(sub.get_one::<String>("state").unwrap()));
+        println!(
+            "{}",
+            trf(
+                "Flex balance: {}",
+                &[&format_duration_signed(
+                    &state.balance_from(starting_balance)
+                )],
+            )
+        );
+        println!(
+            "{}",
+            trf(
+                "   this week: {}",
+                &[&format_duration_signed(
+                    &state.week_balance(now.date_naive())
+                )],
+            )
+        );
+        let days = *sub.get_one::<usize>("days").unwrap();
+        for (date, seconds) in state.days.iter().rev().take(days).rev() {
+            println!(
+                "  {}: {}",
+                date,
+                format_duration_signed(&Duration::try_seconds(*seconds).unwrap())
+            );
+        }
+        return Ok(());
+    }
+
+    if let Some(sub) = m.subcommand_matches("vacation") {
+        match sub.subcommand() {
+            Some(("add", sub)) => {
+                let (first, last) = days_off::parse_range(sub.get_one::<String>("range").unwrap())?;
+                let path = days_off_path(sub, &config);
+                days_off::append(&path, first, last, sub.get_one::<String>("note").unwrap());
+                println!(
+                    "{}",
+                    trf(
+                        "Added {} day(s) off to {}.",
+                        &[&((last - first).num_days() + 1), &path.display()],
+                    )
+                );
+            }
+            Some(("list", sub)) => {
+                let year = sub
+                    .get_one::<i32>("year")
+                    .copied()
+                    .unwrap_or_else(|| now.year());
+                let days_off = days_off(sub, &config)?;
+                let first = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
+                for date in first.iter_days().take_while(|date| date.year() == year) {
+                    if let Some(reason) = days_off.reason(date) {
+                        println!("{} {} {}", date, date.weekday(), reason);
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+        return Ok(());
+    }
+    if let Some(sub) = m.subcommand_matches("report") {
+        let mut records = timesheet::read(Path::new(sub.get_one::<String>("csv").unwrap()))?;
+        let rounding = rounding(sub, &config)?;
+        for record in &mut records {
+            record.work_time = record.reported_work(&rounding);
+        }
+        let date = sub
+            .get_one::<NaiveDate>("date")
+            .copied()
+            .unwrap_or_else(|| Local::now().date_naive());
+        let period = if sub.get_flag("week") {
+            report::Period::Week(date)
+        } else if sub.get_flag("month") {
+            report::Period::Month(date)
+        } else {
+            report::Period::Range(
+                sub.get_one::<NaiveDate>("from").copied(),
+                sub.get_one::<NaiveDate>("to").copied(),
+            )
+        };
+        let weekly = create_duration(&setting(sub, "weekly-goal", &config.weekly_goal).unwrap())?;
+        let days_off = days_off(sub, &config)?;
+        let goal = |date| match config.goal_override(date) {
+            _ if days_off.contains(date) => Ok(Duration::zero()),
+            Some(goal_s) => create_duration(goal_s),
+            None => Ok(weekly_share(weekly)),
+        };
+        print!(
+            "{}",
+            report::render(
+                &records,
+                &period,
+                goal,
+                sub.get_flag("by-tag"),
+                output_width(sub)
+            )?
+        );
+        return Ok(());
+    }
+    if let Some(sub) = m.subcommand_matches("tui") {
+        // Fail on a malformed timesheet or clock file before the dashboard takes over the terminal
+        if let Some(path) = sub.get_one::<String>("csv") {
+            timesheet::read(Path::new(path))?;
+        }
+        if clock_file(sub).exists() {
+            events::read(&clock_file(sub))?;
+        }
+        let dashboard = tui::Dashboard::new(
+            clock_file(sub),
+            sub.get_one::<String>("csv").map(PathBuf::from),
+            Box::new(daily_goals(sub, config)?),
+        );
+        if let Err(e) = tui::run(dashboard) {
+            panic!("Cannot run the dashboard: {}", e);
+        }
+        return Ok(());
+    }
+    if let Some(sub) = m.subcommand_matches("stats") {
+        let mut records = timesheet::read(Path::new(sub.get_one::<String>("csv").unwrap()))?;
+        let rounding = rounding(sub, &config)?;
+        for record in &mut records {
+            record.work_time = record.reported_work(&rounding);
+        }
+        let weekly = create_duration(&setting(sub, "weekly-goal", &config.weekly_goal).unwrap())?;
+        let days_off = days_off(sub, &config)?;
+        let goal = |date| match config.goal_override(date) {
+            _ if days_off.contains(date) => Ok(Duration::zero()),
+            Some(goal_s) => create_duration(goal_s),
+            None => Ok(weekly_share(weekly)),
+        };
+        let stats = stats::compute(
+            &records,
+            sub.get_one::<NaiveDate>("from").copied(),
+            sub.get_one::<NaiveDate>("to").copied(),
+            goal,
+        )?;
+        print!(
+            "{}",
+            stats::render(&stats, sub.get_flag("chart"), output_width(sub))
+        );
+        return Ok(());
+    }
+    if let Some(sub) = m.subcommand_matches("push") {
+        let service =
+            push::Service::from_spec(sub.get_one::<String>("service").unwrap(), &config.push)
+                .map_err(|reason| ParseError::PushFailed { reason })?;
+        let mut records = timesheet::read(Path::new(sub.get_one::<String>("csv").unwrap()))?;
+        let rounding = rounding(sub, &config)?;
+        for record in &mut records {
+            record.work_time = record.reported_work(&rounding);
+        }
+        let date = sub
+            .get_one::<NaiveDate>("date")
+            .copied()
+            .unwrap_or_else(|| Local::now().date_naive());
+        let (from, to) = if sub.get_flag("week") {
+            report::Period::Week(date)
+        } else if sub.get_flag("month") {
+            report::Period::Month(date)
+        } else {
+            report::Period::Range(
+                sub.get_one::<NaiveDate>("from").copied(),
+                sub.get_one::<NaiveDate>("to").copied(),
+            )
+        }
+        .bounds();
+        let mut days: Vec<NaiveDate> = timesheet::between(&records, from, to)
+            .iter()
+            .map(|r| r.date)
+            .collect();
+        days.dedup();
+        // A day still going on is pushed once it is logged for good
+        let description = config.push.description.as_deref().unwrap_or("Work");
+        let entries = days
+            .into_iter()
+            .filter_map(|day| timesheet::on(&records, day))
+            .filter(|record| !record.provisional && record.work_time > Duration::zero())
+            .map(|record| push::Entry::of(record, description))
+            .collect();
+        let path = sub
+            .get_one::<String>("ledger")
+            .map(PathBuf::from)
+            .unwrap_or_else(push::default_path);
+        let mut ledger = push::Ledger::load(&path);
+        let dry_run = sub.get_flag("dry-run");
+        for action in ledger.plan(service.name(), entries) {
+            let entry = action.entry();
+            let verb = tr(match action {
+                push::Action::Create(_) => "create",
+                push::Action::Update(..) => "update",
+                push::Action::Unchanged(..) => "unchanged",
+            });
+            println!(
+                "{:<10}{} {} {} {}",
+                verb,
+                entry.date,
+                entry.start.format("%H:%M:%S"),
+                format_duration(&Duration::try_seconds(entry.seconds).unwrap()),
+                entry.description
+            );
+            if dry_run {
+                if !matches!(action, push::Action::Unchanged(..)) {
+                    println!("{:>10}{}", "", service.body(entry));
+                }
+                continue;
+            }
+            match service.send(&action) {
+                Ok(id) => ledger.record(service.name(), id, entry.clone()),
+                Err(reason) => {
+                    // Keep what made it so a retry doesn't push it twice
+                    ledger.save(&path);
+                    return Err(ParseError::PushFailed { reason });
+                }
+            }
+        }
+        if dry_run {
+            println!("{}", tr("Dry run, nothing pushed."));
+        } else {
+            ledger.save(&path);
+        }
+        return Ok(());
+    }
+    if let Some(sub) = m.subcommand_matches("serve") {
+        let rounding = rounding(sub, &config)?;
+        let api = serve::Api::new(
+            clock_file(sub),
+            sub.get_one::<String>("csv").map(PathBuf::from),
+            Box::new(daily_goals(sub, config)?),
+            rounding,
+        );
+        serve::run(api, sub.get_one::<String>("bind").unwrap());
+        return Ok(());
+    }
+    if let Some(sub) = m.subcommand_matches("export") {
+        let mut records = timesheet::read(Path::new(sub.get_one::<String>("csv").unwrap()))?;
+        let rounding = rounding(sub, &config)?;
+        for record in &mut records {
+            record.work_time = record.reported_work(&rounding);
+        }
+        let date = sub
+            .get_one::<NaiveDate>("date")
+            .copied()
+            .unwrap_or_else(|| Local::now().date_naive());
+        let (from, to) = if sub.get_flag("week") {
+            report::Period::Week(date)
+        } else if sub.get_flag("month") {
+            report::Period::Month(date)
+        } else {
+            report::Period::Range(
+                sub.get_one::<NaiveDate>("from").copied(),
+                sub.get_one::<NaiveDate>("to").copied(),
+            )
+        }
+        .bounds();
+        let weekly = create_duration(&setting(sub, "weekly-goal", &config.weekly_goal).unwrap())?;
+        let days_off = days_off(sub, &config)?;
+        let goal = |date| match config.goal_override(date) {
+            _ if days_off.contains(date) => Ok(Duration::zero()),
+            Some(goal_s) => create_duration(goal_s),
+            None => Ok(weekly_share(weekly)),
+        };
+        let columns = export::layout(&setting(sub, "columns", &config.export_columns).unwrap())?;
+        let rows = export::rows(&records, from, to, &columns, goal)?;
+        let output = sub.get_one::<String>("output").map(Path::new);
+        match (sub.get_one::<String>("format").unwrap().as_str(), output) {
+            ("xlsx", Some(path)) => export::xlsx(&columns, &rows, path),
+            (_, output) => {
+                let csv = export::csv(&columns, &rows, *sub.get_one::<char>("delimiter").unwrap());
+                match output {
+                    Some(path) => {
+                        if let Err(e) = std::fs::write(path, csv) {
+                            panic!("Cannot write {}: {}", path.display(), e);
+                        }
+                    }
+                    None => print!("{}", csv),
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    // Work segments from a file of start/stop events or from --session replace start, end, and
+    // breaks; status takes today's from the clock file
+    let status = m.subcommand_matches("status");
+    let events_path = match status {
+        Some(sub) => Some(clock_file(sub)),
+        None => m.get_one::<String>("events").map(PathBuf::from),
+    };
+    let mut segment_tags: Vec<Option<String>> = Vec::new();
+    let segments = match events_path {
+        Some(path) => {
+            if status.is_some() && !path.exists() {
+                println!("{}", tr("Not clocked in today."));
+                return Ok(());
+            }
+            let mut events = events::read(&path)?;
+            if status.is_some() {
+                events = events::on(events, now.date_naive());
+                if events.is_empty() {
+                    println!("{}", tr("Not clocked in today."));
+                    return Ok(());
+                }
+            }
+            let mut segments = events::segments(&events);
+            if let Some(start_from) = m.get_many::<String>("start-from") {
+                let start_from: Vec<&String> = start_from.collect();
+                if start_from[0] != "first-after" {
+                    return Err(ParseError::UnknownStartFrom {
+                        mode: start_from[0].clone(),
+                    });
+                }
+                let after = create_time(&dated(localized(start_from[1])))?;
+                segments.retain(|(segment_start, _)| *segment_start >= after);
+            }
+            if let Some(min_segment_s) = m.get_one::<String>("min-segment") {
+                let min_segment = create_duration(&localized(min_segment_s))?;
+                let count = segments.len();
+                segments.retain(|(from, to)| to.is_none_or(|to| to - *from >= min_segment));
+                if segments.len() < count {
+                    eprintln!(
+                        "{}",
+                        trf(
+                            "Discarded {} work segment(s) shorter than {}.",
+                            &[&(count - segments.len()), &format_duration(&min_segment)],
+                        )
+                    );
+                }
+            }
+            if segments.is_empty() {
+                return Err(ParseError::NoWorkSegment {
+                    path: path.display().to_string(),
+                });
+            }
+            segment_tags = segments
+                .iter()
+                .map(|(from, _)| events::tag_at(&events, *from))
+                .collect();
+            Some(segments)
+        }
+        None => m
+            .get_many::<String>("session")
+            .map(|sessions| {
+                let mut segments = sessions
+                    .map(|s| {
+                        let (from, to) = parse_interval(&relative(s)?)?;
+                        Ok((from, Some(to)))
+                    })
+                    .collect::<Result<Vec<_>, ParseError>>()?;
+                segments.sort();
+                Ok::<_, ParseError>(segments)
+            })
+            .transpose()?,
+    };
+
+    // Build start and end time from commandline
+    let clock_in = match (&segments, m.get_one::<String>("starttime")) {
+        (Some(segments), _) => segments[0].0,
+        (None, Some(start_s)) => match start_s.strip_prefix('-') {
+            Some(ago_s) => now - create_duration(&localized(&ago_s.to_owned()))?,
+            None => create_time(&relative(start_s)?)?,
+        },
+        (None, None) => panic!("Start time not defined"),
+    };
+
+    // Snap to the planned shift
+    let schedule = m
+        .get_one::<String>("schedule")
+        .map(|path| schedule::Schedule::load(Path::new(path)));
+    let schedule_tolerance = create_duration(m.get_one::<String>("schedule-tolerance").unwrap())?;
+    let shift = schedule
+        .as_ref()
+        .and_then(|schedule| schedule.shift(clock_in.weekday()));
+    let clock_in = match shift {
+        Some(shift) => snap_to_schedule(
+            clock_in,
+            &shift.start,
+            schedule_tolerance,
+            "start",
+            m.get_flag("strict"),
+        )?,
+        None => clock_in,
+    };
+
+    // Clamp start to the earliest allowed start
+    let mut start = clock_in;
+    let min_start = setting(m, "min-start", &config.min_start)
+        .map(|min_start_s| {
+            // On the day of the start, which --date or a dated start may set
+            let min_start_s = localized(&min_start_s);
+            earliest_start(&match min_start_s.trim() {
+                "none" => min_start_s.clone(),
+                _ => on_date(&min_start_s, clock_in.date_naive()),
+            })
+        })
+        .transpose()?
+        .flatten();
+    if let Some(min_start) = min_start {
+        if start < min_start {
+            eprintln!(
+                "{}",
+                trf(
+                    "Start {} is before the earliest allowed start, counting from {}.",
+                    &[&start.time(), &min_start.time()],
+                )
+            );
+            if let Some(path) = m.get_one::<String>("audit-log") {
+                let note = audit::Note {
+                    recorded: now,
+                    date: start.date_naive(),
+                    event: audit::Event::EarlyClockIn {
+                        actual: start.time(),
+                        clamped: min_start.time(),
+                    },
+                };
+                audit::append(Path::new(path), &note);
+            }
+            start = min_start;
+        }
+    }
+    let clock_in = if m.get_flag("min-start-applies-only-to-clockin") {
+        clock_in
+    } else {
+        start
+    };
+
+    let end = match &segments {
+        Some(segments) => segments[segments.len() - 1].1,
+        None => m
+            .get_one::<String>("endtime")
+            .map(|end_s| {
+                let (days, time_s) = day_offset(end_s)?;
+                let end = create_time(&relative(&time_s.to_owned())?)?;
+                // An end with its own date or day offset needs no guessing about midnight
+                Ok::<_, ParseError>(match days {
+                    Some(days) => add_days(
+                        end,
+                        (clock_in.date_naive() - end.date_naive()).num_days() + days,
+                    ),
+                    None if is_dated(end_s) => end,
+                    None if m.get_flag("overnight") => add_days(
+                        end,
+                        (clock_in.date_naive() - end.date_naive()).num_days() + 1,
+                    ),
+                    None => roll_past(end, clock_in),
+                })
+            })
+            .transpose()?,
+    }
+    .map(|end| floor_to_minutes(end, *m.get_one::<u32>("round-end-down").unwrap()))
+    .map(|end| match shift {
+        Some(shift) => snap_to_schedule(
+            end,
+            &shift.end,
+            schedule_tolerance,
+            "end",
+            m.get_flag("strict"),
+        ),
+        None => Ok(end),
+    })
+    .transpose()?;
+
+    let strict = m.get_flag("strict");
+    if m.get_flag("rest-check") {
+        let records = timesheet::read(Path::new(m.get_one::<String>("csv").unwrap()))?;
+        let min_rest = create_duration(m.get_one::<String>("min-rest").unwrap())?;
+        check_rest(&records, clock_in, min_rest, strict);
+    }
+
+    // Build daily worktime goal
+    let from_history = if m.contains_id("goal") {
+        let records = timesheet::read(Path::new(m.get_one::<String>("csv").unwrap()))?;
+        let recent = timesheet::recent(
+            &records,
+            start.date_naive(),
+            *m.get_one::<usize>("history-days").unwrap(),
+        );
+        if recent.len() < MIN_HISTORY {
+            eprintln!(
+                "{}",
+                trf(
+                    "Only {} logged day(s), at least {} needed to derive the goal; using the default.",
+                    &[&recent.len(), &MIN_HISTORY],
+                )
+            );
+            None
+        } else {
+            let goal = timesheet::median_work(&recent).unwrap();
+            eprintln!(
+                "{}",
+                trf(
+                    "Goal derived as the median of {} logged days: {}.",
+                    &[&recent.len(), &format_duration(&goal)],
+                )
+            );
+            Some(goal)
+        }
+    } else {
+        None
+    };
+    let days_off = days_off(m, &config)?;
+    let (workday, workday_source) = if let Some(workday_s) = m.get_one::<String>("daily-goal") {
+        (
+            create_duration(&localized(workday_s))?,
+            value_source(m, "daily-goal"),
+        )
+    } else if days_off.contains(start.date_naive()) {
+        (Duration::zero(), "day off")
+    } else if let Some(goal) = from_history {
+        (goal, "history")
+    } else if let Some(workday_s) = config.goal_override(start.date_naive()) {
+        (create_duration(workday_s)?, "config")
+    } else if let Some(workweek_s) = setting(m, "weekly-goal", &config.weekly_goal) {
+        (
+            weekly_share(create_duration(&workweek_s)?),
+            "weekly goal / 5",
+        )
+    } else {
+        panic!("Working-hour goal undefined")
+    };
+
+    if m.get_flag("show-config-sources") {
+        for arg in cli().get_arguments() {
+            let id = arg.get_id().as_str();
+            if let Some(values) = m.get_raw(id) {
+                let values: Vec<_> = values.map(|v| v.to_string_lossy()).collect();
+                eprintln!("{} = {} ({})", id, values.join(" "), value_source(m, id));
+            }
+        }
+        if !m.contains_id("locale") && !env_locale().is_empty() {
+            eprintln!("locale = {} (env)", env_locale());
+        }
+        eprintln!(
+            "resolved daily goal = {} ({})",
+            format_duration(&workday),
+            workday_source
+        );
+    }
+
+    // Inverse problem: the break that makes the time worked equal the goal
+    if m.get_flag("solve-break") {
+        let total_time = end.unwrap() - start;
+        if total_time < workday {
+            println!(
+                "{}",
+                trf(
+                    "Impossible: {} between start and end is less than the goal of {}.",
+                    &[&format_duration(&total_time), &format_duration(&workday)],
+                )
+            );
+        } else {
+            println!(
+                "{}",
+                trf(
+                    "break for exactly {} of work: {}",
+                    &[
+                        &format_duration(&workday),
+                        &format_duration(&(total_time - workday)),
+                    ],
+                )
+            );
+        }
+        return Ok(());
+    }
+
+    // Build breaks, from the gaps between work segments if there are any
+    let breaks_s: Vec<String> = match &segments {
+        Some(segments) => segments
+            .windows(2)
+            .map(|pair| {
+                format!(
+                    "{}-{}",
+                    pair[0].1.unwrap().format("%H:%M:%S"),
+                    pair[1].0.format("%H:%M:%S")
+                )
+            })
+            .collect(),
+        None => m
+            .get_many::<String>("breaks")
+            .map(|x| x.map(relative).collect::<Result<Vec<_>, _>>())
+            .transpose()?
+            .unwrap_or_default(),
+    };
+    // Breaks after midnight belong to a shift that started the day before, provided they still
+    // fall before its end; without one, no shift runs longer than half a day
+    let latest = end.unwrap_or(clock_in + Duration::try_hours(12).unwrap());
+    let mut breaks = breaks_s
+        .iter()
+        .map(|b| {
+            let (from, to) = parse_interval(b)?;
+            Ok(if from < clock_in && roll_past(from, clock_in) < latest {
+                (roll_past(from, clock_in), roll_past(to, clock_in))
+            } else {
+                (from, to)
+            })
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+
+    if m.get_flag("require-sorted-breaks") {
+        let mut previous_end: Option<DateTime<Local>> = None;
+        for (break_s, &(from, to)) in breaks_s.iter().zip(&breaks) {
+            if previous_end.is_some_and(|previous_end| from < previous_end) {
+                return Err(ParseError::UnsortedBreak {
+                    input: break_s.clone(),
+                });
+            }
+            previous_end = Some(to);
+        }
+    }
+
+    // Calendar events of the day are breaks if tagged so, and meetings otherwise
+    let mut meetings = Vec::new();
+    if let Some(path) = setting(m, "import-ics", &config.import_ics) {
+        let tags: Vec<String> = m
+            .get_one::<String>("ics-break-tags")
+            .unwrap()
+            .split(',')
+            .map(|tag| tag.trim().to_owned())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        for event in ics::read(Path::new(&path)) {
+            if event.start.date_naive() != clock_in.date_naive() {
+                continue;
+            }
+            if event.is_break(&tags) {
+                breaks.push((event.start, event.end));
+            } else {
+                meetings.push(event);
+            }
+        }
+    }
+
+    // Count only what lies within the working period, and overlapping breaks once
+    let (breaks, notes) = clamp_breaks(&breaks, start, end);
+    for note in &notes {
+        warn(strict, note);
+    }
+
+    // Only logged breaks can break the rules; without any the break policy deducts its own
+    let rules = match m.get_one::<String>("rules") {
+        Some(name) => Some(rules::RuleSet::preset(name)),
+        None => config
+            .rules
+            .as_ref()
+            .map(rules::RuleSet::from_spec)
+            .transpose()?,
+    };
+    if let Some(rules) = rules.as_ref().filter(|_| !breaks.is_empty()) {
+        for note in rules.check(start, end.unwrap_or(now), &breaks) {
+            warn(strict, &note);
+        }
+    }
+
+    if let Some(core_s) = m.get_one::<String>("core") {
+        let core = parse_interval(&dated(localized(core_s)))?;
+        for (gap_start, gap_end) in core_gaps(core, clock_in, end, &breaks) {
+            warn(
+                strict,
+                &trf(
+                    "core hours {}-{} not covered from {} to {}",
+                    &[
+                        &core.0.time(),
+                        &core.1.time(),
+                        &gap_start.time(),
+                        &gap_end.time(),
+                    ],
+                ),
+            );
+        }
+        if m.get_flag("no-break-in-core") {
+            for break_ in breaks.iter().filter(|b| overlap(core, **b).is_some()) {
+                warn(
+                    strict,
+                    &trf(
+                        "break {}-{} lies within core hours {}-{}",
+                        &[
+                            &break_.0.time(),
+                            &break_.1.time(),
+                            &core.0.time(),
+                            &core.1.time(),
+                        ],
+                    ),
+                );
+            }
+        }
+    }
+
+    if let Some(required_s) = m.get_one::<String>("require-continuous-break") {
+        let required = create_duration(&localized(required_s))?;
+        let breaks: Vec<Duration> = breaks.iter().map(|(from, to)| *to - *from).collect();
+        let longest = breaks.iter().copied().max().unwrap_or_default();
+        if !breaks.is_empty() && longest < required {
+            let total = breaks.iter().copied().sum::<Duration>();
+            warn(
+                strict,
+                &trf(
+                    if total >= required {
+                        "no continuous break of {}: the longest is {}, {} in total but split into pieces"
+                    } else {
+                        "no continuous break of {}: the longest is {}, {} in total"
+                    },
+                    &[
+                        &format_duration(&required),
+                        &format_duration(&longest),
+                        &format_duration(&total),
+                    ],
+                ),
+            );
+        }
+    }
+
+    let standard_arg =
+        |id: &str, configured: &Option<String>, default: Duration| match setting(m, id, configured)
+        {
+            Some(input) => create_duration(&localized(&input)),
+            None => Ok(default),
+        };
+    let break_policy: Option<Box<dyn BreakPolicy>> =
+        if let Some(path) = m.get_one::<String>("break-policy-file") {
+            Some(Box::new(break_policy::BreakTable::load(Path::new(path))))
+        } else if ["break-short", "break-long", "break-threshold"]
+            .iter()
+            .any(|id| m.contains_id(id))
+            || [
+                &config.break_short,
+                &config.break_long,
+                &config.break_threshold,
+            ]
+            .iter()
+            .any(|configured| configured.is_some())
+        {
+            let default = break_policy::Standard::default();
+            Some(Box::new(break_policy::Standard {
+                short: standard_arg("break-short", &config.break_short, default.short)?,
+                long: standard_arg("break-long", &config.break_long, default.long)?,
+                threshold: standard_arg(
+                    "break-threshold",
+                    &config.break_threshold,
+                    default.threshold,
+                )?,
+            }))
+        } else {
+            None
+        };
+    let options = Options {
+        round_mode: RoundMode::from_name(&setting(m, "round-mode", &config.round_mode).unwrap())?,
+        break_policy,
+        break_total: match m.get_one::<String>("break-total") {
+            Some(total_s) => Some((
+                create_duration(&localized(total_s))?,
+                *m.get_one::<u32>("break-count").unwrap(),
+            )),
+            None => None,
+        },
+        break_not_yet: m.get_one::<String>("break-realized").unwrap() == "not-yet",
+        max_daily: create_duration(&localized(
+            &setting(m, "max-daily", &config.max_daily).unwrap(),
+        ))?,
+        goal_tolerance: create_duration(&localized(
+            m.get_one::<String>("goal-tolerance").unwrap(),
+        ))?,
+    };
+    let summary = summarize(now, clock_in, start, end, workday, &breaks, &options);
+
+    // Notify of what came due since the previous refresh, a minute ago
+    if m.get_flag("watch") && (m.get_flag("notify") || config.notify) {
+        let enabled = notify::enabled(&setting(m, "notify-on", &config.notify_on).unwrap())?;
+        let lead = create_duration(&setting(m, "notify-lead", &config.notify_lead).unwrap())?;
+        let break_due = rules
+            .as_ref()
+            .and_then(|rules| rules.break_due(start, &breaks));
+        let since = now - Duration::try_minutes(1).unwrap();
+        for event in notify::due(&enabled, &summary, lead, break_due, since) {
+            notify::send(&notify::message(event, &summary, lead));
+        }
+    }
+
+    // Earlier run of the same day to add this one to
+    let earlier = if m.get_flag("accumulate") {
+        let records = timesheet::read(Path::new(m.get_one::<String>("log").unwrap()))?;
+        timesheet::on(&records, summary.start.date_naive()).cloned()
+    } else {
+        None
+    };
+    let day_work = summary.work_time + earlier.as_ref().map(|r| r.work_time).unwrap_or_default();
+
+    let mut balance = None;
+    if let Some(path) = m.get_one::<String>("state") {
+        let overtime_factor = *m.get_one::<f64>("overtime-factor").unwrap();
+        let weighted = state::weighted(day_work - workday, overtime_factor);
+        let mut state = state::State::load(Path::new(path));
+        state.book(summary.start.date_naive(), weighted);
+        state.save(Path::new(path));
+        balance = Some((weighted, state.balance_from(starting_balance)));
+    }
+
+    if let Some(path) = m.get_one::<String>("log") {
+        let mut record = timesheet::Record {
+            date: summary.start.date_naive(),
+            start: summary.clock_in.time(),
+            end: summary.end.unwrap_or(summary.now).time(),
+            break_time: summary.break_time,
+            work_time: summary.work_time,
+            breaks: breaks
+                .iter()
+                .map(|(from, to)| (from.time(), to.time()))
+                .collect(),
+            goal_met: day_work > workday - options.goal_tolerance,
+            provisional: summary.end.is_none(),
+            tags: BTreeMap::new(),
+            zone: std::env::var("TZ").ok().filter(|tz| !tz.is_empty()),
+        };
+        match (m.get_one::<String>("tag"), &segments) {
+            (Some(tag), _) => {
+                record.tags.insert(tag.clone(), summary.work_time);
+            }
+            (None, Some(segments)) => {
+                for (&(from, to), tag) in segments.iter().zip(&segment_tags) {
+                    if let Some(tag) = tag {
+                        *record
+                            .tags
+                            .entry(tag.clone())
+                            .or_insert_with(Duration::zero) += to.unwrap_or(summary.now) - from;
+                    }
+                }
+            }
+            (None, None) => {}
+        }
+        let format = timesheet::Format::from_name(m.get_one::<String>("persist-format").unwrap());
+        match &earlier {
+            Some(earlier) => {
+                record.start = min(earlier.start, record.start);
+                record.end = max(earlier.end, record.end);
+                record.break_time += earlier.break_time;
+                record.work_time += earlier.work_time;
+                record.breaks.splice(0..0, earlier.breaks.iter().copied());
+                for (tag, work) in &earlier.tags {
+                    *record
+                        .tags
+                        .entry(tag.clone())
+                        .or_insert_with(Duration::zero) += *work;
+                }
+                timesheet::replace(Path::new(path), &record, format)?;
+            }
+            None => timesheet::append(Path::new(path), &record, format),
+        }
+    }
+
+    // Beyond the legal maximum, however the breaks were taken
+    if summary.work_time > summary.max_daily {
+        warn(
+            strict,
+            &trf(
+                "worked {} exceeds the daily maximum of {} by {}",
+                &[
+                    &format_duration(&summary.work_time),
+                    &format_duration(&summary.max_daily),
+                    &format_duration(&(summary.work_time - summary.max_daily)),
+                ],
+            ),
+        );
+    }
+
+    // Billing increments and rounding policies only change what is reported, not what is booked
+    // or logged
+    let mut summary = summary;
+    let logged = summary.work_time;
+    let increment = *m.get_one::<u32>("round").unwrap();
+    let policy = rounding(m, &config)?;
+    summary.work_time = round_up_to_minutes(summary.work_time, increment);
+    let reported = policy.work(
+        summary.start.naive_local(),
+        summary.end.unwrap_or(summary.now).naive_local(),
+        summary.work_time,
+    );
+    summary.delta += reported - summary.work_time;
+    summary.work_time = reported;
+
+    if m.get_flag("explain-breaks") {
+        for line in &summary.break_explanation {
+            eprintln!("{}", trf("Break: {}", &[line]));
+        }
+    }
+    if m.get_flag("explain") {
+        let time = |t: DateTime<Local>| t.format("%H:%M:%S").to_string();
+        let mut steps = vec![if summary.start == summary.clock_in {
+            trf("clocked in at {}", &[&time(summary.clock_in)])
+        } else {
+            trf(
+                "clocked in at {}, counted from the earliest start {}",
+                &[&time(summary.clock_in), &time(summary.start)],
+            )
+        }];
+        steps.push(match summary.end {
+            Some(end) => trf("clocked out at {}", &[&time(end)]),
+            None => trf("still working at {}", &[&time(summary.now)]),
+        });
+        steps.push(match workday_source {
+            "weekly goal / 5" => trf(
+                "daily goal {} as the weekly goal {} split over {} workdays",
+                &[
+                    &format_duration(&summary.workday),
+                    &format_duration(&create_duration(
+                        &setting(m, "weekly-goal", &config.weekly_goal).unwrap(),
+                    )?),
+                    &WORKDAYS,
+                ],
+            ),
+            source => trf(
+                "daily goal {} from {}",
+                &[&format_duration(&summary.workday), &tr(source)],
+            ),
+        });
+        steps.extend(summary.break_explanation.iter().cloned());
+        steps.push(trf("work time {}", &[&format_duration(&logged)]));
+        if increment > 0 {
+            steps.push(trf(
+                "billing increment: work time rounded up to {} minutes",
+                &[&increment],
+            ));
+        }
+        steps.push(trf(
+            "{}, reported {}",
+            &[&policy.describe(), &format_duration(&summary.work_time)],
+        ));
+        steps.push(trf(
+            if summary.delta < Duration::zero() {
+                "short of the goal by {}"
+            } else {
+                "over the goal by {}"
+            },
+            &[&format_duration(&summary.delta.abs())],
+        ));
+        for (i, step) in steps.iter().enumerate() {
+            eprintln!("{:>2}. {}", i + 1, step);
+        }
+    }
+
+    // --json and --format json are the older spellings of --output json
+    let output = m.get_one::<String>("output").cloned().or_else(|| {
+        (m.get_flag("json") || setting(m, "format", &config.format).as_deref() == Some("json"))
+            .then(|| "json".to_owned())
+    });
+    if m.get_flag("week-target-leave") {
+        // Work logged on the earlier days against their goals, as worked: the flextime account
+        // credits overtime at its factor
+        let today = summary.start.date_naive();
+        let records: Vec<timesheet::Record> =
+            timesheet::read(Path::new(m.get_one::<String>("log").unwrap()))?
+                .into_iter()
+                .filter(|record| record.date < today)
+                .collect();
+        let goal = daily_goals(m, load_config(m))?;
+        let earlier_goals: Duration = today
+            .week(Weekday::Mon)
+            .first_day()
+            .iter_days()
+            .take_while(|day| *day < today)
+            .map(&goal)
+            .sum();
+        let week_balance = timesheet::week_work(&records, today) - earlier_goals;
+        let days: Vec<NaiveDate> = today
+            .iter_days()
+            .take_while(|day| day.weekday().num_days_from_monday() < 5)
+            .filter(|day| !days_off.contains(*day))
+            .collect();
+        let standard = break_policy::Standard::default();
+        let policy = options.break_policy.as_deref().unwrap_or(&standard);
+        let locale = m
+            .get_one::<String>("locale")
+            .cloned()
+            .unwrap_or_else(env_locale);
+        if days.is_empty() {
+            println!("{}", tr("No work days left this week."));
+        }
+        for day in &days {
+            let share = goal(*day) - week_balance / days.len() as i32;
+            let leave = start + share + policy.required_for_work(share);
+            println!(
+                "{}",
+                trf(
+                    "{} {} leave at {} [{} of work]",
+                    &[
+                        &format!("{:<3}", weekday_name(day.weekday(), &locale)),
+                        &day,
+                        &leave.format("%H:%M:%S"),
+                        &format_duration(&share),
+                    ],
+                )
+            );
+        }
+    } else if let Some(names) = m.get_one::<Vec<String>>("compare-policies") {
+        println!("{:<12}{:<10}{}", tr("policy"), tr("work"), tr("remaining"));
+        for name in names {
+            let compared = summarize(
+                now,
+                clock_in,
+                start,
+                end,
+                workday,
+                &breaks,
+                &Options {
+                    break_policy: break_policy::named(name),
+                    break_total: None,
+                    ..options
+                },
+            );
+            println!(
+                "{:<12}{:<10}{}",
+                name,
+                format_duration(&compared.work_time),
+                (find_field("remaining").unwrap().value)(&compared)
+            );
+        }
+    } else if m.get_flag("overtime-only") {
+        let state = state::State::load(Path::new(m.get_one::<String>("state").unwrap()));
+        println!(
+            "{}",
+            trf("today: {}", &[&format_duration_signed(&summary.delta)])
+        );
+        println!(
+            "{}",
+            trf(
+                "week:  {}",
+                &[&format_duration_signed(
+                    &state.week_balance(summary.start.date_naive())
+                )],
+            )
+        );
+    } else if m.get_flag("week-summary") {
+        let records = timesheet::read(Path::new(m.get_one::<String>("log").unwrap()))?;
+        let today = summary.start.date_naive();
+        let worked = timesheet::week_work(&records, today);
+        // Each work day off takes its share off the weekly goal
+        let monday = today.week(chrono::Weekday::Mon).first_day();
+        let weekly = create_duration(&localized(
+            &setting(m, "weekly-goal", &config.weekly_goal).unwrap(),
+        ))?;
+        let weekly = weekly
+            - weekly_share(weekly)
+                * monday
+                    .iter_days()
+                    .take(WORKDAYS as usize)
+                    .filter(|day| days_off.contains(*day))
+                    .count() as i32;
+        let week = today.iso_week();
+        println!(
+            "{}",
+            trf(
+                "week {}: {}h worked of {}h; {}h {}",
+                &[
+                    &format!("{}-W{:02}", week.year(), week.week()),
+                    &format_duration_hours(&worked, summary.round_mode),
+                    &format_duration_hours(&weekly, summary.round_mode),
+                    &format_duration_hours(&(weekly - worked), summary.round_mode),
+                    &tr(if worked > weekly {
+                        "overtime"
+                    } else {
+                        "remaining"
+                    }),
+                ],
+            )
+        );
+    } else if m.get_flag("short") {
+        println!(
+            "{}",
+            fill(&setting(m, "template", &config.template).unwrap(), &summary)
+        );
+    } else if m.get_flag("bar") {
+        let label = format!(
+            "{}%{}",
+            round(summary.percent.min(100.), 0, summary.round_mode),
+            if summary.delta > Duration::zero() {
+                format!(
+                    " {} {}",
+                    tr("overtime"),
+                    format_duration_signed(&summary.delta)
+                )
+            } else {
+                String::new()
+            }
+        );
+        // The bar gets what the label leaves of the width, besides "[] "
+        let width = output_width(m);
+        let cells = week_grid::CELLS
+            .min(width.saturating_sub(label.chars().count() + 3))
+            .max(1);
+        let line = format!(
+            "[{}] {}",
+            week_grid::bar(summary.work_time, workday, cells),
+            label
+        );
+        println!("{}", fit(&line, width));
+    } else if m.get_flag("target") {
+        if summary.done {
+            println!(
+                "{}",
+                trf(
+                    "Daily goal of {} already met: {} beyond it.",
+                    &[
+                        &format_duration(&workday),
+                        &format_duration_signed(&summary.delta),
+                    ],
+                )
+            );
+        } else {
+            println!(
+                "{}",
+                trf(
+                    "Leave at {} to meet the daily goal of {}.",
+                    &[
+                        &summary.projected_goal.format("%H:%M:%S"),
+                        &format_duration(&workday),
+                    ],
+                )
+            );
+        }
+    } else if m.get_flag("json-pretty") {
+        println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+    } else if let Some(format) = &output {
+        let bytes = match format.as_str() {
+            "msgpack" => rmp_serde::to_vec_named(&summary).unwrap(),
+            "csv" => format!("{}\n", summary.to_csv()).into_bytes(),
+            "plain" => FIELDS
+                .iter()
+                .map(|field| format!("{} {}\n", field.name, (field.value)(&summary)))
+                .collect::<String>()
+                .into_bytes(),
+            _ => {
+                let mut json = serde_json::to_vec(&summary).unwrap();
+                json.push(b'\n');
+                json
+            }
+        };
+        match m.get_one::<String>("output-file") {
+            Some(path) if format == "csv" => {
+                let is_new = std::fs::metadata(path)
+                    .map(|m| m.len() == 0)
+                    .unwrap_or(true);
+                let mut file = match std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                {
+                    Ok(file) => file,
+                    Err(e) => panic!("Cannot open {}: {}", path, e),
+                };
+                if is_new {
+                    writeln!(file, "{}", Summary::CSV_HEADER).unwrap();
+                }
+                if let Err(e) = file.write_all(&bytes) {
+                    panic!("Cannot write {}: {}", path, e);
+                }
+            }
+            Some(path) => {
+                if let Err(e) = std::fs::write(path, bytes) {
+                    panic!("Cannot write {}: {}", path, e);
+                }
+            }
+            None => std::io::stdout().write_all(&bytes).unwrap(),
+        }
+    } else if let Some(names) = m.get_many::<String>("field") {
+        for name in names {
+            match find_field(name) {
+                Some(field) => println!("{}", (field.value)(&summary)),
+                None => return Err(ParseError::UnknownField { name: name.clone() }),
+            }
+        }
+    } else {
+        if breaks.is_empty() && !m.contains_id("break-total") {
+            println!("{}", tr("No breaks defined, using default."));
+        }
+        print_summary(
+            &summary,
+            &TextOptions {
+                countdown: setting(m, "projections", &config.projections).unwrap() == "countdown",
+                truncate_future: !m.get_flag("no-truncate-future"),
+                goal_unit: m.get_flag("goal-unit"),
+            },
+        );
+        if earlier.is_some() {
+            println!(
+                "           {}",
+                trf(
+                    "accumulated today: {} [{}]",
+                    &[
+                        &format_duration(&day_work),
+                        &format_duration_hours(&day_work, summary.round_mode),
+                    ],
+                )
+            );
+        }
+        if let Some((weighted, total)) = balance {
+            println!(
+                "           {}",
+                trf(
+                    "flex balance: {}; today: {} [weighted {}]",
+                    &[
+                        &format_duration_signed(&total),
+                        &format_duration_signed(&(day_work - workday)),
+                        &format_duration_signed(&weighted),
+                    ],
+                )
+            );
+        }
+        if m.get_flag("compare-to-yesterday") {
+            let records = timesheet::read(Path::new(m.get_one::<String>("csv").unwrap()))?;
+            let yesterday = summary.start.date_naive().pred_opt().unwrap();
+            match timesheet::on(&records, yesterday) {
+                Some(record) => println!(
+                    "           {}",
+                    trf(
+                        "compared to yesterday: {} [yesterday {}]",
+                        &[
+                            &format_duration_signed(&(summary.work_time - record.work_time)),
+                            &format_duration(&record.work_time),
+                        ],
+                    )
+                ),
+                None => println!(
+                    "           {}",
+                    trf("compared to yesterday: no record of {}", &[&yesterday])
+                ),
+            }
+        }
+        if let Some(targets) = targets {
+            let mut targets: Vec<(&String, Duration)> = targets
+                .iter()
+                .map(|(name, goal_s)| Ok((name, create_duration(goal_s)?)))
+                .collect::<Result<_, ParseError>>()?;
+            targets.sort_by_key(|(_, goal)| *goal);
+            for (name, goal) in targets {
+                let status = if summary.work_time >= goal {
+                    tr("met").to_owned()
+                } else {
+                    trf(
+                        "{} remaining",
+                        &[&format_duration(&(goal - summary.work_time))],
+                    )
+                };
+                println!(
+                    "           {}",
+                    trf(
+                        "target {}: {} {}",
+                        &[name, &format_duration(&goal), &status],
+                    )
+                );
+            }
+        }
+        if let Some(window_s) = m.get_one::<String>("premium-window") {
+            let window = match window_s.split_once('-') {
+                Some((from, to)) => (
+                    create_time(&localized(&from.to_owned()))?.time(),
+                    create_time(&localized(&to.to_owned()))?.time(),
+                ),
+                None => {
+                    return Err(ParseError::NotAnInterval {
+                        input: window_s.to_owned(),
+                    })
+                }
+            };
+            let premium = premium_time(clock_in, end.unwrap_or(now), &breaks, window);
+            println!(
+                "           {}",
+                trf(
+                    "premium time {}: {} [{} min]",
+                    &[window_s, &format_duration(&premium), &premium.num_minutes(),],
+                )
+            );
+        }
+        if m.get_flag("meetings") {
+            for meeting in &meetings {
+                println!(
+                    "           {}",
+                    trf(
+                        "meeting {}-{} {}",
+                        &[
+                            &meeting.start.format("%H:%M"),
+                            &meeting.end.format("%H:%M"),
+                            &meeting.summary,
+                        ],
+                    )
+                );
+            }
+        }
+    }
+
+    if let (Some(hook), true) = (m.get_one::<String>("on-goal"), summary.done) {
+        let command = fill(hook, &summary);
+        if m.get_flag("dry-run-hook") {
+            println!("{}", command);
+        } else {
+            match std::process::Command::new("sh")
+                .arg("-c")
+                .arg(&command)
+                .status()
+            {
+                Ok(status) if status.success() => {}
+                Ok(status) => eprintln!("{}", trf("Goal hook failed with {}.", &[&status])),
+                Err(e) => eprintln!("{}", trf("Cannot run goal hook: {}", &[&e])),
+            }
+        }
+    }
+
+    if m.get_flag("exit-status") && !summary.done {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/** Configuration of --config, else of the default path if there is one
+ */
+fn load_config(m: &ArgMatches) -> config::Config {
+    match m.get_one::<String>("config") {
+        Some(path) => config::Config::load(Path::new(path)),
+        None => match config::default_path().filter(|path| path.exists()) {
+            Some(path) => config::Config::load(&path),
+            None => config::Config::default(),
+        },
+    }
+}
+
+fn main() {
+    let m = cli().get_matches();
+    i18n::set(
+        m.get_one::<String>("lang")
+            .cloned()
+            .or_else(|| load_config(&m).lang)
+            .and_then(|lang| i18n::Lang::from_name(&lang))
+            .unwrap_or_else(i18n::Lang::from_env),
+    );
+    let watch = m.get_flag("watch");
+    loop {
+        if watch {
+            // Clear the terminal and redraw from its top left
+            print!("\x1b[2J\x1b[H");
+        }
+        if let Err(e) = run(&m) {
+            eprintln!("{}", trf("Error: {}", &[&e]));
+            std::process::exit(e.exit_code());
+        }
+        if !watch {
+            break;
+        }
+        std::io::stdout().flush().unwrap();
+        // Refresh on the start of the next minute
+        let second = Local::now().second() as u64;
+        std::thread::sleep(std::time::Duration::from_secs(60 - second.min(59)));
     }
 }
@@ -0,0 +1,102 @@
+/*! Markdown reference of the command line, generated from the clap definition so it can't drift
+ */
+use clap::{Arg, Command};
+use std::fmt::Write;
+
+fn flags(arg: &Arg) -> String {
+    let mut names = Vec::new();
+    if let Some(short) = arg.get_short() {
+        names.push(format!("-{}", short));
+    }
+    if let Some(long) = arg.get_long() {
+        names.push(format!("--{}", long));
+    }
+    if names.is_empty() {
+        format!("<{}>", arg.get_id())
+    } else {
+        names.join(", ")
+    }
+}
+
+fn render_arg(out: &mut String, arg: &Arg, level: usize) {
+    writeln!(
+        out,
+        "{} `{}` ({})\n",
+        "#".repeat(level),
+        arg.get_id(),
+        flags(arg)
+    )
+    .unwrap();
+    if let Some(help) = arg.get_help() {
+        writeln!(out, "{}\n", help).unwrap();
+    }
+    let possible: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .map(|v| format!("`{}`", v.get_name()))
+        .collect();
+    if !possible.is_empty() {
+        writeln!(out, "Possible values: {}\n", possible.join(", ")).unwrap();
+    }
+    let defaults: Vec<String> = arg
+        .get_default_values()
+        .iter()
+        .map(|v| format!("`{}`", v.to_string_lossy()))
+        .collect();
+    if !defaults.is_empty() {
+        writeln!(out, "Default: {}\n", defaults.join(", ")).unwrap();
+    }
+    if arg.is_required_set() {
+        writeln!(out, "Required.\n").unwrap();
+    }
+}
+
+fn render_command(out: &mut String, cmd: &Command, path: &str, level: usize) {
+    writeln!(out, "{} {}\n", "#".repeat(level), path).unwrap();
+    if let Some(about) = cmd.get_about() {
+        writeln!(out, "{}\n", about).unwrap();
+    }
+    for arg in cmd.get_arguments() {
+        if !matches!(arg.get_id().as_str(), "help" | "version") {
+            render_arg(out, arg, level + 1);
+        }
+    }
+    for sub in cmd.get_subcommands() {
+        render_command(out, sub, &format!("{} {}", path, sub.get_name()), level + 1);
+    }
+}
+
+pub fn render(cmd: &Command) -> String {
+    let mut out = String::new();
+    render_command(&mut out, cmd, cmd.get_name(), 1);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arguments_and_subcommands_get_sections() {
+        let cmd = Command::new("time_rust")
+            .arg(
+                Arg::new("starttime")
+                    .short('s')
+                    .long("start")
+                    .required(true)
+                    .help("Time when work started"),
+            )
+            .subcommand(
+                Command::new("stats")
+                    .about("Statistics")
+                    .arg(Arg::new("width").long("width").default_value("80")),
+            );
+        assert_eq!(
+            render(&cmd),
+            "# time_rust\n\n\
+             ## `starttime` (-s, --start)\n\nTime when work started\n\nRequired.\n\n\
+             ## time_rust stats\n\nStatistics\n\n\
+             ### `width` (--width)\n\nDefault: `80`\n\n"
+        );
+    }
+}
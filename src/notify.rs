@@ -0,0 +1,150 @@
+/*! Desktop notifications while watching a day: the goal done, a lead time before the 9h mark and
+ * before the daily maximum, and a break becoming overdue under the break rules.  Each is sent by
+ * the refresh that passes its moment, so every refresh looks back to the one before it.
+ */
+use crate::error::{choice, ParseError};
+use crate::i18n::{tr, trf};
+use crate::{format_duration, Summary};
+use chrono::{DateTime, Duration, Local};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    Goal,
+    Before9h,
+    BeforeMax,
+    BreakDue,
+}
+
+/// Name of each event, as in --notify-on
+pub const NAMES: [(&str, Event); 4] = [
+    ("goal", Event::Goal),
+    ("9h", Event::Before9h),
+    ("max", Event::BeforeMax),
+    ("break", Event::BreakDue),
+];
+
+impl Event {
+    pub fn from_name(name: &str) -> Result<Event, ParseError> {
+        choice("notify-on", name, &NAMES)
+    }
+}
+
+/** Events of a comma-separated list of names, e.g. goal,max
+ */
+pub fn enabled(names: &str) -> Result<Vec<Event>, ParseError> {
+    names
+        .split(',')
+        .map(|name| Event::from_name(name.trim()))
+        .collect()
+}
+
+/// Every event, in the order they usually happen
+pub const EVENTS: [Event; 4] = [
+    Event::BreakDue,
+    Event::Goal,
+    Event::Before9h,
+    Event::BeforeMax,
+];
+
+/** Moment an event is due on, if the day has one
+ */
+fn moment(
+    event: Event,
+    s: &Summary,
+    lead: Duration,
+    break_due: Option<DateTime<Local>>,
+) -> Option<DateTime<Local>> {
+    match event {
+        Event::Goal => Some(s.projected_goal),
+        Event::Before9h => Some(s.projected_9h - lead),
+        Event::BeforeMax => Some(s.projected_10h - lead),
+        Event::BreakDue => break_due,
+    }
+}
+
+/** The enabled events due after `since` up to the summary's now; a finished day has none
+ */
+pub fn due(
+    enabled: &[Event],
+    s: &Summary,
+    lead: Duration,
+    break_due: Option<DateTime<Local>>,
+    since: DateTime<Local>,
+) -> Vec<Event> {
+    if s.end.is_some() {
+        return Vec::new();
+    }
+    enabled
+        .iter()
+        .copied()
+        .filter(|&event| {
+            moment(event, s, lead, break_due).is_some_and(|at| since < at && at <= s.now)
+        })
+        .collect()
+}
+
+/** Text of the notification of an event
+ */
+pub fn message(event: Event, s: &Summary, lead: Duration) -> String {
+    match event {
+        Event::Goal => trf("Daily goal of {} done", &[&format_duration(&s.workday)]),
+        Event::Before9h => trf(
+            "{} left until 9 hours of work, at {}",
+            &[&format_duration(&lead), &s.projected_9h.time()],
+        ),
+        Event::BeforeMax => trf(
+            "{} left until the daily maximum of {}, at {}",
+            &[
+                &format_duration(&lead),
+                &format_duration(&s.max_daily),
+                &s.projected_10h.time(),
+            ],
+        ),
+        Event::BreakDue => tr("A break is overdue").to_string(),
+    }
+}
+
+pub fn send(body: &str) {
+    if let Err(e) = notify_rust::Notification::new()
+        .summary(env!("CARGO_PKG_NAME"))
+        .body(body)
+        .show()
+    {
+        eprintln!("{}", trf("Cannot send notification: {}", &[&e]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_time, summarize, Options};
+
+    #[test]
+    fn due_once_when_passed() {
+        let summary = |now: &str| {
+            let start = create_time("8:00").unwrap();
+            summarize(
+                create_time(now).unwrap(),
+                start,
+                start,
+                None,
+                Duration::try_hours(8).unwrap(),
+                &[],
+                &Options::default(),
+            )
+        };
+        let lead = Duration::try_minutes(15).unwrap();
+        let minute = Duration::try_minutes(1).unwrap();
+        // 8 hours and the default 30 minutes break
+        let s = summary("16:30");
+        assert_eq!(due(&EVENTS, &s, lead, None, s.now - minute), [Event::Goal]);
+        let s = summary("16:31");
+        assert!(due(&EVENTS, &s, lead, None, s.now - minute).is_empty());
+        let s = summary("17:30");
+        assert_eq!(
+            due(&EVENTS, &s, lead, None, s.now - minute),
+            [Event::Before9h]
+        );
+        assert!(due(&[Event::Goal], &s, lead, None, s.now - minute).is_empty());
+    }
+}
@@ -0,0 +1,66 @@
+/*! Focus cycles of the pomodoro subcommand: stretches of work, each followed by a short break, and
+ * every few a long break instead.  The work is clocked in and out on the clock file, so it counts
+ * toward the day like any other.
+ */
+use chrono::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Work => "Work",
+            Phase::ShortBreak => "Short break",
+            Phase::LongBreak => "Long break",
+        }
+    }
+}
+
+pub struct Cycle {
+    pub work: Duration,
+    pub short_break: Duration,
+    pub long_break: Duration,
+    /// Number of work phases up to and including the one before a long break
+    pub long_every: u32,
+}
+
+impl Cycle {
+    /** The phases with their lengths, endlessly, each work phase numbered from 1
+     */
+    pub fn phases(&self) -> impl Iterator<Item = (u32, Phase, Duration)> + '_ {
+        (1..).flat_map(move |n| {
+            let rest = if n % self.long_every.max(1) == 0 {
+                (n, Phase::LongBreak, self.long_break)
+            } else {
+                (n, Phase::ShortBreak, self.short_break)
+            };
+            [(n, Phase::Work, self.work), rest]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_break_every_fourth() {
+        let minutes = |m| Duration::try_minutes(m).unwrap();
+        let cycle = Cycle {
+            work: minutes(25),
+            short_break: minutes(5),
+            long_break: minutes(15),
+            long_every: 4,
+        };
+        let phases: Vec<(u32, Phase, Duration)> = cycle.phases().take(10).collect();
+        assert_eq!(phases[0], (1, Phase::Work, minutes(25)));
+        assert_eq!(phases[1], (1, Phase::ShortBreak, minutes(5)));
+        assert_eq!(phases[7], (4, Phase::LongBreak, minutes(15)));
+        assert_eq!(phases[9], (5, Phase::ShortBreak, minutes(5)));
+    }
+}
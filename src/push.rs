@@ -0,0 +1,336 @@
+/*! Upload of the logged days to an external time tracker, one entry per day: a time entry of
+ * Toggl Track or a worklog of Jira Tempo.  The credentials come from the [push] table of the
+ * config:
+ *
+ *     [push.toggl]
+ *     api-token = "1971800d4d82861d8f2c1651fea4d212"
+ *     workspace-id = 1234567
+ *     project-id = 7654321
+ *
+ *     [push.tempo]
+ *     api-token = "tempo-token"
+ *     account-id = "5b10ac8d82e05b22cc7d4ef5"
+ *     issue-id = 10001
+ *
+ * Pushed entries are kept in a ledger in JSON, by default $XDG_DATA_HOME/time_rust/pushed:
+ *
+ *     {"toggl":{"2024-01-15":{"id":"3456789012","start":"08:00:00","seconds":28800,
+ *      "description":"clientA"}}}
+ *
+ * Pushing a day again updates its remote entry if the day changed and leaves it alone otherwise,
+ * so the same range can be pushed as often as needed.
+ */
+use crate::i18n::trf;
+use crate::timesheet::Record;
+use base64::Engine;
+use chrono::{Local, NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/** Credentials and target of Toggl Track
+ */
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct Toggl {
+    pub api_token: String,
+    pub workspace_id: u64,
+    pub project_id: Option<u64>,
+}
+
+/** Credentials and target of Jira Tempo; worklogs go on one issue by its numeric id
+ */
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct Tempo {
+    pub api_token: String,
+    pub account_id: String,
+    pub issue_id: u64,
+}
+
+/** The [push] table as written in the config
+ */
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Spec {
+    pub toggl: Option<Toggl>,
+    pub tempo: Option<Tempo>,
+    /// Description of a day without tags, by default "Work"
+    pub description: Option<String>,
+}
+
+pub enum Service {
+    Toggl(Toggl),
+    Tempo(Tempo),
+}
+
+impl Service {
+    /** The service of the name, configured in the spec
+     */
+    pub fn from_spec(name: &str, spec: &Spec) -> Result<Service, String> {
+        let missing = || trf("no [push.{}] table in the config", &[&name]);
+        match name {
+            "toggl" => spec.toggl.clone().map(Service::Toggl).ok_or_else(missing),
+            "tempo" => spec.tempo.clone().map(Service::Tempo).ok_or_else(missing),
+            _ => Err(trf("unknown service '{}'", &[&name])),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Service::Toggl(_) => "toggl",
+            Service::Tempo(_) => "tempo",
+        }
+    }
+
+    /** Request body of an entry, as its API expects it
+     */
+    pub fn body(&self, entry: &Entry) -> Value {
+        match self {
+            Service::Toggl(toggl) => {
+                let start = entry
+                    .date
+                    .and_time(entry.start)
+                    .and_local_timezone(Local)
+                    .earliest()
+                    .unwrap();
+                json!({
+                    "created_with": "time_rust",
+                    "workspace_id": toggl.workspace_id,
+                    "project_id": toggl.project_id,
+                    "start": start.to_rfc3339(),
+                    "duration": entry.seconds,
+                    "description": entry.description,
+                })
+            }
+            Service::Tempo(tempo) => json!({
+                "issueId": tempo.issue_id,
+                "authorAccountId": tempo.account_id,
+                "startDate": entry.date.to_string(),
+                "startTime": entry.start.format("%H:%M:%S").to_string(),
+                "timeSpentSeconds": entry.seconds,
+                "description": entry.description,
+            }),
+        }
+    }
+
+    /** Create or update the remote entry; the id of the entry
+     */
+    pub fn send(&self, action: &Action) -> Result<String, String> {
+        let (entry, id) = match action {
+            Action::Create(entry) => (entry, None),
+            Action::Update(id, entry) => (entry, Some(id)),
+            Action::Unchanged(id, _) => return Ok(id.clone()),
+        };
+        let (url, auth, id_field) = match self {
+            Service::Toggl(toggl) => (
+                format!(
+                    "https://api.track.toggl.com/api/v9/workspaces/{}/time_entries",
+                    toggl.workspace_id
+                ),
+                format!(
+                    "Basic {}",
+                    base64::engine::general_purpose::STANDARD
+                        .encode(format!("{}:api_token", toggl.api_token))
+                ),
+                "id",
+            ),
+            Service::Tempo(tempo) => (
+                "https://api.tempo.io/4/worklogs".to_owned(),
+                format!("Bearer {}", tempo.api_token),
+                "tempoWorklogId",
+            ),
+        };
+        let request = match id {
+            Some(id) => ureq::put(format!("{}/{}", url, id)),
+            None => ureq::post(url),
+        };
+        let response: Value = request
+            .header("Authorization", auth)
+            .send_json(self.body(entry))
+            .and_then(|mut response| response.body_mut().read_json())
+            .map_err(|e| trf("{} rejected {}: {}", &[&self.name(), &entry.date, &e]))?;
+        match &response[id_field] {
+            Value::Number(id) => Ok(id.to_string()),
+            _ => Err(trf(
+                "{} answered {} without an id",
+                &[&self.name(), &entry.date],
+            )),
+        }
+    }
+}
+
+/** A day as it is pushed
+ */
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Entry {
+    #[serde(skip)]
+    pub date: NaiveDate,
+    pub start: NaiveTime,
+    pub seconds: i64,
+    pub description: String,
+}
+
+impl Entry {
+    /** Entry of a record, described by its tags or else by the default description
+     */
+    pub fn of(record: &Record, description: &str) -> Entry {
+        let tags: Vec<&str> = record.tags.keys().map(|tag| tag.as_str()).collect();
+        Entry {
+            date: record.date,
+            start: record.start,
+            seconds: record.work_time.num_seconds(),
+            description: if tags.is_empty() {
+                description.to_owned()
+            } else {
+                tags.join(", ")
+            },
+        }
+    }
+}
+
+/** What pushing an entry takes, given the ledger
+ */
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    Create(Entry),
+    /// The remote entry of the id differs and is overwritten
+    Update(String, Entry),
+    /// The remote entry of the id is up to date
+    Unchanged(String, Entry),
+}
+
+impl Action {
+    pub fn entry(&self) -> &Entry {
+        match self {
+            Action::Create(entry) | Action::Update(_, entry) | Action::Unchanged(_, entry) => entry,
+        }
+    }
+}
+
+/** Remote entry of a pushed day and the entry it was pushed as
+ */
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Pushed {
+    pub id: String,
+    #[serde(flatten)]
+    pub entry: Entry,
+}
+
+/** Pushed days per service
+ */
+#[derive(Serialize, Deserialize, Default)]
+pub struct Ledger(pub BTreeMap<String, BTreeMap<NaiveDate, Pushed>>);
+
+impl Ledger {
+    /** Load the ledger; a missing file is an empty ledger
+     */
+    pub fn load(path: &Path) -> Ledger {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(_) => return Ledger::default(),
+        };
+        match Ledger::parse(&content) {
+            Ok(ledger) => ledger,
+            Err(e) => panic!("Invalid push ledger {}: {}", path.display(), e),
+        }
+    }
+
+    /** The ledger in JSON, the entries dated by their keys
+     */
+    fn parse(content: &str) -> serde_json::Result<Ledger> {
+        let mut ledger: Ledger = serde_json::from_str(content)?;
+        for days in ledger.0.values_mut() {
+            for (date, pushed) in days.iter_mut() {
+                pushed.entry.date = *date;
+            }
+        }
+        Ok(ledger)
+    }
+
+    pub fn save(&self, path: &Path) {
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Err(e) = fs::write(path, serde_json::to_string_pretty(self).unwrap()) {
+            panic!("Cannot write push ledger {}: {}", path.display(), e);
+        }
+    }
+
+    /** What pushing each entry to the service takes
+     */
+    pub fn plan(&self, service: &str, entries: Vec<Entry>) -> Vec<Action> {
+        let pushed = self.0.get(service);
+        entries
+            .into_iter()
+            .map(|entry| match pushed.and_then(|p| p.get(&entry.date)) {
+                Some(p) if p.entry == entry => Action::Unchanged(p.id.clone(), entry),
+                Some(p) => Action::Update(p.id.clone(), entry),
+                None => Action::Create(entry),
+            })
+            .collect()
+    }
+
+    pub fn record(&mut self, service: &str, id: String, entry: Entry) {
+        self.0
+            .entry(service.to_owned())
+            .or_default()
+            .insert(entry.date, Pushed { id, entry });
+    }
+}
+
+pub fn default_path() -> PathBuf {
+    let data = match std::env::var("XDG_DATA_HOME") {
+        Ok(data) if !data.is_empty() => PathBuf::from(data),
+        _ => match std::env::var("HOME") {
+            Ok(home) => PathBuf::from(home).join(".local").join("share"),
+            Err(_) => panic!("Neither XDG_DATA_HOME nor HOME set for the push ledger.  Stop!"),
+        },
+    };
+    data.join("time_rust").join("pushed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_against_ledger() {
+        let time = |t: &str| NaiveTime::parse_from_str(t, "%H:%M").unwrap();
+        let entry = |day: u32, seconds| Entry {
+            date: NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            start: time("08:00"),
+            seconds,
+            description: "Work".to_owned(),
+        };
+        let mut ledger = Ledger::default();
+        ledger.record("toggl", "11".to_owned(), entry(15, 28800));
+        ledger.record("toggl", "12".to_owned(), entry(16, 28800));
+        let actions = ledger.plan(
+            "toggl",
+            vec![entry(15, 28800), entry(16, 25200), entry(17, 1)],
+        );
+        assert_eq!(
+            actions,
+            [
+                Action::Unchanged("11".to_owned(), entry(15, 28800)),
+                Action::Update("12".to_owned(), entry(16, 25200)),
+                Action::Create(entry(17, 1)),
+            ]
+        );
+        assert_eq!(ledger.plan("tempo", vec![entry(15, 28800)]).len(), 1);
+
+        let saved = serde_json::to_string(&ledger).unwrap();
+        assert!(saved.starts_with(
+            "{\"toggl\":{\"2024-01-15\":{\"id\":\"11\",\"start\":\"08:00:00\",\"seconds\":28800,"
+        ));
+        assert_eq!(
+            Ledger::parse(&saved)
+                .unwrap()
+                .plan("toggl", vec![entry(15, 28800)]),
+            [Action::Unchanged("11".to_owned(), entry(15, 28800))]
+        );
+    }
+}
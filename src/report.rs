@@ -0,0 +1,331 @@
+/*! Table of the logged days of a date range, with subtotals per ISO week and a grand total, each
+ * against the daily goals of its days, and optionally broken down by tag
+ */
+use crate::error::ParseError;
+use crate::i18n::{tr, trf};
+use crate::timesheet::{self, Record};
+use crate::{fit, format_duration, format_duration_signed, seconds, seconds_by_name};
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/** Span of days a report covers
+ */
+pub enum Period {
+    /// Monday to Sunday of the week of the date
+    Week(NaiveDate),
+    /// The calendar month of the date
+    Month(NaiveDate),
+    /// Both dates inclusive and optional
+    Range(Option<NaiveDate>, Option<NaiveDate>),
+}
+
+impl Period {
+    pub fn bounds(&self) -> (Option<NaiveDate>, Option<NaiveDate>) {
+        match *self {
+            Period::Week(date) => {
+                let week = date.week(Weekday::Mon);
+                (Some(week.first_day()), Some(week.last_day()))
+            }
+            Period::Month(date) => {
+                let first = date.with_day(1).unwrap();
+                (Some(first), (first + Months::new(1)).pred_opt())
+            }
+            Period::Range(from, to) => (from, to),
+        }
+    }
+}
+
+/** Sum, average, and over- or undertime of some days
+ */
+struct Tally {
+    days: i32,
+    work: Duration,
+    goal: Duration,
+    tags: BTreeMap<String, Duration>,
+}
+
+impl Tally {
+    fn new() -> Tally {
+        Tally {
+            days: 0,
+            work: Duration::zero(),
+            goal: Duration::zero(),
+            tags: BTreeMap::new(),
+        }
+    }
+
+    fn add(&mut self, record: &Record, goal: Duration) {
+        self.days += 1;
+        self.work += record.work_time;
+        self.goal += goal;
+        for (tag, work) in &record.tags {
+            *self.tags.entry(tag.clone()).or_insert_with(Duration::zero) += *work;
+        }
+    }
+
+    fn row(&self, label: &str) -> String {
+        format!(
+            "{:<14}{:<10}{:<11}{}",
+            label,
+            format_duration(&self.work),
+            format_duration_signed(&(self.work - self.goal)),
+            trf(
+                "avg {} over {} day(s)",
+                &[
+                    &format_duration(&(self.work / self.days.max(1))),
+                    &self.days
+                ],
+            )
+        )
+    }
+}
+
+/** One line per tag with its work time, indented below the line they break down
+ */
+fn tag_rows(tags: &BTreeMap<String, Duration>) -> Vec<String> {
+    tags.iter()
+        .map(|(tag, work)| format!("  {:<12}{}", tag, format_duration(work)))
+        .collect()
+}
+
+/** A logged day against its goal, for machine-readable reports
+ */
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Day {
+    pub date: NaiveDate,
+    #[serde(rename = "work_time_seconds", with = "seconds")]
+    pub work_time: Duration,
+    #[serde(rename = "goal_seconds", with = "seconds")]
+    pub goal: Duration,
+    #[serde(rename = "delta_seconds", with = "seconds")]
+    pub delta: Duration,
+    #[serde(rename = "tag_seconds", with = "seconds_by_name")]
+    pub tags: BTreeMap<String, Duration>,
+}
+
+/** The days of the period that have a record, the last record of a day counting, each against the
+ * goal of its date
+ */
+pub fn days(
+    records: &[Record],
+    period: &Period,
+    goal: impl Fn(NaiveDate) -> Result<Duration, ParseError>,
+) -> Result<Vec<Day>, ParseError> {
+    let (from, to) = period.bounds();
+    let mut dates: Vec<NaiveDate> = timesheet::between(records, from, to)
+        .iter()
+        .map(|r| r.date)
+        .collect();
+    dates.dedup();
+    dates
+        .into_iter()
+        .map(|date| {
+            let record = timesheet::on(records, date).unwrap();
+            let goal = goal(date)?;
+            Ok(Day {
+                date,
+                work_time: record.work_time,
+                goal,
+                delta: record.work_time - goal,
+                tags: record.tags.clone(),
+            })
+        })
+        .collect()
+}
+
+/** Render the days of the period that have a record, the last record of a day counting, each
+ * against the goal of its date, with the work time per tag of days, weeks, and the total if
+ * `by_tag`; no wider than `width`
+ */
+pub fn render(
+    records: &[Record],
+    period: &Period,
+    goal: impl Fn(NaiveDate) -> Result<Duration, ParseError>,
+    by_tag: bool,
+    width: usize,
+) -> Result<String, ParseError> {
+    let (from, to) = period.bounds();
+    let mut days: Vec<NaiveDate> = timesheet::between(records, from, to)
+        .iter()
+        .map(|r| r.date)
+        .collect();
+    days.dedup();
+
+    let mut lines = vec![format!(
+        "{:<14}{:<10}{}",
+        tr("date"),
+        tr("work"),
+        tr("delta")
+    )];
+    let mut total = Tally::new();
+    let mut week = Tally::new();
+    for (i, &day) in days.iter().enumerate() {
+        let record = timesheet::on(records, day).unwrap();
+        let goal = goal(day)?;
+        lines.push(format!(
+            "{:<14}{:<10}{}",
+            day.to_string(),
+            format_duration(&record.work_time),
+            format_duration_signed(&(record.work_time - goal))
+        ));
+        if by_tag {
+            lines.extend(tag_rows(&record.tags));
+        }
+        week.add(record, goal);
+        total.add(record, goal);
+        if days
+            .get(i + 1)
+            .is_none_or(|next| next.iso_week() != day.iso_week())
+        {
+            let iso = day.iso_week();
+            lines.push(week.row(&format!("{}-W{:02}", iso.year(), iso.week())));
+            if by_tag {
+                lines.extend(tag_rows(&week.tags));
+            }
+            week = Tally::new();
+        }
+    }
+    lines.push(total.row(tr("total")));
+    if by_tag {
+        lines.extend(tag_rows(&total.tags));
+    }
+
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&fit(&line, width));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(date: &str, hours: i64) -> Record {
+        let time = |t: &str| chrono::NaiveTime::parse_from_str(t, "%H:%M").unwrap();
+        Record {
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            start: time("08:00"),
+            end: time("16:00"),
+            break_time: Duration::zero(),
+            work_time: Duration::try_hours(hours).unwrap(),
+            breaks: Vec::new(),
+            goal_met: false,
+            provisional: false,
+            tags: BTreeMap::new(),
+            zone: None,
+        }
+    }
+
+    #[test]
+    fn month_bounds() {
+        let date = NaiveDate::from_ymd_opt(2024, 2, 10).unwrap();
+        assert_eq!(
+            Period::Month(date).bounds(),
+            (
+                NaiveDate::from_ymd_opt(2024, 2, 1),
+                NaiveDate::from_ymd_opt(2024, 2, 29)
+            )
+        );
+    }
+
+    #[test]
+    fn weeks_and_total() {
+        let records = [
+            record("2024-01-12", 9),
+            record("2024-01-15", 8),
+            record("2024-01-16", 6),
+            record("2024-01-16", 7),
+        ];
+        let report = render(
+            &records,
+            &Period::Range(None, None),
+            |_| Ok(Duration::try_hours(8).unwrap()),
+            false,
+            80,
+        )
+        .unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert!(lines[2].starts_with("2024-W02      09:00:00  +01:00:00"));
+        assert!(lines[5].starts_with("2024-W03      15:00:00  -01:00:00"));
+        assert!(lines[6].starts_with("total         24:00:00  +00:00:00  avg 08:00:00 over 3"));
+    }
+
+    #[test]
+    fn goal_per_date() {
+        let records = [record("2024-01-18", 8), record("2024-01-19", 5)];
+        let report = render(
+            &records,
+            &Period::Week(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()),
+            |date| {
+                Ok(Duration::try_hours(match date.weekday() {
+                    Weekday::Fri => 5,
+                    _ => 8,
+                })
+                .unwrap())
+            },
+            false,
+            80,
+        )
+        .unwrap();
+        assert!(report.contains("2024-01-19    05:00:00  +00:00:00"));
+        assert!(report.contains("2024-W03      13:00:00  +00:00:00"));
+    }
+
+    #[test]
+    fn days_of_week() {
+        let records = [
+            record("2024-01-12", 9),
+            record("2024-01-15", 6),
+            record("2024-01-15", 7),
+        ];
+        let days = days(
+            &records,
+            &Period::Week(NaiveDate::from_ymd_opt(2024, 1, 17).unwrap()),
+            |_| Ok(Duration::try_hours(8).unwrap()),
+        )
+        .unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].work_time, Duration::try_hours(7).unwrap());
+        assert_eq!(days[0].delta, Duration::try_hours(-1).unwrap());
+        assert_eq!(
+            serde_json::to_string(&days[0]).unwrap(),
+            "{\"date\":\"2024-01-15\",\"work_time_seconds\":25200,\"goal_seconds\":28800,\
+             \"delta_seconds\":-3600,\"tag_seconds\":{}}"
+        );
+    }
+
+    #[test]
+    fn hours_by_tag() {
+        let mut monday = record("2024-01-15", 8);
+        monday
+            .tags
+            .insert("clientA".to_owned(), Duration::try_hours(5).unwrap());
+        monday
+            .tags
+            .insert("meeting".to_owned(), Duration::try_hours(3).unwrap());
+        let mut tuesday = record("2024-01-16", 6);
+        tuesday
+            .tags
+            .insert("clientA".to_owned(), Duration::try_hours(6).unwrap());
+        let report = render(
+            &[monday, tuesday],
+            &Period::Range(None, None),
+            |_| Ok(Duration::try_hours(8).unwrap()),
+            true,
+            80,
+        )
+        .unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[2], "  clientA     05:00:00");
+        assert_eq!(
+            lines[6],
+            "2024-W03      14:00:00  -02:00:00  avg 07:00:00 over 2 day(s)"
+        );
+        assert_eq!(lines[7], "  clientA     11:00:00");
+        assert_eq!(lines[8], "  meeting     03:00:00");
+    }
+}
@@ -0,0 +1,214 @@
+/*! Statutory break rules the logged breaks are checked against, from a preset or the [rules]
+ * table of the config, e.g. the German working hours act:
+ *
+ *     [rules]
+ *     required = { "6:00" = "0:30", "9:00" = "0:45" }
+ *     max-stretch = "6:00"
+ *     min-break = "0:15"
+ *
+ * Work of at least a duration in required needs breaks of at least its value in total.  No stretch
+ * of work may run longer than max-stretch without a break, and breaks shorter than min-break count
+ * for neither.  A preset given with preset = "de-arbzg" supplies what the table leaves out.
+ */
+use crate::error::ParseError;
+use crate::i18n::trf;
+use crate::{create_duration, format_duration, Interval};
+use chrono::{DateTime, Duration, Local};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/** The [rules] table as written in the config
+ */
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Spec {
+    pub preset: Option<String>,
+    /// Required total break <HH:MM[:SS]> keyed by worked duration <HH:MM[:SS]>
+    pub required: BTreeMap<String, String>,
+    pub max_stretch: Option<String>,
+    pub min_break: Option<String>,
+}
+
+pub struct RuleSet {
+    /// Worked duration and the total break it requires, ascending
+    pub required: Vec<(Duration, Duration)>,
+    pub max_stretch: Option<Duration>,
+    pub min_break: Duration,
+}
+
+impl RuleSet {
+    /** Rule set by name: de-arbzg (German working hours act) or none
+     */
+    pub fn preset(name: &str) -> RuleSet {
+        let minutes = |m| Duration::try_minutes(m).unwrap();
+        match name {
+            "de-arbzg" => RuleSet {
+                required: vec![
+                    (Duration::try_hours(6).unwrap(), minutes(30)),
+                    (Duration::try_hours(9).unwrap(), minutes(45)),
+                ],
+                max_stretch: Some(Duration::try_hours(6).unwrap()),
+                min_break: minutes(15),
+            },
+            "none" => RuleSet {
+                required: Vec::new(),
+                max_stretch: None,
+                min_break: Duration::zero(),
+            },
+            _ => panic!("Unknown rule set '{}'.  Stop!", name),
+        }
+    }
+
+    pub fn from_spec(spec: &Spec) -> Result<RuleSet, ParseError> {
+        let mut rules = RuleSet::preset(spec.preset.as_deref().unwrap_or("none"));
+        if !spec.required.is_empty() {
+            rules.required = spec
+                .required
+                .iter()
+                .map(|(worked, required)| {
+                    Ok((create_duration(worked)?, create_duration(required)?))
+                })
+                .collect::<Result<_, ParseError>>()?;
+            rules.required.sort();
+        }
+        if let Some(max_stretch) = &spec.max_stretch {
+            rules.max_stretch = Some(create_duration(max_stretch)?);
+        }
+        if let Some(min_break) = &spec.min_break {
+            rules.min_break = create_duration(min_break)?;
+        }
+        Ok(rules)
+    }
+
+    /** Total break required for the given worked duration
+     */
+    pub fn required(&self, worked: Duration) -> Duration {
+        self.required
+            .iter()
+            .rev()
+            .find(|(from, _)| worked >= *from)
+            .map(|(_, required)| *required)
+            .unwrap_or_else(Duration::zero)
+    }
+
+    /** Moment the stretch of work since the last break counted, or since the start, outruns the
+     * longest one allowed
+     */
+    pub fn break_due(
+        &self,
+        start: DateTime<Local>,
+        breaks: &[Interval],
+    ) -> Option<DateTime<Local>> {
+        let since = breaks
+            .iter()
+            .filter(|(from, to)| *to - *from >= self.min_break)
+            .map(|(_, to)| *to)
+            .max()
+            .unwrap_or(start);
+        self.max_stretch.map(|max_stretch| since + max_stretch)
+    }
+
+    /** Violations of the rules by a working period from start to end and its breaks, which lie
+     * within the period and don't overlap
+     */
+    pub fn check(
+        &self,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        breaks: &[Interval],
+    ) -> Vec<String> {
+        let mut notes = Vec::new();
+        let mut counted: Vec<Interval> = breaks
+            .iter()
+            .filter(|(from, to)| *to - *from >= self.min_break)
+            .copied()
+            .collect();
+        counted.sort();
+        let break_time: Duration = counted.iter().map(|(from, to)| *to - *from).sum();
+        let worked = end - start - breaks.iter().map(|(from, to)| *to - *from).sum();
+        let required = self.required(worked);
+        if break_time < required {
+            notes.push(trf(
+                "breaks of {} fall short of the {} required for {} of work",
+                &[
+                    &format_duration(&break_time),
+                    &format_duration(&required),
+                    &format_duration(&worked),
+                ],
+            ));
+        }
+        if let Some(max_stretch) = self.max_stretch {
+            let mut stretch_start = start;
+            for (from, to) in counted.into_iter().chain(std::iter::once((end, end))) {
+                if from - stretch_start > max_stretch {
+                    notes.push(trf(
+                        "worked {} from {} to {} without a break, more than {}",
+                        &[
+                            &format_duration(&(from - stretch_start)),
+                            &stretch_start.time(),
+                            &from.time(),
+                            &format_duration(&max_stretch),
+                        ],
+                    ));
+                }
+                stretch_start = to;
+            }
+        }
+        notes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_time, parse_interval};
+
+    #[test]
+    fn arbzg_required_and_stretch() {
+        let rules = RuleSet::preset("de-arbzg");
+        let start = create_time("7:00").unwrap();
+        let end = create_time("17:00").unwrap();
+        let breaks = [
+            parse_interval("12:00-12:30").unwrap(),
+            parse_interval("15:00-15:10").unwrap(),
+        ];
+        let notes = rules.check(start, end, &breaks);
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].starts_with("breaks of 00:30:00 fall short of the 00:45:00"));
+
+        let notes = rules.check(
+            start,
+            create_time("14:00").unwrap(),
+            &[parse_interval("13:00-13:10").unwrap()],
+        );
+        assert_eq!(notes.len(), 2);
+        assert!(notes[1].starts_with("worked 07:00:00 from 07:00:00 to 14:00:00"));
+    }
+
+    #[test]
+    fn break_due_after_longest_stretch() {
+        let rules = RuleSet::preset("de-arbzg");
+        let start = create_time("7:00").unwrap();
+        assert_eq!(rules.break_due(start, &[]), create_time("13:00").ok());
+        let breaks = [
+            parse_interval("11:00-11:30").unwrap(),
+            parse_interval("14:00-14:10").unwrap(),
+        ];
+        assert_eq!(rules.break_due(start, &breaks), create_time("17:30").ok());
+    }
+
+    #[test]
+    fn spec_overrides_preset() {
+        let spec: Spec = toml::from_str(
+            "preset = \"de-arbzg\"\nrequired = { \"4:30\" = \"0:30\" }\nmin-break = \"0:10\"\n",
+        )
+        .unwrap();
+        let rules = RuleSet::from_spec(&spec).unwrap();
+        assert_eq!(
+            rules.required(create_duration("5:00").unwrap()),
+            create_duration("0:30").unwrap()
+        );
+        assert_eq!(rules.max_stretch, Some(create_duration("6:00").unwrap()));
+        assert_eq!(rules.min_break, create_duration("0:10").unwrap());
+    }
+}
@@ -0,0 +1,65 @@
+/*! Planned shifts per weekday as TOML, e.g.
+ *
+ *     [monday]
+ *     start = "8:00"
+ *     end = "16:30"
+ *
+ * Actual start and end times within a tolerance of the planned ones are snapped to them.
+ */
+use chrono::Weekday;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Deserialize)]
+pub struct Shift {
+    /// Planned start <HH:MM[:SS]>
+    pub start: String,
+    /// Planned end <HH:MM[:SS]>
+    pub end: String,
+}
+
+/** Shifts keyed by weekday name
+ */
+#[derive(Deserialize)]
+pub struct Schedule(BTreeMap<String, Shift>);
+
+impl Schedule {
+    pub fn load(path: &Path) -> Schedule {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => panic!("Cannot read schedule {}: {}", path.display(), e),
+        };
+        match toml::from_str(&content) {
+            Ok(schedule) => schedule,
+            Err(e) => panic!("Invalid schedule {}: {}", path.display(), e),
+        }
+    }
+
+    /** Shift planned for the given weekday, if any
+     */
+    pub fn shift(&self, weekday: Weekday) -> Option<&Shift> {
+        self.0
+            .iter()
+            .find(|(key, _)| key.parse::<Weekday>() == Ok(weekday))
+            .map(|(_, shift)| shift)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shift_by_weekday() {
+        let schedule: Schedule =
+            toml::from_str("[monday]\nstart = \"8:00\"\nend = \"16:30\"\n").unwrap();
+        let monday = schedule.shift(Weekday::Mon).unwrap();
+        assert_eq!(
+            (monday.start.as_str(), monday.end.as_str()),
+            ("8:00", "16:30")
+        );
+        assert!(schedule.shift(Weekday::Tue).is_none());
+    }
+}
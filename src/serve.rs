@@ -0,0 +1,268 @@
+/*! JSON API of the serve subcommand, for editor plugins, status bars, and scripts on the same
+ * machine:
+ *
+ *     GET  /status                  today from the clock file: running, and the summary if worked
+ *     POST /start[?tag=<tag>]       clock in, 409 if already clocked in
+ *     POST /stop                    clock out, 409 if not clocked in
+ *     GET  /report?week=<date>      days of the timesheet against their goals; also month=<date>,
+ *                                   or from=<date> and to=<date>, each optional
+ *
+ * Query values may be percent-encoded, and a tag is a single word.  Errors come as
+ * {"error": <message>}, with status 500 for a malformed clock file or timesheet.
+ *
+ * There is no authentication, so the server should only listen on the loopback interface.
+ */
+use chrono::{Duration, Local, NaiveDate};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use time_rust::error::ParseError;
+use time_rust::i18n::trf;
+use time_rust::{events, report, timesheet, Rounding};
+use tiny_http::{Header, Method, Response, Server};
+
+pub struct Api {
+    clock_file: PathBuf,
+    /// Timesheet of the reports
+    timesheet: Option<PathBuf>,
+    goal: Box<dyn Fn(NaiveDate) -> Duration>,
+    rounding: Rounding,
+}
+
+impl Api {
+    pub fn new(
+        clock_file: PathBuf,
+        timesheet: Option<PathBuf>,
+        goal: Box<dyn Fn(NaiveDate) -> Duration>,
+        rounding: Rounding,
+    ) -> Api {
+        Api {
+            clock_file,
+            timesheet,
+            goal,
+            rounding,
+        }
+    }
+
+    fn status(&self) -> Result<Value, ParseError> {
+        let now = Local::now();
+        let today = if self.clock_file.exists() {
+            events::on(events::read(&self.clock_file)?, now.date_naive())
+        } else {
+            Vec::new()
+        };
+        let segments = events::segments(&today);
+        Ok(json!({
+            "running": events::is_running(&today),
+            "summary": crate::clock_summary(&segments, now, (self.goal)(now.date_naive())),
+        }))
+    }
+
+    fn clock(&self, kind: events::Kind, tag: Option<&str>) -> (u16, Value) {
+        if tag.is_some_and(|tag| tag.is_empty() || tag.contains(char::is_whitespace)) {
+            return (400, json!({ "error": "A tag is one word." }));
+        }
+        match crate::clock_event(&self.clock_file, kind, Local::now(), tag) {
+            Ok(true) => answer(self.status()),
+            Ok(false) if kind == events::Kind::Start => {
+                (409, json!({ "error": "Already clocked in." }))
+            }
+            Ok(false) => (409, json!({ "error": "Not clocked in." })),
+            Err(e) => answer(Err(e)),
+        }
+    }
+
+    fn report(&self, query: &[(String, String)]) -> (u16, Value) {
+        let Some(path) = &self.timesheet else {
+            return (
+                404,
+                json!({ "error": "No timesheet; serve it with --csv." }),
+            );
+        };
+        let mut dates = Vec::new();
+        for (name, value) in query {
+            let name = name.as_str();
+            if !["week", "month", "from", "to"].contains(&name) {
+                continue;
+            }
+            match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+                Ok(date) => dates.push((name, date)),
+                Err(_) => return (400, json!({ "error": format!("Bad date '{}'.", value) })),
+            }
+        }
+        let date = |name| dates.iter().find(|(n, _)| *n == name).map(|&(_, d)| d);
+        let period = match (date("week"), date("month")) {
+            (Some(week), None) => report::Period::Week(week),
+            (None, Some(month)) => report::Period::Month(month),
+            (None, None) => report::Period::Range(date("from"), date("to")),
+            _ => return (400, json!({ "error": "Either week or month, not both." })),
+        };
+        let mut records = match timesheet::read(path) {
+            Ok(records) => records,
+            Err(e) => return (500, json!({ "error": e.to_string() })),
+        };
+        for record in &mut records {
+            record.work_time = record.reported_work(&self.rounding);
+        }
+        match report::days(&records, &period, |date| Ok((self.goal)(date))) {
+            Ok(days) => (200, json!(days)),
+            Err(e) => (500, json!({ "error": e.to_string() })),
+        }
+    }
+
+    fn respond(&self, method: &Method, url: &str) -> (u16, Value) {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let query: Vec<(String, String)> = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(name, value)| (decode(name), decode(value)))
+            .collect();
+        let tag = query
+            .iter()
+            .find(|(name, _)| name == "tag")
+            .map(|(_, t)| t.as_str());
+        match (method, path) {
+            (Method::Get, "/status") => answer(self.status()),
+            (Method::Post, "/start") => self.clock(events::Kind::Start, tag),
+            (Method::Post, "/stop") => self.clock(events::Kind::Stop, None),
+            (Method::Get, "/report") => self.report(&query),
+            (_, "/status" | "/start" | "/stop" | "/report") => {
+                (405, json!({ "error": "Method not allowed." }))
+            }
+            _ => (404, json!({ "error": "Not found." })),
+        }
+    }
+}
+
+/** 200 with the value, or 500 with the error, such as a malformed line of the clock file
+ */
+fn answer(result: Result<Value, ParseError>) -> (u16, Value) {
+    match result {
+        Ok(value) => (200, value),
+        Err(e) => (500, json!({ "error": e.to_string() })),
+    }
+}
+
+/** Undo the percent-encoding of a query name or value, with + for a space
+ */
+fn decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(if bytes[i] == b'+' { b' ' } else { bytes[i] });
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/** Answer requests on the address until the process is stopped
+ */
+pub fn run(api: Api, address: &str) {
+    let server = match Server::http(address) {
+        Ok(server) => server,
+        Err(e) => panic!("Cannot listen on {}: {}", address, e),
+    };
+    println!("{}", trf("Listening on http://{}", &[&address]));
+    let content_type = Header::from_bytes("Content-Type", "application/json").unwrap();
+    for request in server.incoming_requests() {
+        let (status, body) = api.respond(request.method(), request.url());
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        // A client gone before the answer is no reason to stop serving the others
+        let _ = request.respond(response);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use time_rust::Direction;
+
+    fn api(test: &str, timesheet: Option<&str>) -> Api {
+        let dir =
+            std::env::temp_dir().join(format!("time_rust-serve-{}-{}", std::process::id(), test));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let timesheet = timesheet.map(|content| {
+            let path = dir.join("log.csv");
+            fs::write(&path, content).unwrap();
+            path
+        });
+        Api::new(
+            dir.join("events"),
+            timesheet,
+            Box::new(|_| Duration::try_hours(8).unwrap()),
+            Rounding {
+                minutes: 0,
+                direction: Direction::Nearest,
+                stamps: false,
+            },
+        )
+    }
+
+    #[test]
+    fn routes_and_status_codes() {
+        let api = api("routes", None);
+        let status = |method, url| api.respond(&method, url).0;
+        assert_eq!(status(Method::Get, "/status"), 200);
+        assert_eq!(status(Method::Post, "/stop"), 409);
+        assert_eq!(status(Method::Post, "/start"), 200);
+        assert_eq!(status(Method::Post, "/start"), 409);
+        assert_eq!(status(Method::Post, "/stop"), 200);
+        assert_eq!(status(Method::Get, "/start"), 405);
+        assert_eq!(status(Method::Post, "/status"), 405);
+        assert_eq!(status(Method::Get, "/report"), 404);
+        assert_eq!(status(Method::Get, "/nowhere"), 404);
+    }
+
+    #[test]
+    fn report_dates_checked() {
+        let csv = "2024-01-15,08:00:00,16:30:00,00:30:00,8\n";
+        let api = api("report", Some(csv));
+        let status = |url| api.respond(&Method::Get, url).0;
+        assert_eq!(status("/report?week=2024-01-15"), 200);
+        assert_eq!(status("/report?from=2024-01-15&to=2024-01-19"), 200);
+        assert_eq!(status("/report?week=monday"), 400);
+        assert_eq!(status("/report?week=2024-01-15&month=2024-01-01"), 400);
+    }
+
+    #[test]
+    fn tags_percent_decoded() {
+        let api = api("tags", None);
+        assert_eq!(api.respond(&Method::Post, "/start?tag=caf%C3%A9").0, 200);
+        let events = fs::read_to_string(&api.clock_file).unwrap();
+        assert!(events.ends_with(" start café\n"));
+        assert_eq!(api.respond(&Method::Post, "/start?tag=client%20A").0, 400);
+        assert_eq!(decode("client+A%2fB%"), "client A/B%");
+    }
+
+    #[test]
+    fn malformed_files_are_server_errors() {
+        let api = api("malformed", Some("2024-01-15,08:00:00\n"));
+        fs::write(&api.clock_file, "8:00 begin\n").unwrap();
+        let (status, body) = api.respond(&Method::Get, "/status");
+        assert_eq!(status, 500);
+        assert!(body["error"].as_str().unwrap().contains("'8:00 begin'"));
+        assert_eq!(api.respond(&Method::Post, "/start").0, 500);
+        let (status, body) = api.respond(&Method::Get, "/report");
+        assert_eq!(status, 500);
+        assert!(body["error"]
+            .as_str()
+            .unwrap()
+            .contains("'2024-01-15,08:00:00'"));
+    }
+}
@@ -0,0 +1,136 @@
+/*! Persistent flextime account in JSON:
+ *
+ *     {"days":{"2024-01-15":3600,"2024-01-16":-900}}
+ *
+ * holding the over- or undertime in seconds booked for each day.  Booking a day again replaces
+ * its previous value, so running the tool several times a day doesn't count twice.  Until the first
+ * reset the account opens with the configured starting balance; a reset drops the booked days and
+ * starts over from a carried balance instead:
+ *
+ *     {"days":{},"carry_seconds":0,"reset_at":"2024-04-01T08:00:00+02:00"}
+ */
+use chrono::{DateTime, Duration, Local, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct State {
+    #[serde(default)]
+    pub days: BTreeMap<NaiveDate, i64>,
+    /// Balance in seconds carried over from the last reset
+    #[serde(default)]
+    pub carry_seconds: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reset_at: Option<DateTime<Local>>,
+}
+
+impl State {
+    /** Load the state; a missing file is an empty account
+     */
+    pub fn load(path: &Path) -> State {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(_) => return State::default(),
+        };
+        match serde_json::from_str(&content) {
+            Ok(state) => state,
+            Err(e) => panic!("Invalid state {}: {}", path.display(), e),
+        }
+    }
+
+    pub fn save(&self, path: &Path) {
+        if let Err(e) = fs::write(path, serde_json::to_string_pretty(self).unwrap()) {
+            panic!("Cannot write state {}: {}", path.display(), e);
+        }
+    }
+
+    pub fn book(&mut self, date: NaiveDate, delta: Duration) {
+        self.days.insert(date, delta.num_seconds());
+    }
+
+    pub fn balance(&self) -> Duration {
+        Duration::try_seconds(self.carry_seconds + self.days.values().sum::<i64>()).unwrap()
+    }
+
+    /** Balance of an account that opened with `starting`; a reset carries its own balance
+     */
+    pub fn balance_from(&self, starting: Duration) -> Duration {
+        match self.reset_at {
+            Some(_) => self.balance(),
+            None => starting + self.balance(),
+        }
+    }
+
+    /** Drop the booked days and start over from the given balance
+     */
+    pub fn reset(&mut self, balance: Duration, at: DateTime<Local>) {
+        self.days.clear();
+        self.carry_seconds = balance.num_seconds();
+        self.reset_at = Some(at);
+    }
+
+    /** Balance of the week containing the given date, from Monday up to that date
+     */
+    pub fn week_balance(&self, date: NaiveDate) -> Duration {
+        let monday = date.week(Weekday::Mon).first_day();
+        Duration::try_seconds(self.days.range(monday..=date).map(|(_, s)| s).sum()).unwrap()
+    }
+
+    /** Balance of the week containing the given date, from Monday up to the day before
+     */
+    pub fn week_balance_before(&self, date: NaiveDate) -> Duration {
+        let monday = date.week(Weekday::Mon).first_day();
+        Duration::try_seconds(self.days.range(monday..date).map(|(_, s)| s).sum()).unwrap()
+    }
+}
+
+/** Overtime credited at the given factor, undertime counts 1:1
+ */
+pub fn weighted(delta: Duration, overtime_factor: f64) -> Duration {
+    if delta > Duration::zero() {
+        Duration::try_milliseconds(
+            (delta.num_milliseconds() as f64 * overtime_factor).round() as i64
+        )
+        .unwrap()
+    } else {
+        delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overtime_weighted_undertime_not() {
+        let hour = Duration::try_hours(1).unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 6, 3).unwrap();
+        let mut state = State::default();
+        state.book(date, weighted(hour, 1.25));
+        assert_eq!(state.balance(), Duration::try_minutes(75).unwrap());
+        assert_eq!(weighted(-hour, 1.25), -hour);
+    }
+
+    #[test]
+    fn reset_to_zero_and_to_a_custom_balance() {
+        let hour = Duration::try_hours(1).unwrap();
+        let mut state = State::default();
+        state.book(NaiveDate::from_ymd_opt(2024, 6, 3).unwrap(), hour);
+        state.book(NaiveDate::from_ymd_opt(2024, 6, 4).unwrap(), hour / 2);
+        assert_eq!(state.balance_from(hour), hour * 5 / 2);
+
+        let at = Local::now();
+        state.reset(Duration::zero(), at);
+        assert!(state.days.is_empty());
+        assert_eq!(state.reset_at, Some(at));
+        // The starting balance is left behind with the reset
+        assert_eq!(state.balance_from(hour), Duration::zero());
+
+        state.reset(-hour * 3, at);
+        assert_eq!(state.balance_from(hour), -hour * 3);
+        state.book(NaiveDate::from_ymd_opt(2024, 6, 5).unwrap(), hour);
+        assert_eq!(state.balance(), -hour * 2);
+    }
+}
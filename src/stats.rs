@@ -0,0 +1,298 @@
+/*! Statistics over the logged days of a date range: when the days start and end on average, how
+ * long they are, the longest run of days meeting their goal, how long the breaks last, and the work
+ * of each ISO week against its goals with the change from the week before, to spot overtime
+ * creeping in
+ */
+use crate::error::ParseError;
+use crate::i18n::{tr, trf};
+use crate::timesheet::{self, Record};
+use crate::week_grid::bar;
+use crate::{fit, format_duration, format_duration_signed};
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Timelike};
+
+/** Upper bounds in minutes of the break length classes, the last class open
+ */
+const BREAK_CLASSES: [i64; 4] = [15, 30, 45, 60];
+
+/** Characters of a sparkline, lowest to highest
+ */
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/** Work of an ISO week against the goals of its logged days
+ */
+#[derive(Debug, PartialEq)]
+pub struct Week {
+    pub year: i32,
+    pub week: u32,
+    pub work: Duration,
+    pub goal: Duration,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Stats {
+    pub days: usize,
+    pub average_start: Option<NaiveTime>,
+    pub average_end: Option<NaiveTime>,
+    pub average_work: Duration,
+    /// Most logged days in a row with the goal met
+    pub longest_streak: usize,
+    /// Number of breaks per length class of BREAK_CLASSES
+    pub break_lengths: [usize; BREAK_CLASSES.len() + 1],
+    pub weeks: Vec<Week>,
+}
+
+/** Mean of times of day
+ */
+fn average_time(times: impl Iterator<Item = NaiveTime>) -> Option<NaiveTime> {
+    let seconds: Vec<u32> = times.map(|t| t.num_seconds_from_midnight()).collect();
+    if seconds.is_empty() {
+        return None;
+    }
+    let mean = seconds.iter().map(|&s| s as u64).sum::<u64>() / seconds.len() as u64;
+    NaiveTime::from_num_seconds_from_midnight_opt(mean as u32, 0)
+}
+
+/** Statistics of the days of the range that have a record, the last record of a day counting,
+ * each against the goal of its date
+ */
+pub fn compute(
+    records: &[Record],
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    goal: impl Fn(NaiveDate) -> Result<Duration, ParseError>,
+) -> Result<Stats, ParseError> {
+    let mut dates: Vec<NaiveDate> = timesheet::between(records, from, to)
+        .iter()
+        .map(|r| r.date)
+        .collect();
+    dates.dedup();
+    let days: Vec<&Record> = dates
+        .iter()
+        .filter_map(|&date| timesheet::on(records, date))
+        .collect();
+
+    let (mut streak, mut longest_streak) = (0, 0);
+    let mut break_lengths = [0; BREAK_CLASSES.len() + 1];
+    let mut weeks: Vec<Week> = Vec::new();
+    for record in &days {
+        let goal = goal(record.date)?;
+        if record.work_time >= goal {
+            streak += 1;
+            longest_streak = longest_streak.max(streak);
+        } else {
+            streak = 0;
+        }
+
+        // A day logged with a break total only has one break of that length
+        let lengths: Vec<Duration> = if record.breaks.is_empty() {
+            Some(record.break_time)
+                .filter(|b| *b > Duration::zero())
+                .into_iter()
+                .collect()
+        } else {
+            record.breaks.iter().map(|(from, to)| *to - *from).collect()
+        };
+        for length in lengths {
+            let class = BREAK_CLASSES
+                .iter()
+                .position(|&minutes| length.num_minutes() < minutes)
+                .unwrap_or(BREAK_CLASSES.len());
+            break_lengths[class] += 1;
+        }
+
+        let iso = record.date.iso_week();
+        match weeks.last_mut() {
+            Some(week) if (week.year, week.week) == (iso.year(), iso.week()) => {
+                week.work += record.work_time;
+                week.goal += goal;
+            }
+            _ => weeks.push(Week {
+                year: iso.year(),
+                week: iso.week(),
+                work: record.work_time,
+                goal,
+            }),
+        }
+    }
+
+    Ok(Stats {
+        days: days.len(),
+        average_start: average_time(days.iter().map(|r| r.start)),
+        average_end: average_time(days.iter().map(|r| r.end)),
+        average_work: if days.is_empty() {
+            Duration::zero()
+        } else {
+            days.iter().map(|r| r.work_time).sum::<Duration>() / days.len() as i32
+        },
+        longest_streak,
+        break_lengths,
+        weeks,
+    })
+}
+
+/** One character per value, scaled from the smallest to the largest
+ */
+pub fn sparkline(values: &[i64]) -> String {
+    let (Some(&min), Some(&max)) = (values.iter().min(), values.iter().max()) else {
+        return String::new();
+    };
+    values
+        .iter()
+        .map(|&value| {
+            let level = match max - min {
+                0 => SPARKS.len() / 2,
+                span => ((value - min) * (SPARKS.len() as i64 - 1) / span) as usize,
+            };
+            SPARKS[level]
+        })
+        .collect()
+}
+
+/** Render the statistics no wider than `width`, with a bar per break class and week if `chart`
+ */
+pub fn render(stats: &Stats, chart: bool, width: usize) -> String {
+    let time = |t: Option<NaiveTime>| t.map(|t| t.format("%H:%M").to_string()).unwrap_or_default();
+    let mut lines = vec![
+        format!("{:<14}{}", tr("days"), stats.days),
+        format!("{:<14}{}", tr("avg start"), time(stats.average_start)),
+        format!("{:<14}{}", tr("avg end"), time(stats.average_end)),
+        format!(
+            "{:<14}{}",
+            tr("avg work"),
+            format_duration(&stats.average_work)
+        ),
+        format!(
+            "{:<14}{}",
+            tr("goal streak"),
+            trf("{} day(s)", &[&stats.longest_streak])
+        ),
+        tr("breaks").to_owned(),
+    ];
+    let most = stats.break_lengths.iter().copied().max().unwrap_or(0);
+    let cells = width.saturating_sub(24).clamp(1, 20);
+    for (class, &count) in stats.break_lengths.iter().enumerate() {
+        let label = match (
+            class.checked_sub(1).map(|c| BREAK_CLASSES[c]),
+            BREAK_CLASSES.get(class),
+        ) {
+            (None, Some(to)) => format!("< {}m", to),
+            (Some(from), Some(to)) => format!("{}-{}m", from, to),
+            (Some(from), None) => format!(">= {}m", from),
+            (None, None) => unreachable!(),
+        };
+        let mut line = format!("  {:<12}{:<6}", label, count);
+        if chart {
+            let hours = |n: usize| Duration::try_hours(n as i64).unwrap();
+            line.push_str(&format!("[{}]", bar(hours(count), hours(most), cells)));
+        }
+        lines.push(line);
+    }
+
+    lines.push(format!(
+        "{:<14}{:<10}{:<11}{}",
+        tr("week"),
+        tr("work"),
+        tr("delta"),
+        tr("change")
+    ));
+    let longest = stats.weeks.iter().map(|w| w.work).max().unwrap_or_default();
+    let cells = width.saturating_sub(49).clamp(1, 20);
+    for (i, week) in stats.weeks.iter().enumerate() {
+        let change = match i.checked_sub(1).map(|p| &stats.weeks[p]) {
+            Some(previous) => format_duration_signed(&(week.work - previous.work)),
+            None => String::new(),
+        };
+        let mut line = format!(
+            "{:<14}{:<10}{:<11}{:<11}",
+            format!("{}-W{:02}", week.year, week.week),
+            format_duration(&week.work),
+            format_duration_signed(&(week.work - week.goal)),
+            change
+        );
+        if chart {
+            line.push_str(&format!(" [{}]", bar(week.work, longest, cells)));
+        }
+        lines.push(line);
+    }
+    let overtime: Vec<i64> = stats
+        .weeks
+        .iter()
+        .map(|w| (w.work - w.goal).num_seconds())
+        .collect();
+    lines.push(format!("{:<14}{}", tr("overtime"), sparkline(&overtime)));
+
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&fit(&line, width));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn record(date: &str, start: &str, end: &str, hours: i64, breaks: &[(&str, &str)]) -> Record {
+        let time = |t: &str| NaiveTime::parse_from_str(t, "%H:%M").unwrap();
+        let breaks: Vec<(NaiveTime, NaiveTime)> = breaks
+            .iter()
+            .map(|&(from, to)| (time(from), time(to)))
+            .collect();
+        Record {
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            start: time(start),
+            end: time(end),
+            break_time: breaks.iter().map(|(from, to)| *to - *from).sum(),
+            work_time: Duration::try_hours(hours).unwrap(),
+            breaks,
+            goal_met: false,
+            provisional: false,
+            tags: BTreeMap::new(),
+            zone: None,
+        }
+    }
+
+    #[test]
+    fn averages_streak_breaks_and_weeks() {
+        let records = [
+            record("2024-01-11", "08:00", "16:30", 8, &[("12:00", "12:30")]),
+            record(
+                "2024-01-12",
+                "09:00",
+                "17:30",
+                8,
+                &[("12:00", "12:10"), ("15:00", "15:20")],
+            ),
+            record("2024-01-15", "07:00", "14:30", 7, &[("12:00", "12:30")]),
+            record("2024-01-16", "08:00", "17:30", 9, &[("12:00", "13:00")]),
+            record("2024-01-17", "08:00", "16:30", 8, &[("12:00", "12:45")]),
+        ];
+        let stats = compute(
+            &records,
+            None,
+            None,
+            |_| Ok(Duration::try_hours(8).unwrap()),
+        )
+        .unwrap();
+        assert_eq!(stats.days, 5);
+        assert_eq!(stats.average_start, NaiveTime::from_hms_opt(8, 0, 0));
+        assert_eq!(stats.average_end, NaiveTime::from_hms_opt(16, 30, 0));
+        assert_eq!(stats.average_work, Duration::try_minutes(480).unwrap());
+        assert_eq!(stats.longest_streak, 2);
+        assert_eq!(stats.break_lengths, [1, 1, 2, 1, 1]);
+        assert_eq!(stats.weeks.len(), 2);
+        assert_eq!(stats.weeks[1].work, Duration::try_hours(24).unwrap());
+
+        let out = render(&stats, false, 80);
+        assert!(out.contains("2024-W03      24:00:00  +00:00:00  +08:00:00"));
+        assert!(out.contains("overtime      ▅▅"));
+    }
+
+    #[test]
+    fn sparkline_scales_to_range() {
+        assert_eq!(sparkline(&[0, 7, 14]), "▁▄█");
+        assert_eq!(sparkline(&[]), "");
+    }
+}
@@ -0,0 +1,657 @@
+/*! Timesheet with one record per working day, stored either as CSV
+ *
+ *     date,start,end,break_time,work_time,breaks,goal_met,provisional,tags,zone
+ *     2024-01-15,08:00:00,16:30:00,00:30:00,8,12:00:00-12:30:00,true,false,clientA=05:00:00,
+ *
+ * with the work time in decimal hours, the single breaks separated by spaces, a record logged
+ * before the end of the day marked provisional, the work time per tag separated by spaces, and the
+ * time zone of the times if one was given, or as JSON lines
+ *
+ *     {"date":"2024-01-15","start":"08:00:00","end":"16:30:00","break_time_seconds":1800,...}
+ *
+ * The reader accepts both, even mixed within one file.  The columns from breaks on are
+ * optional, further CSV columns are ignored.
+ */
+use crate::break_policy::BreakPolicy;
+use crate::error::ParseError;
+use crate::i18n::{tr, trf};
+use crate::{
+    create_duration, format_duration, format_duration_hours, seconds, seconds_by_name, RoundMode,
+    Rounding,
+};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+const CSV_HEADER: &str =
+    "date,start,end,break_time,work_time,breaks,goal_met,provisional,tags,zone";
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    Csv,
+    Jsonl,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Format {
+        match name {
+            "csv" => Format::Csv,
+            "jsonl" => Format::Jsonl,
+            _ => panic!("Invalid timesheet format '{}'.  Stop!", name),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub date: NaiveDate,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    #[serde(rename = "break_time_seconds", with = "seconds")]
+    pub break_time: Duration,
+    #[serde(rename = "work_time_seconds", with = "seconds")]
+    pub work_time: Duration,
+    /// Single breaks as start and end; empty for older records
+    #[serde(default)]
+    pub breaks: Vec<(NaiveTime, NaiveTime)>,
+    #[serde(default)]
+    pub goal_met: bool,
+    /// Logged without an end time, with the time of the run as the end
+    #[serde(default)]
+    pub provisional: bool,
+    /// Work time per tag, such as a project; untagged work is left out
+    #[serde(default, rename = "tag_seconds", with = "seconds_by_name")]
+    pub tags: BTreeMap<String, Duration>,
+    /// Time zone of the start and end, e.g. Europe/Berlin; the system's if none
+    #[serde(default)]
+    pub zone: Option<String>,
+}
+
+impl Record {
+    /** End of the shift, on the following day if it ended before it started
+     */
+    pub fn end_datetime(&self) -> NaiveDateTime {
+        let end = self.date.and_time(self.end);
+        if self.end < self.start {
+            end + Duration::try_days(1).unwrap()
+        } else {
+            end
+        }
+    }
+
+    /** Work time as reported under a rounding policy
+     */
+    pub fn reported_work(&self, rounding: &Rounding) -> Duration {
+        rounding.work(
+            self.date.and_time(self.start),
+            self.end_datetime(),
+            self.work_time,
+        )
+    }
+
+    fn to_csv(&self) -> String {
+        let breaks: Vec<String> = self
+            .breaks
+            .iter()
+            .map(|(from, to)| format!("{}-{}", from.format("%H:%M:%S"), to.format("%H:%M:%S")))
+            .collect();
+        let tags: Vec<String> = self
+            .tags
+            .iter()
+            .map(|(tag, work)| format!("{}={}", tag, format_duration(work)))
+            .collect();
+        format!(
+            "{},{},{},{},{},{},{},{},{},{}",
+            self.date,
+            self.start.format("%H:%M:%S"),
+            self.end.format("%H:%M:%S"),
+            format_duration(&self.break_time),
+            format_duration_hours(&self.work_time, RoundMode::HalfAway),
+            breaks.join(" "),
+            self.goal_met,
+            self.provisional,
+            tags.join(" "),
+            self.zone.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+fn parse_csv(line: &str) -> Result<Record, ParseError> {
+    let malformed = |reason: String| ParseError::MalformedRecord {
+        line: line.to_owned(),
+        reason,
+    };
+    let columns: Vec<&str> = line.split(',').map(|c| c.trim()).collect();
+    if columns.len() < 5 {
+        return Err(malformed(tr("fewer than five columns").to_owned()));
+    }
+    let hours: f64 = columns[4]
+        .parse()
+        .map_err(|_| malformed(trf("invalid work time '{}'", &[&columns[4]])))?;
+    let time = |t: &str| {
+        NaiveTime::parse_from_str(t, "%H:%M:%S")
+            .map_err(|_| malformed(trf("invalid time '{}'", &[&t])))
+    };
+    let breaks = match columns.get(5) {
+        Some(breaks) => breaks
+            .split_whitespace()
+            .map(|b| match b.split_once('-') {
+                Some((from, to)) => Ok((time(from)?, time(to)?)),
+                None => Err(malformed(trf("invalid break '{}'", &[&b]))),
+            })
+            .collect::<Result<_, _>>()?,
+        None => Vec::new(),
+    };
+    let flag = |i: usize| match columns.get(i) {
+        None | Some(&"") | Some(&"false") => Ok(false),
+        Some(&"true") => Ok(true),
+        Some(other) => Err(malformed(trf("invalid flag '{}'", &[other]))),
+    };
+    let tags = match columns.get(8) {
+        Some(tags) => tags
+            .split_whitespace()
+            .map(|t| {
+                match t
+                    .split_once('=')
+                    .map(|(tag, work)| (tag, create_duration(work)))
+                {
+                    Some((tag, Ok(work))) => Ok((tag.to_owned(), work)),
+                    _ => Err(malformed(trf("invalid tag '{}'", &[&t]))),
+                }
+            })
+            .collect::<Result<_, _>>()?,
+        None => BTreeMap::new(),
+    };
+    Ok(Record {
+        date: NaiveDate::parse_from_str(columns[0], "%Y-%m-%d")
+            .map_err(|_| malformed(trf("invalid date '{}'", &[&columns[0]])))?,
+        start: time(columns[1])?,
+        end: time(columns[2])?,
+        break_time: create_duration(columns[3]).map_err(|e| malformed(e.to_string()))?,
+        work_time: Duration::try_seconds((hours * 3600.).round() as i64)
+            .ok_or_else(|| malformed(trf("invalid work time '{}'", &[&columns[4]])))?,
+        breaks,
+        goal_met: flag(6)?,
+        provisional: flag(7)?,
+        tags,
+        zone: columns
+            .get(9)
+            .filter(|zone| !zone.is_empty())
+            .map(|zone| zone.to_string()),
+    })
+}
+
+fn parse_record(line: &str) -> Result<Record, ParseError> {
+    if line.trim_start().starts_with('{') {
+        serde_json::from_str(line).map_err(|e| ParseError::MalformedRecord {
+            line: line.to_owned(),
+            reason: e.to_string(),
+        })
+    } else {
+        parse_csv(line)
+    }
+}
+
+/** Read all records from the timesheet, sorted by date; a missing file has no records
+ */
+pub fn read(path: &Path) -> Result<Vec<Record>, ParseError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return Ok(Vec::new()),
+    };
+    let mut records: Vec<Record> = content
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with("date"))
+        .map(parse_record)
+        .collect::<Result<_, _>>()?;
+    records.sort_by_key(|r| r.date);
+    Ok(records)
+}
+
+/** Append a record to the timesheet, starting a new CSV file with the header row
+ */
+pub fn append(path: &Path, record: &Record, format: Format) {
+    let is_new = fs::metadata(path).map(|m| m.len() == 0).unwrap_or(true);
+    let mut file = match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(file) => file,
+        Err(e) => panic!("Cannot open timesheet {}: {}", path.display(), e),
+    };
+    let line = match format {
+        Format::Csv if is_new => format!("{}\n{}", CSV_HEADER, record.to_csv()),
+        Format::Csv => record.to_csv(),
+        Format::Jsonl => serde_json::to_string(record).unwrap(),
+    };
+    if let Err(e) = writeln!(file, "{}", line) {
+        panic!("Cannot write timesheet {}: {}", path.display(), e);
+    }
+}
+
+/** Replace the records of the record's date by the record
+ */
+pub fn replace(path: &Path, record: &Record, format: Format) -> Result<(), ParseError> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    let mut kept = Vec::new();
+    for line in content.lines() {
+        if line.trim().is_empty()
+            || line.starts_with("date")
+            || parse_record(line)?.date != record.date
+        {
+            kept.push(line);
+        }
+    }
+    let mut rewritten = kept.join("\n");
+    if !rewritten.is_empty() {
+        rewritten.push('\n');
+    }
+    if let Err(e) = fs::write(path, rewritten) {
+        panic!("Cannot write timesheet {}: {}", path.display(), e);
+    }
+    append(path, record, format);
+    Ok(())
+}
+
+/** Remove the records of the given date, keeping the original as <path>.bak; returns the number
+ * of removed records
+ */
+pub fn delete(path: &Path, date: NaiveDate) -> Result<usize, ParseError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => panic!("Cannot read timesheet {}: {}", path.display(), e),
+    };
+    let is_record = |line: &str| !line.trim().is_empty() && !line.starts_with("date");
+    let mut kept = Vec::new();
+    for line in content.lines() {
+        if !is_record(line) || parse_record(line)?.date != date {
+            kept.push(line);
+        }
+    }
+    let removed = content.lines().count() - kept.len();
+
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    if let Err(e) = fs::copy(path, &backup) {
+        panic!("Cannot back up timesheet {}: {}", path.display(), e);
+    }
+    let mut rewritten = kept.join("\n");
+    if !rewritten.is_empty() {
+        rewritten.push('\n');
+    }
+    if let Err(e) = fs::write(path, rewritten) {
+        panic!("Cannot write timesheet {}: {}", path.display(), e);
+    }
+    Ok(removed)
+}
+
+/** Format of the records already in the timesheet, CSV for an empty or missing one
+ */
+pub fn format_of(path: &Path) -> Format {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    match content.lines().find(|line| !line.trim().is_empty()) {
+        Some(line) if line.trim_start().starts_with('{') => Format::Jsonl,
+        _ => Format::Csv,
+    }
+}
+
+/** Changes to a stored record
+ */
+#[derive(Default)]
+pub struct Amendment {
+    pub start: Option<NaiveTime>,
+    pub end: Option<NaiveTime>,
+    pub add_breaks: Vec<(NaiveTime, NaiveTime)>,
+    pub remove_breaks: Vec<(NaiveTime, NaiveTime)>,
+}
+
+/** The record with the amendment applied and its break and work time recalculated against the
+ * goal of its date; a record without single breaks gets the default break of the policy for its
+ * new duration, and one given an end is no longer provisional
+ */
+pub fn amend(
+    record: &Record,
+    amendment: &Amendment,
+    goal: Duration,
+    policy: &dyn BreakPolicy,
+) -> Result<Record, ParseError> {
+    let bad = |reason| ParseError::BadEdit { reason };
+    let mut amended = record.clone();
+    amended.start = amendment.start.unwrap_or(record.start);
+    amended.end = amendment.end.unwrap_or(record.end);
+    if amendment.end.is_some() {
+        amended.provisional = false;
+    }
+    let show = |(from, to): &(NaiveTime, NaiveTime)| {
+        format!("{}-{}", from.format("%H:%M:%S"), to.format("%H:%M:%S"))
+    };
+    for removed in &amendment.remove_breaks {
+        match amended.breaks.iter().position(|b| b == removed) {
+            Some(i) => {
+                amended.breaks.remove(i);
+            }
+            None => {
+                return Err(bad(trf(
+                    "no break {} on {}",
+                    &[&show(removed), &record.date],
+                )))
+            }
+        }
+    }
+    amended.breaks.extend(amendment.add_breaks.iter().copied());
+
+    let start = amended.date.and_time(amended.start);
+    let end = amended.end_datetime();
+    if end == start {
+        return Err(bad(trf(
+            "start and end of {} are the same",
+            &[&record.date],
+        )));
+    }
+    // Breaks after midnight belong to an overnight shift
+    let at = |time: NaiveTime| {
+        let at = amended.date.and_time(time);
+        if at < start {
+            at + Duration::try_days(1).unwrap()
+        } else {
+            at
+        }
+    };
+    let mut intervals: Vec<_> = amended
+        .breaks
+        .iter()
+        .map(|&(from, to)| (at(from), at(to)))
+        .collect();
+    intervals.sort();
+    for (i, &(from, to)) in intervals.iter().enumerate() {
+        if to <= from || to > end {
+            return Err(bad(trf(
+                "break {}-{} lies outside the working period",
+                &[&from.format("%H:%M:%S"), &to.format("%H:%M:%S")],
+            )));
+        }
+        if intervals.get(i + 1).is_some_and(|next| next.0 < to) {
+            return Err(bad(trf(
+                "break {}-{} overlaps the one after it",
+                &[&from.format("%H:%M:%S"), &to.format("%H:%M:%S")],
+            )));
+        }
+    }
+    amended.breaks.sort_by_key(|&(from, _)| at(from));
+    amended.break_time = if amended.breaks.is_empty() && record.breaks.is_empty() {
+        policy.required(end - start)
+    } else {
+        intervals.iter().map(|(from, to)| *to - *from).sum()
+    };
+    amended.work_time = end - start - amended.break_time;
+    amended.goal_met = amended.work_time > goal;
+    Ok(amended)
+}
+
+/** Aggregate over a range of records
+ */
+#[derive(Serialize)]
+pub struct Totals {
+    pub count: usize,
+    #[serde(rename = "total_work_seconds", with = "seconds")]
+    pub total_work: Duration,
+    #[serde(rename = "total_break_seconds", with = "seconds")]
+    pub total_break: Duration,
+    #[serde(rename = "average_work_seconds", with = "seconds")]
+    pub average_work: Duration,
+    #[serde(rename = "min_work_seconds", with = "seconds")]
+    pub min_work: Duration,
+    #[serde(rename = "max_work_seconds", with = "seconds")]
+    pub max_work: Duration,
+}
+
+/** Sum up the records; everything is zero without records
+ */
+pub fn totals(records: &[Record]) -> Totals {
+    let total_work = records.iter().map(|r| r.work_time).sum::<Duration>();
+    Totals {
+        count: records.len(),
+        total_work,
+        total_break: records.iter().map(|r| r.break_time).sum(),
+        average_work: if records.is_empty() {
+            Duration::zero()
+        } else {
+            total_work / records.len() as i32
+        },
+        min_work: records
+            .iter()
+            .map(|r| r.work_time)
+            .min()
+            .unwrap_or_default(),
+        max_work: records
+            .iter()
+            .map(|r| r.work_time)
+            .max()
+            .unwrap_or_default(),
+    }
+}
+
+/** Records within the given dates, both inclusive and optional
+ */
+pub fn between(records: &[Record], from: Option<NaiveDate>, to: Option<NaiveDate>) -> Vec<&Record> {
+    records
+        .iter()
+        .filter(|r| from.is_none_or(|from| r.date >= from) && to.is_none_or(|to| r.date <= to))
+        .collect()
+}
+
+/** The last `count` records of days before the given date
+ */
+pub fn recent(records: &[Record], date: NaiveDate, count: usize) -> Vec<&Record> {
+    let before: Vec<&Record> = records.iter().filter(|r| r.date < date).collect();
+    before[before.len().saturating_sub(count)..].to_vec()
+}
+
+/** Median work time of the records, the mean of the middle two for an even count
+ */
+pub fn median_work(records: &[&Record]) -> Option<Duration> {
+    let mut work: Vec<Duration> = records.iter().map(|r| r.work_time).collect();
+    work.sort();
+    match work.len() {
+        0 => None,
+        n if n % 2 == 1 => Some(work[n / 2]),
+        n => Some((work[n / 2 - 1] + work[n / 2]) / 2),
+    }
+}
+
+/** Work time in the ISO week, Monday to Sunday, of the given date; of several records of a day
+ * only the last one counts, as each run of that day logs it anew
+ */
+pub fn week_work(records: &[Record], date: NaiveDate) -> Duration {
+    let week = date.iso_week();
+    let mut days: Vec<NaiveDate> = records
+        .iter()
+        .map(|r| r.date)
+        .filter(|d| d.iso_week() == week)
+        .collect();
+    days.dedup();
+    days.iter()
+        .filter_map(|&day| on(records, day))
+        .map(|r| r.work_time)
+        .sum()
+}
+
+/** The record of the given date
+ */
+pub fn on(records: &[Record], date: NaiveDate) -> Option<&Record> {
+    records.iter().rev().find(|r| r.date == date)
+}
+
+/** The most recent record of a day before the given date
+ */
+pub fn previous(records: &[Record], date: NaiveDate) -> Option<&Record> {
+    records.iter().rev().find(|r| r.date < date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::break_policy::Standard;
+
+    #[test]
+    fn csv_round_trip() {
+        let line = "2024-01-15,08:00:00,16:30:00,00:30:00,8,12:00:00-12:30:00,true,false,\
+                    clientA=05:00:00 meeting=03:00:00,Europe/Berlin";
+        let record = parse_csv(line).unwrap();
+        assert!(record.goal_met);
+        assert!(!record.provisional);
+        assert_eq!(record.tags["meeting"], Duration::try_hours(3).unwrap());
+        assert_eq!(record.zone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(record.to_csv(), line);
+    }
+
+    #[test]
+    fn amend_end_and_breaks() {
+        let record =
+            parse_csv("2024-06-03,08:00:00,16:30:00,00:30:00,8,12:00:00-12:30:00").unwrap();
+        let time = |t: &str| NaiveTime::parse_from_str(t, "%H:%M").unwrap();
+        let amended = amend(
+            &record,
+            &Amendment {
+                end: Some(time("17:15")),
+                add_breaks: vec![(time("15:00"), time("15:15"))],
+                remove_breaks: vec![(time("12:00"), time("12:30"))],
+                ..Amendment::default()
+            },
+            Duration::try_hours(8).unwrap(),
+            &Standard::default(),
+        )
+        .unwrap();
+        assert_eq!(amended.breaks, [(time("15:00"), time("15:15"))]);
+        assert_eq!(amended.break_time, Duration::try_minutes(15).unwrap());
+        assert_eq!(amended.work_time, Duration::try_minutes(9 * 60).unwrap());
+
+        let overlapping = Amendment {
+            add_breaks: vec![(time("12:15"), time("12:45"))],
+            ..Amendment::default()
+        };
+        let goal = Duration::try_hours(8).unwrap();
+        assert!(amend(&record, &overlapping, goal, &Standard::default()).is_err());
+        let outside = Amendment {
+            end: Some(time("12:15")),
+            ..Amendment::default()
+        };
+        assert!(amend(&record, &outside, goal, &Standard::default()).is_err());
+    }
+
+    #[test]
+    fn amend_rederives_default_break_goal_and_provisional() {
+        let record = parse_csv("2024-06-03,08:00:00,12:00:00,00:30:00,3.5,,false,true").unwrap();
+        let time = |t: &str| NaiveTime::parse_from_str(t, "%H:%M").unwrap();
+        let end = |end: &str| Amendment {
+            end: Some(time(end)),
+            ..Amendment::default()
+        };
+        let goal = Duration::try_hours(8).unwrap();
+        let amended = amend(&record, &end("18:00"), goal, &Standard::default()).unwrap();
+        assert_eq!(amended.break_time, Duration::try_minutes(45).unwrap());
+        assert_eq!(
+            amended.work_time,
+            Duration::try_minutes(9 * 60 + 15).unwrap()
+        );
+        assert!(amended.goal_met);
+        assert!(!amended.provisional);
+
+        let amended = amend(&record, &end("16:00"), goal, &Standard::default()).unwrap();
+        assert_eq!(amended.break_time, Duration::try_minutes(30).unwrap());
+        assert!(!amended.goal_met);
+
+        let start = Amendment {
+            start: Some(time("9:00")),
+            ..Amendment::default()
+        };
+        let amended = amend(&record, &start, goal, &Standard::default()).unwrap();
+        assert!(amended.provisional);
+    }
+
+    #[test]
+    fn week_work_from_monday() {
+        let records: Vec<Record> = [
+            "2024-01-14,08:00:00,16:00:00,00:00:00,8",
+            "2024-01-15,08:00:00,12:00:00,00:00:00,4,,false,true",
+            "2024-01-15,08:00:00,16:00:00,00:00:00,8",
+            "2024-01-17,08:00:00,15:00:00,00:00:00,7",
+            "2024-01-22,08:00:00,16:00:00,00:00:00,8",
+        ]
+        .iter()
+        .map(|line| parse_csv(line).unwrap())
+        .collect();
+        let wednesday = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap();
+        assert_eq!(
+            week_work(&records, wednesday),
+            Duration::try_hours(15).unwrap()
+        );
+        assert_eq!(week_work(&[], wednesday), Duration::zero());
+    }
+
+    #[test]
+    fn csv_without_flags() {
+        let record = parse_csv("2024-01-15,08:00:00,12:00:00,00:00:00,4").unwrap();
+        assert!(record.breaks.is_empty());
+        assert!(!record.goal_met);
+        assert!(!record.provisional);
+        assert!(record.tags.is_empty());
+        assert_eq!(record.work_time, Duration::try_hours(4).unwrap());
+    }
+
+    #[test]
+    fn record_round_trips_through_csv_and_jsonl() {
+        let line = "2024-01-15,08:00:00,16:30:00,00:30:00,8,12:00:00-12:30:00,true,false,\
+                    clientA=08:00:00,Europe/Berlin";
+        let record = parse_csv(line).unwrap();
+        let dir = std::env::temp_dir().join(format!("time_rust-timesheet-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (name, format) in [("log.csv", Format::Csv), ("log.jsonl", Format::Jsonl)] {
+            let path = dir.join(name);
+            let _ = fs::remove_file(&path);
+            append(&path, &record, format);
+            let read = read(&path).unwrap();
+            assert_eq!(read.len(), 1);
+            assert_eq!(read[0].to_csv(), line);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn delete_one_of_three_keeps_a_backup() {
+        let content = "date,start,end,break_time,work_time,breaks,goal_met,provisional,tags,zone\n\
+                       2024-01-15,08:00:00,16:30:00,00:30:00,8,,true,false,,\n\
+                       2024-01-16,08:00:00,15:45:00,00:45:00,7,,false,false,,\n\
+                       2024-01-17,07:30:00,17:00:00,00:30:00,9,,true,false,,\n";
+        let dir = std::env::temp_dir().join(format!("time_rust-delete-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("log.csv");
+        fs::write(&path, content).unwrap();
+        let date = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        assert_eq!(delete(&path, date("2024-01-16")).unwrap(), 1);
+        let dates: Vec<NaiveDate> = read(&path).unwrap().iter().map(|r| r.date).collect();
+        assert_eq!(dates, [date("2024-01-15"), date("2024-01-17")]);
+        assert_eq!(
+            fs::read_to_string(dir.join("log.csv.bak")).unwrap(),
+            content
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn malformed_csv_is_an_error() {
+        for line in [
+            "2024-01-15,08:00:00,16:30:00",
+            "2024-01-15,08:00:00,16:30:00,00:30:00,eight",
+            "2024-01-15,8 o'clock,16:30:00,00:30:00,8",
+            "2024-01-15,08:00:00,16:30:00,00:30:00,8,12:00:00",
+            "2024-01-15,08:00:00,16:30:00,00:30:00,8,,yes",
+        ] {
+            assert!(
+                matches!(parse_csv(line), Err(ParseError::MalformedRecord { .. })),
+                "{}",
+                line
+            );
+        }
+    }
+}
@@ -0,0 +1,293 @@
+/*! Dashboard of the tui subcommand: today's sessions and breaks from the clock file as a bar, the
+ * time left or the overtime, and the days of the week against their goals.  Keys clock in and out
+ * like the start, stop, and break subcommands:
+ *
+ *     s  start or stop work
+ *     b  start or end a break
+ *     q  quit
+ */
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Weekday};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Gauge, Paragraph};
+use ratatui::Frame;
+use std::path::PathBuf;
+use time_rust::i18n::{tr, trf};
+use time_rust::{events, format_duration_short, timesheet, Summary, WORKDAYS};
+
+pub struct Dashboard {
+    clock_file: PathBuf,
+    /// Timesheet of the days before today in the week view
+    timesheet: Option<PathBuf>,
+    goal: Box<dyn Fn(NaiveDate) -> Duration>,
+    /// Outcome of the last key, shown at the bottom
+    message: String,
+}
+
+type Segment = (DateTime<Local>, Option<DateTime<Local>>);
+
+impl Dashboard {
+    pub fn new(
+        clock_file: PathBuf,
+        timesheet: Option<PathBuf>,
+        goal: Box<dyn Fn(NaiveDate) -> Duration>,
+    ) -> Dashboard {
+        Dashboard {
+            clock_file,
+            timesheet,
+            goal,
+            message: tr("s: start/stop work, b: start/end break, q: quit").to_owned(),
+        }
+    }
+
+    fn today(&self, now: DateTime<Local>) -> Vec<events::Event> {
+        if self.clock_file.exists() {
+            // A clock file broken while the dashboard runs shows as an empty day
+            events::read(&self.clock_file)
+                .map(|events| events::on(events, now.date_naive()))
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /** Clock in or out unless already in that state, and say what happened
+     */
+    fn clock(&mut self, kind: events::Kind, done: &'static str, now: DateTime<Local>) {
+        // Back from a break, the work goes on with the tag from before it
+        let tag = match done {
+            "Break ended" => crate::last_tag(&self.clock_file),
+            _ => Ok(None),
+        };
+        let clocked =
+            tag.and_then(|tag| crate::clock_event(&self.clock_file, kind, now, tag.as_deref()));
+        self.message = match clocked {
+            Ok(true) => trf("{} at {}.", &[&tr(done), &now.format("%H:%M:%S")]),
+            Ok(false) if kind == events::Kind::Start => tr("Already clocked in.").to_owned(),
+            Ok(false) => tr("Not clocked in.").to_owned(),
+            Err(e) => trf("Error: {}", &[&e]),
+        };
+    }
+
+    fn key(&mut self, code: KeyCode) -> bool {
+        let now = Local::now();
+        let running = events::is_running(&self.today(now));
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('s') if running => self.clock(events::Kind::Stop, "Clocked out", now),
+            KeyCode::Char('s') => self.clock(events::Kind::Start, "Clocked in", now),
+            KeyCode::Char('b') if running => self.clock(events::Kind::Stop, "Break started", now),
+            KeyCode::Char('b') => self.clock(events::Kind::Start, "Break ended", now),
+            _ => {}
+        }
+        true
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let now = Local::now();
+        let today = self.today(now);
+        let segments = events::segments(&today);
+        let summary = crate::clock_summary(&segments, now, (self.goal)(now.date_naive()));
+        let [title, timeline, gauge, week, message] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(9),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let state = tr(match (events::is_running(&today), segments.is_empty()) {
+            (true, _) => "working",
+            (false, true) => "not clocked in",
+            (false, false) => "on a break or done",
+        });
+        frame.render_widget(
+            Paragraph::new(format!("{}  {}", now.format("%a %Y-%m-%d %H:%M:%S"), state)),
+            title,
+        );
+
+        let width = timeline.width.saturating_sub(2) as usize;
+        frame.render_widget(
+            Paragraph::new(timeline_bar(&segments, summary.as_ref(), now, width))
+                .block(Block::bordered().title(tr("Today"))),
+            timeline,
+        );
+
+        let (ratio, label) = match &summary {
+            Some(s) if s.done => (
+                1.0,
+                trf(
+                    "{} worked, {} overtime",
+                    &[
+                        &format_duration_short(&s.work_time),
+                        &format_duration_short(&s.delta),
+                    ],
+                ),
+            ),
+            Some(s) => (
+                (s.percent / 100.).clamp(0., 1.),
+                trf(
+                    "{} worked, {} left, goal at {}",
+                    &[
+                        &format_duration_short(&s.work_time),
+                        &format_duration_short(&-s.delta),
+                        &s.projected_goal.format("%H:%M"),
+                    ],
+                ),
+            ),
+            None => (0.0, tr("nothing worked yet").to_owned()),
+        };
+        frame.render_widget(
+            Gauge::default()
+                .block(Block::bordered().title(tr("Goal")))
+                .gauge_style(Style::default().fg(Color::Green))
+                .ratio(ratio)
+                .label(label),
+            gauge,
+        );
+
+        let width = week.width.saturating_sub(22) as usize;
+        frame.render_widget(
+            Paragraph::new(self.week_lines(now, summary.as_ref(), width))
+                .block(Block::bordered().title(tr("Week"))),
+            week,
+        );
+        frame.render_widget(Paragraph::new(self.message.as_str()), message);
+    }
+
+    /** One bar per workday, and per weekend day worked, the work of the day against its goal
+     */
+    fn week_lines(
+        &self,
+        now: DateTime<Local>,
+        today: Option<&Summary>,
+        width: usize,
+    ) -> Vec<Line<'static>> {
+        let records = self
+            .timesheet
+            .as_ref()
+            .and_then(|path| timesheet::read(path).ok())
+            .unwrap_or_default();
+        let week = now.date_naive().week(Weekday::Mon);
+        let days: Vec<(NaiveDate, Duration, Duration)> = week
+            .first_day()
+            .iter_days()
+            .take(7)
+            .map(|date| {
+                let work = match today {
+                    Some(s) if date == now.date_naive() => s.work_time,
+                    _ => timesheet::on(&records, date)
+                        .map(|r| r.work_time)
+                        .unwrap_or_else(Duration::zero),
+                };
+                (date, work, (self.goal)(date))
+            })
+            .filter(|&(date, work, _)| {
+                date.weekday().num_days_from_monday() < WORKDAYS as u32 || work > Duration::zero()
+            })
+            .collect();
+        let longest = days
+            .iter()
+            .map(|&(_, work, goal)| work.max(goal))
+            .max()
+            .filter(|longest| *longest > Duration::zero())
+            .unwrap_or_else(|| Duration::try_hours(8).unwrap());
+        let cells = |d: Duration| (d.num_seconds() * width as i64 / longest.num_seconds()) as usize;
+        let mut lines: Vec<Line> = days
+            .iter()
+            .map(|&(date, work, goal)| {
+                let (work_cells, goal_cells) = (cells(work), cells(goal));
+                let color = if work >= goal {
+                    Color::Green
+                } else {
+                    Color::Yellow
+                };
+                Line::from(vec![
+                    Span::raw(format!("{} ", date.format("%a"))),
+                    Span::styled("█".repeat(work_cells), Style::default().fg(color)),
+                    Span::raw("░".repeat(goal_cells.saturating_sub(work_cells))),
+                    Span::raw(" ".repeat(width.saturating_sub(work_cells.max(goal_cells)))),
+                    Span::raw(format!(
+                        " {:>6} / {:>5}",
+                        format_duration_short(&work),
+                        format_duration_short(&goal)
+                    )),
+                ])
+            })
+            .collect();
+        let (work, goal) = days.iter().fold(
+            (Duration::zero(), Duration::zero()),
+            |(w, g), &(_, work, goal)| (w + work, g + goal),
+        );
+        lines.push(Line::from(trf(
+            "total {} of {}",
+            &[&format_duration_short(&work), &format_duration_short(&goal)],
+        )));
+        lines
+    }
+}
+
+/** Today from the first start up to the projected goal or now, whichever is later: work, breaks,
+ * and the work still to go, one cell per slice of the day
+ */
+fn timeline_bar(
+    segments: &[Segment],
+    summary: Option<&Summary>,
+    now: DateTime<Local>,
+    width: usize,
+) -> Line<'static> {
+    let (Some(first), Some(s), true) = (segments.first(), summary, width > 0) else {
+        return Line::from("");
+    };
+    let from = first.0;
+    let to = s.end.unwrap_or(s.projected_goal.max(now));
+    let span = (to - from).max(Duration::try_minutes(1).unwrap());
+    let mut spans = vec![Span::raw(from.format("%H:%M ").to_string())];
+    let width = width.saturating_sub(12);
+    for i in 0..width {
+        let at = from + span * (2 * i as i32 + 1) / (2 * width as i32);
+        let working = segments
+            .iter()
+            .any(|&(start, end)| start <= at && at < end.unwrap_or(now));
+        let (cell, color) = if working {
+            ("█", Color::Green)
+        } else if at < segments.last().unwrap().1.unwrap_or(now) {
+            ("░", Color::Blue)
+        } else if s.end.is_none() {
+            ("·", Color::DarkGray)
+        } else {
+            (" ", Color::Reset)
+        };
+        spans.push(Span::styled(cell, Style::default().fg(color)));
+    }
+    spans.push(Span::raw(to.format(" %H:%M").to_string()));
+    Line::from(spans)
+}
+
+pub fn run(mut dashboard: Dashboard) -> std::io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = loop {
+        if let Err(e) = terminal.draw(|frame| dashboard.draw(frame)) {
+            break Err(e);
+        }
+        // Redraw every second for the live counters, at once on a key
+        match event::poll(std::time::Duration::from_secs(1)) {
+            Ok(false) => {}
+            Ok(true) => match event::read() {
+                Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                    if !dashboard.key(key.code) {
+                        break Ok(());
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => break Err(e),
+            },
+            Err(e) => break Err(e),
+        }
+    };
+    ratatui::restore();
+    result
+}
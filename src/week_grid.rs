@@ -0,0 +1,112 @@
+/*! ASCII overview of a week: one row per day with a bar of the work time against the daily goal
+ */
+use crate::timesheet::{self, Record};
+use crate::{fit, format_duration, weekday_name};
+use chrono::{Datelike, Duration, NaiveDate};
+
+/** Number of cells of a fully met daily goal, unless the width leaves less room
+ */
+pub const CELLS: usize = 20;
+
+/** Columns of a row besides the cells of the bar, as in "Mon 2024-01-15 [] 08:00:00"
+ */
+const LABELS: usize = 26;
+
+/** Cells filled in proportion to the goal met, all of them once it is met
+ */
+pub fn bar(work_time: Duration, goal: Duration, cells: usize) -> String {
+    let filled = if goal <= Duration::zero() {
+        cells
+    } else {
+        let ratio = work_time.num_seconds() as f64 / goal.num_seconds() as f64;
+        ((ratio * cells as f64).round().max(0.) as usize).min(cells)
+    };
+    format!("{}{}", "#".repeat(filled), ".".repeat(cells - filled))
+}
+
+/** Render `days` rows starting with the Monday of the week containing `date`, labeled in the
+ * language of `locale` and no wider than `width`; days without record stay empty
+ */
+pub fn render(
+    records: &[Record],
+    date: NaiveDate,
+    days: u32,
+    goal: Duration,
+    locale: &str,
+    width: usize,
+) -> String {
+    let cells = CELLS.min(width.saturating_sub(LABELS)).max(1);
+    let monday = date.week(chrono::Weekday::Mon).first_day();
+    let mut out = String::new();
+    for day in monday.iter_days().take(days as usize) {
+        let row = match timesheet::on(records, day) {
+            Some(record) => format!(
+                "[{}] {}",
+                bar(record.work_time, goal, cells),
+                format_duration(&record.work_time)
+            ),
+            None => format!("[{}]", " ".repeat(cells)),
+        };
+        let line = format!("{:<3} {} {}", weekday_name(day.weekday(), locale), day, row);
+        out.push_str(&fit(&line, width));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+    use std::collections::BTreeMap;
+
+    fn record(date: &str, hours: i64) -> Record {
+        Record {
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(8, 30, 0).unwrap() + Duration::try_hours(hours).unwrap(),
+            break_time: Duration::try_minutes(30).unwrap(),
+            work_time: Duration::try_hours(hours).unwrap(),
+            breaks: Vec::new(),
+            goal_met: false,
+            provisional: false,
+            tags: BTreeMap::new(),
+            zone: None,
+        }
+    }
+
+    #[test]
+    fn one_row_per_day() {
+        let records = [record("2024-01-15", 8), record("2024-01-17", 4)];
+        let wednesday = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap();
+        let out = render(
+            &records,
+            wednesday,
+            5,
+            Duration::try_hours(8).unwrap(),
+            "C",
+            80,
+        );
+        let rows: Vec<&str> = out.lines().collect();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[0], "Mon 2024-01-15 [####################] 08:00:00");
+        assert_eq!(rows[1], "Tue 2024-01-16 [                    ]");
+        assert_eq!(rows[2], "Wed 2024-01-17 [##########..........] 04:00:00");
+        assert!(rows[4].starts_with("Fri 2024-01-19 "));
+    }
+
+    #[test]
+    fn bar_in_proportion_to_the_goal() {
+        let hours = |h| Duration::try_hours(h).unwrap();
+        assert_eq!(bar(hours(2), hours(8), 4), "#...");
+        assert_eq!(bar(hours(10), hours(8), 4), "####");
+        assert_eq!(bar(hours(1), Duration::zero(), 4), "####");
+    }
+
+    #[test]
+    fn german_weekday_labels() {
+        let monday = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let out = render(&[], monday, 2, Duration::try_hours(8).unwrap(), "de_DE", 80);
+        assert!(out.starts_with("Mo  2024-01-15 [") && out.contains("\nDi  2024-01-16 ["));
+    }
+}
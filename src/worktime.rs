@@ -0,0 +1,136 @@
+/*! A working day as plain values, for tools that want the calculation without the command line:
+ *
+ *     let mut day = WorkDay::new(create_time("8:00")?, WorkGoal::Weekly(create_duration("39:00")?));
+ *     day.breaks.push(Break::parse("12:00-12:30")?);
+ *     println!("leave at {}", day.projected_end(Local::now()).time());
+ */
+use crate::error::ParseError;
+use crate::{parse_interval, summarize, weekly_share, Options, Summary};
+use chrono::{DateTime, Duration, Local};
+
+/** A single break with start and end
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Break {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+impl Break {
+    /** Parse <HH:MM[:SS]>-<HH:MM[:SS]>, as given to -b
+     */
+    pub fn parse(input: &str) -> Result<Break, ParseError> {
+        let (start, end) = parse_interval(input)?;
+        Ok(Break { start, end })
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+}
+
+/** Working-hour goal, either for the day or for the week spread over its work days
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WorkGoal {
+    Daily(Duration),
+    Weekly(Duration),
+}
+
+impl WorkGoal {
+    /** Goal of a single day
+     */
+    pub fn daily(&self) -> Duration {
+        match self {
+            WorkGoal::Daily(goal) => *goal,
+            WorkGoal::Weekly(goal) => weekly_share(*goal),
+        }
+    }
+}
+
+/** Start, end if already known, and breaks of a working day; without breaks the default break
+ * policy applies
+ */
+pub struct WorkDay {
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+    pub breaks: Vec<Break>,
+    pub goal: WorkGoal,
+    pub options: Options,
+}
+
+impl WorkDay {
+    pub fn new(start: DateTime<Local>, goal: WorkGoal) -> WorkDay {
+        WorkDay {
+            start,
+            end: None,
+            breaks: Vec::new(),
+            goal,
+            options: Options::default(),
+        }
+    }
+
+    /** Everything the command line tool prints, as of `now`
+     */
+    pub fn summary(&self, now: DateTime<Local>) -> Summary {
+        let breaks: Vec<_> = self.breaks.iter().map(|b| (b.start, b.end)).collect();
+        summarize(
+            now,
+            self.start,
+            self.start,
+            self.end,
+            self.goal.daily(),
+            &breaks,
+            &self.options,
+        )
+    }
+
+    /** Time worked by `now`, or by the end if there is one
+     */
+    pub fn work_time(&self, now: DateTime<Local>) -> Duration {
+        self.summary(now).work_time
+    }
+
+    /** Time left until the goal is done, zero from then on
+     */
+    pub fn remaining(&self, now: DateTime<Local>) -> Duration {
+        self.summary(now).remaining
+    }
+
+    /** Clock time at which the goal is done, breaks included
+     */
+    pub fn projected_end(&self, now: DateTime<Local>) -> DateTime<Local> {
+        self.summary(now).projected_goal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{create_duration, create_time};
+
+    #[test]
+    fn remaining_and_projected_end() {
+        let mut day = WorkDay::new(
+            create_time("8:00").unwrap(),
+            WorkGoal::Weekly(create_duration("39:00").unwrap()),
+        );
+        day.breaks.push(Break::parse("12:00-12:30").unwrap());
+        let now = create_time("12:30").unwrap();
+        assert_eq!(day.work_time(now), create_duration("4:00").unwrap());
+        assert_eq!(day.remaining(now), create_duration("3:48").unwrap());
+        assert_eq!(day.projected_end(now), create_time("16:18").unwrap());
+    }
+
+    #[test]
+    fn default_break_without_breaks() {
+        let day = WorkDay::new(
+            create_time("8:00").unwrap(),
+            WorkGoal::Daily(create_duration("8:00").unwrap()),
+        );
+        assert_eq!(
+            day.projected_end(create_time("9:00").unwrap()),
+            create_time("16:30").unwrap()
+        );
+    }
+}
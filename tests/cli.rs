@@ -0,0 +1,1075 @@
+//! Runs of the binary, for what only the command line puts together
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/** Output and exit status of a run in English and UTC, without the user's configuration
+ */
+fn run(args: &[&str]) -> (String, String, i32) {
+    run_in("UTC", args)
+}
+
+/** Output and exit status of a run in the local time zone `tz`
+ */
+fn run_in(tz: &str, args: &[&str]) -> (String, String, i32) {
+    run_with(&[("TZ", tz)], args)
+}
+
+/** Output and exit status of a run with these environment variables set on top
+ */
+fn run_with(vars: &[(&str, &str)], args: &[&str]) -> (String, String, i32) {
+    let output = Command::new(env!("CARGO_BIN_EXE_time_rust"))
+        .args(args)
+        .env("TZ", "UTC")
+        .env(
+            "XDG_CONFIG_HOME",
+            std::env::temp_dir().join("time_rust-no-config"),
+        )
+        .env("LANG", "C")
+        .env_remove("LC_ALL")
+        .env_remove("LC_MESSAGES")
+        .env_remove("TIME_RUST_DAILY_GOAL")
+        .env_remove("TIME_RUST_WEEKLY_GOAL")
+        .envs(vars.iter().copied())
+        .output()
+        .unwrap();
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+        output.status.code().unwrap_or(-1),
+    )
+}
+
+/** File of a test with the content, in a directory of its own
+ */
+fn fixture(test: &str, name: &str, content: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("time_rust-{}", std::process::id()))
+        .join(test);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+/** Raw value of a top-level key of a JSON object on one line, quotes and all
+ */
+fn value<'a>(json: &'a str, key: &str) -> &'a str {
+    let from = json.find(&format!("\"{}\":", key)).unwrap() + key.len() + 3;
+    let len = json[from..].find([',', '}']).unwrap_or(json.len() - from);
+    &json[from..from + len]
+}
+
+const HEADER: &str = "date,start,end,break_time,work_time,breaks,goal_met,provisional,tags,zone\n";
+
+#[test]
+fn fields_lists_work_time_and_remaining() {
+    let (out, _, code) = run(&["fields"]);
+    assert_eq!(code, 0);
+    assert!(out.lines().any(|line| line.starts_with("work_time ")));
+    assert!(out.lines().any(|line| line.starts_with("remaining ")));
+}
+
+#[test]
+fn json_percent_unrounded_text_rounded() {
+    let args = ["-s", "8:00", "-e", "15:30", "-d", "7:50", "--now", "16:00"];
+    let (json, _, _) = run(&[&args[..], &["--json"]].concat());
+    assert!(json.contains("\"percent\":89.3617021276"));
+    let (text, _, _) = run(&args);
+    assert!(text.contains("[7 -> 89.36 %]"));
+}
+
+#[test]
+fn rest_warning_after_a_late_shift() {
+    let csv = fixture(
+        "rest",
+        "log.csv",
+        &format!(
+            "{}2024-01-15,14:00:00,22:00:00,00:30:00,7.5,,true,false,,\n",
+            HEADER
+        ),
+    );
+    let (_, err, code) = run(&[
+        "-s",
+        "6:00",
+        "-e",
+        "14:00",
+        "--date",
+        "2024-01-16",
+        "--csv",
+        csv.to_str().unwrap(),
+        "--rest-check",
+    ]);
+    assert_eq!(code, 0);
+    assert!(err.contains(
+        "Warning: only 08:00:00 rest since the shift of 2024-01-15 ended at 22:00:00, at least \
+         11:00:00 required"
+    ));
+}
+
+#[test]
+fn friday_override_beats_weekly_share() {
+    let config = fixture(
+        "friday",
+        "config.toml",
+        "[goal-overrides]\nfriday = \"4:00\"\n",
+    );
+    let config = config.to_str().unwrap();
+    let day = |date| {
+        let args = [
+            "--config", config, "-w", "40:00", "-s", "8:00", "-e", "12:00",
+        ];
+        run(&[&args[..], &["--date", date, "--json"]].concat()).0
+    };
+    assert!(day("2024-01-19").contains("\"goal_seconds\":14400"));
+    assert!(day("2024-01-18").contains("\"goal_seconds\":28800"));
+}
+
+#[test]
+fn shown_start_is_the_actual_one_remaining_from_the_clamped_one() {
+    let (out, err, _) = run(&[
+        "-s",
+        "6:00",
+        "-e",
+        "15:00",
+        "--min-start",
+        "7:00",
+        "--min-start-applies-only-to-clockin",
+        "--now",
+        "16:00",
+    ]);
+    assert!(err.contains("counting from 07:00:00"));
+    assert!(out.contains("start: 06:00:00;"));
+    // 8:00 less the break from 7:00; 8:30 and 0:42 more from 6:00
+    assert!(out.contains("already done: 07:30:00"));
+    assert!(out.contains("00:18:00 [0.3] remaining"));
+}
+
+#[test]
+fn countdown_projections_at_a_fixed_now() {
+    let at = |now| run(&["-s", "8:00", "--now", now, "--projections", "countdown"]).0;
+    assert!(at("12:00").contains("7.8h: in 04:18:00, 9h: in 05:45:00, 10h: in 06:45:00"));
+    assert!(at("17:50").contains("7.8h: passed, 9h: passed, 10h: in 00:55:00"));
+}
+
+#[test]
+fn break_in_core_hours_warns_of_coverage() {
+    let (_, err, _) = run(&[
+        "-s",
+        "8:00",
+        "-e",
+        "17:00",
+        "-b",
+        "12:00-12:30",
+        "--core",
+        "10:00-15:00",
+    ]);
+    assert_eq!(
+        err,
+        "Warning: core hours 10:00:00-15:00:00 not covered from 12:00:00 to 12:30:00\n"
+    );
+}
+
+#[test]
+fn locale_separator_reads_durations_as_clock_times() {
+    let args = [
+        "-s",
+        "09.30",
+        "-e",
+        "17.30",
+        "--break-total",
+        "0.45",
+        "-d",
+        "7.30",
+        "--json",
+    ];
+    let (out, _, _) = run(&[&args[..], &["--locale", "fi_FI", "--locale-parse"]].concat());
+    assert!(out.contains("T09:30:00"));
+    assert!(out.contains("T17:30:00"));
+    assert!(out.contains("\"goal_seconds\":27000,\"break_time_seconds\":2700"));
+    // Without it a single dot is decimal hours
+    let (out, _, _) = run(&args);
+    assert!(out.contains("\"goal_seconds\":26280,\"break_time_seconds\":1620"));
+}
+
+#[test]
+fn summarize_totals_three_records_and_an_empty_file() {
+    let csv = [
+        HEADER,
+        "2024-01-15,08:00:00,16:30:00,00:30:00,8,12:00:00-12:30:00,true,false,,\n",
+        "2024-01-16,08:00:00,15:45:00,00:45:00,7,12:00:00-12:45:00,false,false,,\n",
+        "2024-01-17,07:30:00,17:00:00,00:30:00,9,12:00:00-12:30:00,true,false,,\n",
+    ]
+    .concat();
+    let path = fixture("summarize-three", "log.csv", &csv);
+    let (out, _, _) = run(&["summarize", "--csv", path.to_str().unwrap(), "--json"]);
+    assert_eq!(
+        out,
+        "{\"count\":3,\"total_work_seconds\":86400,\"total_break_seconds\":6300,\
+         \"average_work_seconds\":28800,\"min_work_seconds\":25200,\"max_work_seconds\":32400}\n"
+    );
+
+    let path = fixture("summarize-empty", "log.csv", "");
+    let (out, _, _) = run(&["summarize", "--csv", path.to_str().unwrap(), "--json"]);
+    assert_eq!(
+        out,
+        "{\"count\":0,\"total_work_seconds\":0,\"total_break_seconds\":0,\
+         \"average_work_seconds\":0,\"min_work_seconds\":0,\"max_work_seconds\":0}\n"
+    );
+}
+
+#[test]
+fn break_not_yet_realized_counts_now_but_not_for_the_projections() {
+    let at = |realized| {
+        let args = [
+            "-s",
+            "8:00",
+            "--now",
+            "14:00",
+            "--break-realized",
+            realized,
+            "--json",
+        ];
+        run(&args).0
+    };
+    let (assume, not_yet) = (at("assume"), at("not-yet"));
+    assert!(assume.contains("\"work_time_seconds\":19800,"));
+    assert!(not_yet.contains("\"work_time_seconds\":21600,"));
+    let projections = |out: &str| out[out.find("\"projected_goal\"").unwrap()..].to_owned();
+    assert_eq!(projections(&assume), projections(&not_yet));
+    assert!(projections(&assume).contains("T16:18:00"));
+}
+
+#[test]
+fn now_from_each_time_source() {
+    let now = |source| {
+        let (out, _, _) = run_in(
+            "Asia/Tokyo",
+            &["-s", "8:00", "--time-source", source, "--json"],
+        );
+        let now = &out["{\"now\":\"".len()..];
+        now[..now.find('"').unwrap()].to_owned()
+    };
+    assert!(now("local").ends_with("+09:00"));
+    assert!(now("utc").ends_with('Z'));
+    assert!(now("fixed:12:00").ends_with("T12:00:00+09:00"));
+}
+
+#[test]
+fn early_start_writes_an_audit_note() {
+    let path = fixture("early-start", "audit.jsonl", "");
+    let log = path.to_str().unwrap();
+    let args = [
+        "-s",
+        "5:30",
+        "-e",
+        "14:00",
+        "--min-start",
+        "6:00",
+        "--audit-log",
+        log,
+    ];
+    let (_, err, _) = run(&[&args[..], &["--date", "2024-01-15"]].concat());
+    assert!(err
+        .contains("Start 05:30:00 is before the earliest allowed start, counting from 06:00:00."));
+    let note = std::fs::read_to_string(&path).unwrap();
+    assert!(note.ends_with(
+        "\"date\":\"2024-01-15\",\"event\":\"early-clock-in\",\
+         \"actual\":\"05:30:00\",\"clamped\":\"06:00:00\"}\n"
+    ));
+}
+
+#[test]
+fn compared_to_yesterday_in_the_timesheet() {
+    let csv = [
+        HEADER,
+        "2024-01-15,08:00:00,16:00:00,00:30:00,7.5,12:00:00-12:30:00,false,false,,\n",
+    ]
+    .concat();
+    let path = fixture("compare-to-yesterday", "log.csv", &csv);
+    let csv = path.to_str().unwrap();
+    let on = |date| {
+        let args = [
+            "-s",
+            "8:00",
+            "-e",
+            "17:00",
+            "--compare-to-yesterday",
+            "--csv",
+            csv,
+        ];
+        run(&[&args[..], &["--date", date]].concat()).0
+    };
+    assert!(on("2024-01-16").contains("compared to yesterday: +01:00:00 [yesterday 07:30:00]"));
+    assert!(on("2024-01-18").contains("compared to yesterday: no record of 2024-01-17"));
+}
+
+#[test]
+fn break_total_feeds_work_time() {
+    let args = [
+        "-s",
+        "8:00",
+        "-e",
+        "17:00",
+        "--break-total",
+        "1:00",
+        "--break-count",
+        "3",
+    ];
+    let (out, _, _) = run(&[&args[..], &["--json"]].concat());
+    assert!(out.contains(
+        "\"break_time_seconds\":3600,\"longest_break_seconds\":1200,\
+         \"longest_break_estimated\":true,\"work_time_seconds\":28800,"
+    ));
+    let (out, _, _) = run(&args);
+    assert!(out.contains("longest break: 00:20:00 (estimated)"));
+}
+
+#[test]
+fn help_markdown_has_a_section_for_starttime() {
+    let (out, _, _) = run(&["help-markdown"]);
+    assert!(out.starts_with("# time_rust\n"));
+    assert!(out.contains("\n## `starttime` (-s, --start)\n\nTime when work started"));
+}
+
+#[test]
+fn break_in_core_hours_flagged() {
+    let with_break = |interval| {
+        let args = [
+            "-s",
+            "8:00",
+            "-e",
+            "17:00",
+            "-b",
+            interval,
+            "--core",
+            "10:00-15:00",
+        ];
+        run(&[&args[..], &["--no-break-in-core"]].concat())
+    };
+    let (_, err, status) = with_break("12:00-12:30");
+    assert!(
+        err.contains("Warning: break 12:00:00-12:30:00 lies within core hours 10:00:00-15:00:00\n")
+    );
+    assert_eq!(status, 0);
+    let (_, err, _) = with_break("16:00-16:30");
+    assert!(!err.contains("lies within core hours"));
+}
+
+#[test]
+fn stray_before_first_after_is_ignored() {
+    let events = "05:55 start\n05:56 stop\n08:00 start\n12:00 stop\n12:30 start\n16:30 stop\n";
+    let path = fixture("start-from", "events", events);
+    let events = path.to_str().unwrap();
+    let (out, _, _) = run(&[
+        "--events",
+        events,
+        "--start-from",
+        "first-after",
+        "6:00",
+        "--json",
+    ]);
+    assert!(value(&out, "clock_in").ends_with("T08:00:00Z\""));
+    assert_eq!(value(&out, "work_time_seconds"), "28800");
+    let (out, _, _) = run(&["--events", events, "--json"]);
+    assert!(value(&out, "clock_in").ends_with("T05:55:00Z\""));
+}
+
+#[test]
+fn pretty_json_spans_lines_compact_json_does_not() {
+    let args = ["-s", "8:00", "-e", "16:00"];
+    let (pretty, _, _) = run(&[&args[..], &["--json-pretty"]].concat());
+    let (compact, _, _) = run(&[&args[..], &["--json"]].concat());
+    assert!(pretty.starts_with("{\n  \"now\": "));
+    assert!(pretty.lines().count() > 2);
+    assert_eq!(compact.lines().count(), 1);
+    assert!(compact.starts_with("{\"now\":"));
+}
+
+#[test]
+fn past_the_cap_reports_it_exceeded() {
+    let at = |now| run(&["-s", "7:00", "--now", now]).0;
+    assert!(at("18:00").contains("; cap exceeded by 00:15:00 [0.25]\n"));
+    assert!(at("17:00").contains("; no longer than 00:45:00 [0.75]\n"));
+}
+
+#[test]
+fn list_state_filters_by_date() {
+    let csv = [
+        HEADER,
+        "2024-01-15,08:00:00,16:30:00,00:30:00,8,,true,false,,\n",
+        "2024-01-16,08:00:00,15:45:00,00:45:00,7,,false,false,,\n",
+        "2024-01-17,07:30:00,17:00:00,00:30:00,9,,true,false,,\n",
+        "2024-01-18,07:30:00,16:00:00,00:30:00,8,,true,false,,\n",
+    ]
+    .concat();
+    let path = fixture("list-state", "log.csv", &csv);
+    let csv = path.to_str().unwrap();
+    let (out, _, _) = run(&[
+        "list-state",
+        "--csv",
+        csv,
+        "--from",
+        "2024-01-16",
+        "--to",
+        "2024-01-17",
+    ]);
+    assert_eq!(
+        out,
+        "date        start     end       break     work\n\
+         2024-01-16  08:00:00  15:45:00  00:45:00  07:00:00\n\
+         2024-01-17  07:30:00  17:00:00  00:30:00  09:00:00\n"
+    );
+    let (out, _, _) = run(&["list-state", "--csv", csv, "--from", "2024-01-17"]);
+    assert_eq!(out.lines().count(), 3);
+}
+
+#[test]
+fn solved_break_of_a_nine_hour_span() {
+    let solve = |end| run(&["-s", "8:00", "-e", end, "-d", "8:00", "--solve-break"]).0;
+    assert_eq!(
+        solve("17:00"),
+        "break for exactly 08:00:00 of work: 01:00:00\n"
+    );
+    assert_eq!(
+        solve("15:00"),
+        "Impossible: 07:00:00 between start and end is less than the goal of 08:00:00.\n"
+    );
+}
+
+#[test]
+fn a_minute_short_within_tolerance_is_done() {
+    let args = ["-s", "8:00", "-e", "16:29", "-d", "8:00"];
+    let tolerant = [&args[..], &["--goal-tolerance", "0:05"]].concat();
+    let (out, _, _) = run(&[&tolerant[..], &["--json"]].concat());
+    assert_eq!(value(&out, "done"), "true");
+    let (out, _, _) = run(&tolerant);
+    assert!(out.contains("; 00:01:00 [0.02] short, within tolerance;"));
+    let (_, _, status) = run(&[&tolerant[..], &["--exit-status"]].concat());
+    assert_eq!(status, 0);
+    let (out, _, _) = run(&[&args[..], &["--json"]].concat());
+    assert_eq!(value(&out, "done"), "false");
+}
+
+#[test]
+fn explanation_names_the_default_break_and_threshold() {
+    let explain = |end| {
+        let (out, err, _) = run(&["-s", "8:00", "-e", end, "--explain-breaks"]);
+        out + &err
+    };
+    assert!(explain("16:30").contains(
+        "Break: no breaks given, 08:30:00 is below the threshold of 09:30:00: default break of 00:30:00\n"
+    ));
+    assert!(explain("18:30").contains(
+        "Break: no breaks given, 10:30:00 reaches the threshold of 09:30:00: default break of 00:45:00\n"
+    ));
+}
+
+#[test]
+fn three_short_breaks_fail_a_continuous_one() {
+    let with = |breaks: &[&str]| {
+        let mut args = vec![
+            "-s",
+            "8:00",
+            "-e",
+            "17:00",
+            "--require-continuous-break",
+            "0:30",
+        ];
+        for interval in breaks {
+            args.extend(["-b", interval]);
+        }
+        run(&args).1
+    };
+    assert!(with(&["10:00-10:10", "12:00-12:10", "15:00-15:10"]).contains(
+        "Warning: no continuous break of 00:30:00: the longest is 00:10:00, 00:30:00 in total but \
+         split into pieces\n"
+    ));
+    assert!(!with(&["12:00-12:30"]).contains("no continuous break"));
+}
+
+#[test]
+fn half_the_goal_in_goals() {
+    let at = |goal| run(&["-s", "8:00", "--now", "12:30", "-d", goal, "--goal-unit"]).0;
+    assert!(at("8:00").contains("in goals: 0.5 done; 0.5 remaining\n"));
+    assert!(at("0:00").contains("in goals: no daily goal\n"));
+}
+
+#[test]
+fn start_given_as_time_before_now() {
+    let (out, _, _) = run(&["-s", "-3:00", "--now", "12:00", "--json"]);
+    assert!(value(&out, "start").ends_with("T09:00:00Z\""));
+    let (out, _, _) = run(&["-s", "-1:30", "--now", "12:00"]);
+    assert!(out.contains("start: 10:30:00;"));
+}
+
+#[test]
+fn weekly_goal_from_the_environment_shown_as_env() {
+    let (_, err, _) = run_with(
+        &[("TIME_RUST_WEEKLY_GOAL", "38:00")],
+        &["-s", "8:00", "--now", "12:00", "--show-config-sources"],
+    );
+    assert!(err.contains("\nweekly-goal = 38:00 (env)\n"));
+    assert!(err.contains("\nstarttime = 8:00 (cli)\n"));
+    assert!(err.contains("\nresolved daily goal = 07:36:00 (weekly goal / 5)\n"));
+}
+
+#[test]
+fn start_snapped_to_the_schedule() {
+    let path = fixture(
+        "schedule",
+        "schedule.toml",
+        "[monday]\nstart = \"8:00\"\nend = \"16:30\"\n",
+    );
+    let schedule = path.to_str().unwrap();
+    let on_monday = |start| {
+        let args = [
+            "-s",
+            start,
+            "-e",
+            "16:30",
+            "--date",
+            "2024-01-15",
+            "--schedule",
+            schedule,
+        ];
+        run(&args)
+    };
+    let (out, err, _) = on_monday("8:07");
+    assert!(out.contains("start: 08:00:00; end: 16:30:00;"));
+    assert!(!err.contains("deviates"));
+    let (out, err, _) = on_monday("8:20");
+    assert!(out.contains("start: 08:20:00;"));
+    assert!(
+        err.contains("Warning: start 08:20:00 deviates from the scheduled 08:00:00 by +00:20:00")
+    );
+}
+
+#[test]
+fn overtime_only_with_the_week_so_far() {
+    let path = fixture("overtime-only", "state.json", "{}");
+    let state = path.to_str().unwrap();
+    let day = |date, end| {
+        [
+            "-s", "8:00", "-e", end, "-d", "8:00", "--date", date, "--state", state,
+        ]
+    };
+    run(&day("2024-01-15", "17:00"));
+    let (out, _, _) = run(&[&day("2024-01-16", "17:30")[..], &["--overtime-only"]].concat());
+    // 9:30 present with the long break of 0:45
+    assert_eq!(out, "today: +00:45:00\nweek:  +01:15:00\n");
+}
+
+#[test]
+fn hook_command_line_printed_past_the_goal() {
+    let at = |now| {
+        let args = ["-s", "8:00", "-d", "8:00", "--now", now, "--short"];
+        let hook = [
+            "--on-goal",
+            "notify-send 'done {worked}, ends {end}'",
+            "--dry-run-hook",
+        ];
+        run(&[&args[..], &hook].concat()).0
+    };
+    assert!(at("16:45").ends_with("\nnotify-send 'done 8:15, ends 16:30'\n"));
+    assert!(!at("16:00").contains("notify-send"));
+}
+
+#[test]
+fn two_accumulated_runs_sum_up() {
+    let path = fixture("accumulate", "log.csv", "");
+    let log = path.to_str().unwrap();
+    let run_of = |start, end, interval| {
+        let args = ["-s", start, "-e", end, "-b", interval, "-d", "8:00"];
+        run(&[
+            &args[..],
+            &["--date", "2024-01-15", "--log", log, "--accumulate"],
+        ]
+        .concat())
+        .0
+    };
+    run_of("8:00", "12:00", "10:00-10:15");
+    let out = run_of("13:00", "17:00", "15:00-15:15");
+    assert!(out.contains("accumulated today: 07:30:00 [7.5]"));
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        [
+            HEADER,
+            "2024-01-15,08:00:00,17:00:00,00:30:00,7.5,10:00:00-10:15:00 15:00:00-15:15:00,\
+             false,false,,UTC\n"
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn stray_half_minute_segment_filtered() {
+    let events = "08:00 start\n08:00:30 stop\n09:00 start\n12:00 stop\n12:30 start\n17:30 stop\n";
+    let path = fixture("min-segment", "events", events);
+    let events = path.to_str().unwrap();
+    let (out, err, _) = run(&["--events", events, "--min-segment", "0:01", "--json"]);
+    assert!(err.contains("Discarded 1 work segment(s) shorter than 00:01:00."));
+    assert!(value(&out, "start").ends_with("T09:00:00Z\""));
+    assert_eq!(value(&out, "work_time_seconds"), "28800");
+    let (out, _, _) = run(&["--events", events, "--json"]);
+    assert_eq!(value(&out, "work_time_seconds"), "28830");
+}
+
+#[test]
+fn goal_derived_from_three_days() {
+    let days = [
+        "2024-01-15,08:00:00,16:30:00,00:30:00,8,,true,false,,\n",
+        "2024-01-16,08:00:00,15:45:00,00:45:00,7,,false,false,,\n",
+        "2024-01-17,07:30:00,17:00:00,00:30:00,9,,true,false,,\n",
+    ];
+    let goal_after = |test, days: &[&str]| {
+        let path = fixture(test, "log.csv", &[&[HEADER][..], days].concat().concat());
+        let csv = path.to_str().unwrap();
+        let args = [
+            "-s",
+            "8:00",
+            "-e",
+            "16:00",
+            "--date",
+            "2024-01-18",
+            "--csv",
+            csv,
+        ];
+        run(&[&args[..], &["--goal", "from-history", "--json"]].concat())
+    };
+    let (out, err, _) = goal_after("history-three", &days);
+    assert!(err.contains("Goal derived as the median of 3 logged days: 08:00:00."));
+    assert_eq!(value(&out, "goal_seconds"), "28800");
+    let (out, err, _) = goal_after("history-two", &days[..2]);
+    assert!(err.contains(
+        "Only 2 logged day(s), at least 3 needed to derive the goal; using the default."
+    ));
+    assert_eq!(value(&out, "goal_seconds"), "28080");
+}
+
+#[test]
+fn two_policies_give_different_work_time() {
+    let (out, _, status) = run(&[
+        "-s",
+        "8:00",
+        "-e",
+        "13:00",
+        "--compare-policies",
+        "de-default,flat30",
+    ]);
+    assert_eq!(status, 0);
+    assert_eq!(
+        out,
+        "policy      work      remaining\n\
+         de-default  05:00:00  02:48:00\n\
+         flat30      04:30:00  03:18:00\n"
+    );
+    let (_, err, status) = run(&[
+        "-s",
+        "8:00",
+        "-e",
+        "13:00",
+        "--compare-policies",
+        "none,flat",
+    ]);
+    assert_eq!(status, 2);
+    assert!(err.contains(
+        "unknown break policy 'flat', expected default, de-default, none, or flat<minutes>"
+    ));
+}
+
+#[test]
+fn leave_times_of_a_partly_worked_week() {
+    // 1:30 overtime on Monday and Tuesday, spread over the rest of the week
+    let log = fixture(
+        "week-target-leave",
+        "log.csv",
+        &[
+            HEADER,
+            "2024-01-15,07:00:00,16:30:00,00:30:00,9,12:00:00-12:30:00,true,false,,\n",
+            "2024-01-16,07:00:00,16:00:00,00:30:00,8.5,12:00:00-12:30:00,true,false,,\n",
+        ]
+        .concat(),
+    );
+    let config = fixture(
+        "week-target-leave-config",
+        "config.toml",
+        "[goal-overrides]\nthursday = \"9:30\"\nfriday = \"4:00\"\n",
+    );
+    // Credited twice in the flextime account, which must not shorten the days left
+    let state = fixture(
+        "week-target-leave-state",
+        "state.json",
+        "{\"days\":{\"2024-01-15\":7200,\"2024-01-16\":3600}}",
+    );
+    let args = [
+        "--config",
+        config.to_str().unwrap(),
+        "-s",
+        "6:30",
+        "--min-start",
+        "7:00",
+        "-w",
+        "40:00",
+        "--date",
+        "2024-01-17",
+        "--now",
+        "9:00",
+        "--overtime-factor",
+        "2",
+        "--state",
+        state.to_str().unwrap(),
+    ];
+    let (out, _, _) = run(&[
+        &args[..],
+        &["--week-target-leave", "--log", log.to_str().unwrap()],
+    ]
+    .concat());
+    // Thursday's 9:00 of work is 9:45 present, past the threshold of the longer break
+    assert_eq!(
+        out,
+        "Wed 2024-01-17 leave at 15:00:00 [07:30:00 of work]\n\
+         Thu 2024-01-18 leave at 16:45:00 [09:00:00 of work]\n\
+         Fri 2024-01-19 leave at 11:00:00 [03:30:00 of work]\n"
+    );
+}
+
+#[test]
+fn unsorted_breaks_fail_only_when_required_sorted() {
+    let args = [
+        "-s",
+        "8:00",
+        "-e",
+        "17:00",
+        "-b",
+        "15:00-15:15",
+        "-b",
+        "12:00-12:30",
+    ];
+    let (out, err, status) = run(&[&args[..], &["--require-sorted-breaks"]].concat());
+    assert_eq!(status, 13);
+    assert!(out.is_empty());
+    assert_eq!(
+        err,
+        "Error: break 12:00-12:30 is out of order or overlaps the one before\n"
+    );
+    let (out, _, status) = run(&args);
+    assert_eq!(status, 0);
+    assert!(out.contains("total break time: 00:45:00; longest break: 00:30:00"));
+}
+
+#[test]
+fn status_against_three_targets() {
+    let path = fixture(
+        "targets",
+        "config.toml",
+        "[targets.layered]\nminimum = \"6:00\"\ncontractual = \"7:48\"\nstretch = \"9:00\"\n",
+    );
+    let args = [
+        "--config",
+        path.to_str().unwrap(),
+        "-s",
+        "8:00",
+        "-e",
+        "15:00",
+    ];
+    let (out, _, status) = run(&[&args[..], &["--targets", "layered"]].concat());
+    assert_eq!(status, 0);
+    assert!(out.ends_with(
+        "           target minimum: 06:00:00 met\n\
+         \x20          target contractual: 07:48:00 01:18:00 remaining\n\
+         \x20          target stretch: 09:00:00 02:30:00 remaining\n"
+    ));
+    let (out, err, status) = run(&[&args[..], &["--targets", "missing"]].concat());
+    assert_eq!(status, 7);
+    assert!(out.is_empty());
+    assert_eq!(err, "Error: no targets 'missing' in the config\n");
+}
+
+#[test]
+fn bar_and_table_stay_within_40_columns() {
+    let (out, _, _) = run(&["-s", "7:00", "--now", "17:30", "--bar", "--width", "40"]);
+    let bar = out.lines().last().unwrap();
+    assert_eq!(bar, "[##############] 100% overtime +01:57:00");
+    assert!(bar.chars().count() <= 40);
+
+    let csv = [
+        HEADER,
+        "2024-01-15,08:00:00,17:00:00,00:30:00,8.5,,true,false,,\n",
+    ]
+    .concat();
+    let path = fixture("narrow-list-state", "log.csv", &csv);
+    let (out, _, _) = run(&[
+        "list-state",
+        "--csv",
+        path.to_str().unwrap(),
+        "--width",
+        "40",
+    ]);
+    assert_eq!(
+        out,
+        "date        end       break     work\n\
+         2024-01-15  17:00:00  00:30:00  08:30:00\n"
+    );
+    assert!(out.lines().all(|line| line.chars().count() <= 40));
+}
+
+#[test]
+fn overnight_shift_spans_eight_hours() {
+    let (out, _, _) = run(&["-s", "22:00", "-e", "06:00", "-b", "23:30-00:15", "--json"]);
+    assert_eq!(value(&out, "break_time_seconds"), "2700");
+    // 8:00 present, not 16:00 the other way round
+    assert_eq!(value(&out, "work_time_seconds"), "26100");
+    let (start, end) = (value(&out, "start"), value(&out, "end"));
+    assert!(start.ends_with("T22:00:00Z\"") && end.ends_with("T06:00:00Z\""));
+    assert!(start < end);
+}
+
+#[test]
+fn default_break_is_the_longest_in_json_and_text() {
+    let args = ["-s", "8:00", "-e", "16:30"];
+    let (out, _, _) = run(&[&args[..], &["--json"]].concat());
+    assert_eq!(value(&out, "break_time_seconds"), "1800");
+    assert_eq!(value(&out, "longest_break_seconds"), "1800");
+    let (out, _, _) = run(&args);
+    assert!(out.contains("total break time: 00:30:00; longest break: 00:30:00\n"));
+}
+
+#[test]
+fn json_spellings_give_the_same_output() {
+    let args = ["-s", "8:00", "--now", "12:00"];
+    let (output, _, _) = run(&[&args[..], &["--output", "json"]].concat());
+    assert!(output.starts_with("{\"now\":"));
+    for spelling in [&["--json"][..], &["-f", "json"], &["--format", "json"]] {
+        assert_eq!(run(&[&args[..], spelling].concat()).0, output);
+    }
+}
+
+#[test]
+fn errors_exit_with_their_own_status() {
+    let (_, err, status) = run(&["-s", "8:00", "-e", "16:00", "--field", "bogus"]);
+    assert_eq!(err, "Error: unknown field 'bogus', see `fields`\n");
+    assert_eq!(status, 7);
+
+    let path = fixture("start-from-bogus", "events", "08:00 start\n16:00 stop\n");
+    let events = path.to_str().unwrap();
+    let (_, err, status) = run(&["--events", events, "--start-from", "last", "6:00"]);
+    assert!(err.starts_with("Error: unknown --start-from mode 'last'"));
+    assert_eq!(status, 7);
+
+    let csv = [HEADER, "2024-01-15,08:00:00,16:30:00,00:30:00,eight\n"].concat();
+    let path = fixture("summarize-malformed", "log.csv", &csv);
+    let (out, err, status) = run(&["summarize", "--csv", path.to_str().unwrap()]);
+    assert!(out.is_empty());
+    assert_eq!(
+        err,
+        "Error: malformed timesheet record '2024-01-15,08:00:00,16:30:00,00:30:00,eight': \
+         invalid work time 'eight'\n"
+    );
+    assert_eq!(status, 8);
+
+    let (_, _, status) = run(&["-s", "8:00", "-e", "12:00", "--exit-status"]);
+    assert_eq!(status, 1);
+}
+
+#[test]
+fn edit_an_end_into_a_provisional_record() {
+    let csv = [
+        HEADER,
+        "2024-06-03,08:00:00,12:00:00,00:30:00,3.5,,false,true,,UTC\n",
+    ]
+    .concat();
+    let path = fixture("edit-provisional", "log.csv", &csv);
+    let csv = path.to_str().unwrap();
+    let (_, err, status) = run(&["edit", "2024-06-03", "--csv", csv, "--set-end", "17:00"]);
+    assert_eq!((err.as_str(), status), ("", 0));
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        [
+            HEADER,
+            "2024-06-03,08:00:00,17:00:00,00:30:00,8.5,,true,false,,UTC\n"
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn malformed_clock_file_is_an_error() {
+    let path = fixture("clock-malformed", "events", "08:00 begin\n");
+    let (_, err, status) = run(&["start", "--clock-file", path.to_str().unwrap()]);
+    assert!(err.starts_with("Error: malformed clock event '08:00 begin'"));
+    assert_eq!(status, 8);
+}
+
+#[test]
+fn unknown_notification_rejected() {
+    let args = [
+        "-s",
+        "8:00",
+        "--watch",
+        "--notify",
+        "--notify-on",
+        "goal,bogus",
+    ];
+    let (_, err, status) = run(&args);
+    assert!(
+        err.contains("unknown value 'bogus' for notify-on, expected one of goal, 9h, max, break")
+    );
+    assert_eq!(status, 2);
+
+    let config = fixture(
+        "notify-on-bogus",
+        "config.toml",
+        "notify = true\nnotify-on = \"goal,bogus\"\n",
+    );
+    let (_, err, status) = run(&[
+        "--config",
+        config.to_str().unwrap(),
+        "-s",
+        "8:00",
+        "--watch",
+    ]);
+    assert!(err.ends_with(
+        "Error: unknown value 'bogus' for notify-on, expected one of goal, 9h, max, break\n"
+    ));
+    assert_eq!(status, 7);
+}
+
+#[test]
+fn unknown_export_column_rejected() {
+    let csv = [
+        HEADER,
+        "2024-01-15,08:00:00,16:30:00,00:30:00,8,,true,false,,\n",
+    ]
+    .concat();
+    let path = fixture("export-bogus-column", "log.csv", &csv);
+    let (out, err, status) = run(&[
+        "export",
+        "--csv",
+        path.to_str().unwrap(),
+        "--columns",
+        "date,foo",
+    ]);
+    assert!(out.is_empty());
+    assert_eq!(
+        err,
+        "Error: unknown value 'foo' for columns, expected one of date, weekday, start, end, \
+         break, breaks, net, hours, goal, delta, delta-hours\n"
+    );
+    assert_eq!(status, 7);
+}
+
+#[test]
+fn no_segment_after_start_from_is_an_error() {
+    let path = fixture("start-from-past", "events", "08:00 start\n16:00 stop\n");
+    let events = path.to_str().unwrap();
+    let (out, err, status) = run(&["--events", events, "--start-from", "first-after", "17:00"]);
+    assert!(out.is_empty());
+    assert_eq!(err, format!("Error: no work segment in {}\n", events));
+    assert_eq!(status, 10);
+}
+
+#[test]
+fn invalid_rounding_in_the_config_rejected() {
+    let csv = fixture("rounding-config-log", "log.csv", HEADER);
+    let csv = csv.to_str().unwrap();
+    let with = |test: &str, setting: &str| {
+        let config = fixture(test, "config.toml", &format!("{}\n", setting));
+        run(&["--config", config.to_str().unwrap(), "export", "--csv", csv])
+    };
+    let (_, err, status) = with("rounding-mode-config", "rounding-mode = \"sideways\"");
+    assert_eq!(
+        err,
+        "Error: unknown value 'sideways' for rounding-mode, expected one of up, down, nearest, favor\n"
+    );
+    assert_eq!(status, 7);
+    let (_, err, status) = with("rounding-to-config", "rounding-to = \"each\"");
+    assert_eq!(
+        err,
+        "Error: unknown value 'each' for rounding-to, expected one of total, stamps\n"
+    );
+    assert_eq!(status, 7);
+    let (_, err, status) = with("rounding-config", "rounding = \"abc\"");
+    assert_eq!(err, "Error: 'rounding' in 'abc' is not a number\n");
+    assert_eq!(status, 3);
+}
+
+#[test]
+fn invalid_round_mode_and_region_in_the_config_rejected() {
+    let with = |test: &str, setting: &str| {
+        let config = fixture(test, "config.toml", &format!("{}\n", setting));
+        run(&[
+            "--config",
+            config.to_str().unwrap(),
+            "-s",
+            "8:00",
+            "-e",
+            "16:00",
+        ])
+    };
+    let (_, err, status) = with("round-mode-config", "round-mode = \"half-up\"");
+    assert_eq!(
+        err,
+        "Error: unknown value 'half-up' for round-mode, expected one of half-away, half-even\n"
+    );
+    assert_eq!(status, 7);
+    let (_, err, status) = with("holidays-config", "holidays = \"fr\"");
+    assert_eq!(
+        err,
+        "Error: unknown value 'fr' for holidays, expected one of de, none\n"
+    );
+    assert_eq!(status, 7);
+}
+
+#[test]
+fn daily_maximum_warning_in_german() {
+    let (_, err, _) = run(&[
+        "--lang",
+        "de",
+        "-s",
+        "6:00",
+        "-e",
+        "18:00",
+        "-b",
+        "12:00-12:30",
+    ]);
+    assert!(err.contains(
+        "Warnung: die Arbeitszeit von 11:30:00 überschreitet die Tageshöchstgrenze von 10:00:00 um 01:30:00"
+    ));
+}
+
+#[test]
+fn impossible_edits_exit_with_their_own_status() {
+    let csv = [
+        HEADER,
+        "2024-06-03,08:00:00,16:30:00,00:30:00,8,12:00:00-12:30:00,true,false,,UTC\n",
+    ]
+    .concat();
+    let path = fixture("edit-impossible", "log.csv", &csv);
+    let csv = path.to_str().unwrap();
+    let (_, err, status) = run(&["edit", "2024-06-04", "--csv", csv, "--set-end", "17:00"]);
+    assert_eq!(err, format!("Error: no record of 2024-06-04 in {}\n", csv));
+    assert_eq!(status, 11);
+    let (_, err, status) = run(&[
+        "edit",
+        "2024-06-03",
+        "--csv",
+        csv,
+        "--add-break",
+        "17:00-17:30",
+    ]);
+    assert_eq!(
+        err,
+        "Error: break 17:00:00-17:30:00 lies outside the working period\n"
+    );
+    assert_eq!(status, 11);
+}
+
+#[test]
+fn push_without_its_config_fails_with_its_own_status() {
+    let csv = fixture("push-unconfigured", "log.csv", HEADER);
+    let (_, err, status) = run(&["push", "toggl", "--csv", csv.to_str().unwrap()]);
+    assert_eq!(err, "Error: no [push.toggl] table in the config\n");
+    assert_eq!(status, 12);
+}